@@ -10,7 +10,8 @@ More detailed description, with
 use crate::amadeus::execute::ResponseMetadata;
 use crate::amadeus::GetRequest;
 use crate::config::AppConfig;
-use crate::itinerary::{AirCarrierCode, AirportCode, Date};
+use crate::itinerary::flight_details::AirportGate;
+use crate::itinerary::{AirCarrierCode, AirportCode, Date, FlightSegment, OperatingFlight};
 use serde::{Deserialize, Serialize};
 
 // ------------------------------------------------------------------------------------------------
@@ -174,6 +175,59 @@ impl GetRequest for Request {
     }
 }
 
+impl Response {
+    pub fn flights(&self) -> impl Iterator<Item = &DatedFlight> {
+        self.data.iter()
+    }
+}
+
+impl DatedFlight {
+    ///
+    /// Fill in the departure and arrival terminal and gate of `flight` from this schedule,
+    /// keeping any value already present that the schedule does not have.
+    ///
+    pub fn update_flight(&self, flight: &mut FlightSegment) {
+        if let Some(departure) = self
+            .flight_point(flight.departure_airport())
+            .and_then(|point| point.departure.as_ref())
+        {
+            flight.update_departure_gate(&departure.into());
+        }
+        if let Some(arrival) = flight
+            .arrival_airport()
+            .and_then(|airport| self.flight_point(airport))
+            .and_then(|point| point.arrival.as_ref())
+        {
+            flight.update_arrival_gate(&arrival.into());
+        }
+    }
+
+    fn flight_point(&self, airport: &AirportCode) -> Option<&FlightPoint> {
+        self.flight_points.iter().find(|v| &v.iata_code == airport)
+    }
+}
+
+impl From<&ArrivalOrDeparture> for AirportGate {
+    fn from(v: &ArrivalOrDeparture) -> Self {
+        AirportGate::new(
+            v.terminal.as_ref().map(|v| v.code.as_str()),
+            v.gate.as_ref().map(|v| v.main_gate.as_str()),
+        )
+    }
+}
+
+impl From<&FlightDesignator> for OperatingFlight {
+    fn from(v: &FlightDesignator) -> Self {
+        OperatingFlight::new(v.carrier_code.clone(), v.flight_number)
+    }
+}
+
+impl From<&Partnership> for OperatingFlight {
+    fn from(v: &Partnership) -> Self {
+        OperatingFlight::from(&v.operating_flight)
+    }
+}
+
 // ------------------------------------------------------------------------------------------------
 // Private Functions
 // ------------------------------------------------------------------------------------------------
@@ -181,3 +235,76 @@ impl GetRequest for Request {
 // ------------------------------------------------------------------------------------------------
 // Modules
 // ------------------------------------------------------------------------------------------------
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::Response;
+    use crate::itinerary::io::from_str;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_update_flight_gates() {
+        let response: Response = serde_json::from_str(
+            r##"{
+  "meta": { "count": 1, "links": {} },
+  "data": [{
+    "type": "DatedFlight",
+    "scheduledDepartureDate": "2022-06-24",
+    "flightDesignator": { "carrierCode": "AA", "flightNumber": 1471 },
+    "flightPoints": [
+      {
+        "iataCode": "SEA",
+        "departure": {
+          "timings": [{ "qualifier": "STD", "value": "2022-06-24T08:00-07:00" }],
+          "terminal": { "code": "1" },
+          "gate": { "mainGate": "C12" }
+        }
+      },
+      {
+        "iataCode": "DFW",
+        "arrival": {
+          "timings": [{ "qualifier": "STA", "value": "2022-06-24T16:40-05:00" }],
+          "terminal": { "code": "D" }
+        }
+      }
+    ]
+  }]
+}"##,
+        )
+        .unwrap();
+        let mut itinerary = from_str(
+            r##"---
+identifier: 2022-06
+name: Playa del Carmen
+travelers: []
+items:
+  - flight:
+      number: 1471
+      arrival_gate:
+        gate: D22
+      record:
+        provider: AA
+        start_date_time: "2022-06-24T08:00:00"
+        start_location: SEA
+        end_date_time: "2022-06-24T16:40:00"
+        end_location: DFW
+"##,
+        )
+        .unwrap();
+        let flight = itinerary.flights_mut().next().unwrap();
+        response.flights().next().unwrap().update_flight(flight);
+
+        assert_eq!(
+            flight.departure_gate().unwrap().to_string(),
+            "terminal 1, gate C12"
+        );
+        assert_eq!(
+            flight.arrival_gate().unwrap().to_string(),
+            "terminal D, gate D22"
+        );
+    }
+}
@@ -23,8 +23,13 @@ pub const APP_CONFIG_NAME: &str = "travelcli";
 
 pub const CONFIG_FILE_NAME: &str = "config.yml";
 
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub const LIBRARY_DIR_NAME: &str = "itineraries";
+
+pub const TEMPLATE_DIR_NAME: &str = "templates";
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum Stage {
+    #[default]
     Development,
     Test,
     Production,
@@ -97,6 +102,20 @@ pub fn get_app_config_path() -> PathBuf {
     }
 }
 
+///
+/// The directory holding the library of itinerary files, within the XDG data directory.
+///
+pub fn get_app_library_path() -> PathBuf {
+    get_app_data_path(LIBRARY_DIR_NAME)
+}
+
+///
+/// The directory holding itinerary templates, within the XDG data directory.
+///
+pub fn get_app_template_path() -> PathBuf {
+    get_app_data_path(TEMPLATE_DIR_NAME)
+}
+
 pub fn write_default_config() -> Result<(), crate::error::Error> {
     debug!("writing default config");
     let default_config = default_config_file();
@@ -116,12 +135,6 @@ pub fn write_default_config() -> Result<(), crate::error::Error> {
 // Implementations
 // ------------------------------------------------------------------------------------------------
 
-impl Default for Stage {
-    fn default() -> Self {
-        Self::Development
-    }
-}
-
 impl Display for Stage {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -189,6 +202,16 @@ impl AppConfig {
 // Private Functions
 // ------------------------------------------------------------------------------------------------
 
+fn get_app_data_path(dir_name: &str) -> PathBuf {
+    match xdirs::data_dir_for(APP_CONFIG_NAME) {
+        Some(p) => p.join(dir_name),
+        None => PathBuf::from(
+            shellexpand::tilde(&format!("~/.local/share/{}/{}", APP_CONFIG_NAME, dir_name))
+                .as_ref(),
+        ),
+    }
+}
+
 fn default_config_file() -> AppConfig {
     let mut app_config = AppConfig {
         services: Default::default(),
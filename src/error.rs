@@ -4,6 +4,90 @@ error_chain! {
             description("Invalid version number")
             display("Invalid version number: '{}'", v)
         }
+        InvalidTimeZone(v: String) {
+            description("Invalid time zone")
+            display("Invalid time zone: '{}'", v)
+        }
+        InvalidMoney(v: String) {
+            description("Invalid amount of money")
+            display("Invalid amount of money, expecting '<amount> <currency>': '{}'", v)
+        }
+        CurrencyMismatch(a: String, b: String) {
+            description("Amounts are in different currencies")
+            display("Amounts are in different currencies: '{}' and '{}'", a, b)
+        }
+        InvalidCabin(v: String) {
+            description("Invalid cabin")
+            display("Invalid cabin: '{}'", v)
+        }
+        InvalidItemStatus(v: String) {
+            description("Invalid item status")
+            display("Invalid item status: '{}'", v)
+        }
+        InvalidStatusTransition(from: String, to: String) {
+            description("Invalid item status transition")
+            display("An item's status may not change from {} to {}", from, to)
+        }
+        InvalidAttachment(v: String) {
+            description("Invalid attachment")
+            display("Attachments must be within the itinerary file's directory: '{}'", v)
+        }
+        UnknownItem(v: String) {
+            description("Unknown item")
+            display("No item with identifier '{}' in this itinerary", v)
+        }
+        UnknownTraveler(v: String) {
+            description("Unknown traveler")
+            display("No traveler named '{}' in this itinerary", v)
+        }
+        DuplicateItem(v: String) {
+            description("Duplicate item")
+            display("More than one item with identifier '{}' in this itinerary", v)
+        }
+        DuplicateTraveler(v: String) {
+            description("Duplicate traveler")
+            display("More than one traveler named '{}' in this itinerary", v)
+        }
+        UnknownItinerary(v: String) {
+            description("Unknown itinerary")
+            display("No itinerary with identifier '{}' in the library", v)
+        }
+        DuplicateItinerary(v: String, path: String) {
+            description("Duplicate itinerary")
+            display("The itinerary '{}' is already in the library, as '{}'", v, path)
+        }
+        JournalOutOfDate(v: String) {
+            description("Journal out of date")
+            display("The itinerary file '{}' has changed since its journal was last written", v)
+        }
+        UnknownBooking(v: String) {
+            description("Unknown booking")
+            display("No booking with confirmation '{}' in this itinerary", v)
+        }
+        DuplicateBooking(v: String) {
+            description("Duplicate booking")
+            display("More than one booking with confirmation '{}' in this itinerary", v)
+        }
+        TravelerInUse(v: String) {
+            description("Traveler in use")
+            display("Items in this itinerary refer to the traveler named '{}'", v)
+        }
+        ItemInUse(v: String, by: String) {
+            description("Item in use")
+            display("The item '{}' is linked to by item '{}'", v, by)
+        }
+        MissingValue(kind: String, field: String) {
+            description("Missing value")
+            display("A {} requires a value for '{}'", kind, field)
+        }
+        VisitorsFailed(v: Vec<String>) {
+            description("One or more visitors failed")
+            display("One or more visitors failed: {}", v.join("; "))
+        }
+        InvalidValue(kind: String, reason: String) {
+            description("Invalid value")
+            display("Invalid {}: {}", kind, reason)
+        }
     }
 
     foreign_links {
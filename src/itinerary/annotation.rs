@@ -0,0 +1,287 @@
+/*!
+Notes, tags, and file attachments that may be added to an itinerary or any of its items.
+
+Attachments, such as a PDF e-ticket, are stored as a path relative to the itinerary file so that
+the itinerary and its attachments can be moved together. Each also records a SHA-256 hash of the
+file's content so that a changed or replaced file can be detected.
+ */
+
+use crate::error::{ErrorKind, Result};
+use serde::{Deserialize, Deserializer, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Component, Path, PathBuf};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Free-form notes, in markdown, along with tags and attachments.
+///
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Annotations {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    notes: Option<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    attachments: Vec<Attachment>,
+}
+
+///
+/// A file attached to an itinerary or item.
+///
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Attachment {
+    #[serde(deserialize_with = "deserialize_relative_path")]
+    path: PathBuf,
+    sha256: String,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Annotations {
+    pub fn is_empty(&self) -> bool {
+        self.notes.is_none() && self.tags.is_empty() && self.attachments.is_empty()
+    }
+
+    pub fn notes(&self) -> Option<&String> {
+        self.notes.as_ref()
+    }
+
+    pub fn set_notes(&mut self, notes: &str) {
+        self.notes = Some(notes.to_string());
+    }
+
+    pub fn tags(&self) -> impl Iterator<Item = &String> {
+        self.tags.iter()
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|v| v == tag)
+    }
+
+    ///
+    /// Add `tag`, unless it is already present.
+    ///
+    pub fn add_tag(&mut self, tag: &str) {
+        if !self.has_tag(tag) {
+            self.tags.push(tag.to_string());
+        }
+    }
+
+    pub fn attachments(&self) -> impl Iterator<Item = &Attachment> {
+        self.attachments.iter()
+    }
+
+    pub fn add_attachment(&mut self, attachment: Attachment) {
+        self.attachments.push(attachment);
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Attachment {
+    ///
+    /// Create an attachment for `file`, which must be within the directory containing the
+    /// itinerary file `itinerary_file`. The content of `file` is read to compute its hash.
+    ///
+    pub fn new(itinerary_file: &Path, file: &Path) -> Result<Self> {
+        let base = base_directory(itinerary_file).canonicalize()?;
+        let path = file
+            .canonicalize()?
+            .strip_prefix(&base)
+            .map_err(|_| ErrorKind::InvalidAttachment(file.to_string_lossy().to_string()))?
+            .to_path_buf();
+        Ok(Self {
+            sha256: content_hash(file)?,
+            path,
+        })
+    }
+
+    ///
+    /// The path of the attached file, relative to the itinerary file.
+    ///
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    ///
+    /// The SHA-256 hash of the attached file's content, as a lower-case hex string.
+    ///
+    pub fn sha256(&self) -> &String {
+        &self.sha256
+    }
+
+    ///
+    /// The location of the attached file, given the location of the itinerary file. It is an
+    /// error if the attachment's path, or any link it follows, leads outside the directory
+    /// containing the itinerary file.
+    ///
+    pub fn resolve(&self, itinerary_file: &Path) -> Result<PathBuf> {
+        let invalid = || ErrorKind::InvalidAttachment(self.path.to_string_lossy().to_string());
+        if !is_relative_path(&self.path) {
+            return Err(invalid().into());
+        }
+        let base = base_directory(itinerary_file);
+        let resolved = base.join(&self.path);
+        if resolved.exists() && !resolved.canonicalize()?.starts_with(base.canonicalize()?) {
+            return Err(invalid().into());
+        }
+        Ok(resolved)
+    }
+
+    ///
+    /// Returns `true` if the attached file still has the content it had when it was attached.
+    ///
+    pub fn verify(&self, itinerary_file: &Path) -> Result<bool> {
+        Ok(content_hash(&self.resolve(itinerary_file)?)? == self.sha256)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn base_directory(itinerary_file: &Path) -> &Path {
+    match itinerary_file.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    }
+}
+
+///
+/// A path that stays below the directory it is relative to; it has no root, drive prefix, or
+/// parent (`..`) components.
+///
+fn is_relative_path(path: &Path) -> bool {
+    path.components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+}
+
+fn deserialize_relative_path<'de, D>(deserializer: D) -> std::result::Result<PathBuf, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let path = PathBuf::deserialize(deserializer)?;
+    if is_relative_path(&path) {
+        Ok(path)
+    } else {
+        Err(serde::de::Error::custom(format!(
+            "attachment path '{}' must be relative to, and within, the itinerary's directory",
+            path.display()
+        )))
+    }
+}
+
+fn content_hash(file: &Path) -> Result<String> {
+    let content = std::fs::read(file)?;
+    Ok(Sha256::digest(&content)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::{Annotations, Attachment};
+    use crate::itinerary::io::{from_str, to_string};
+    use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_annotations() {
+        let itinerary = from_str(
+            r##"---
+identifier: 2022-06
+name: Playa del Carmen
+notes: "Anniversary trip, *no* work email."
+tags:
+  - personal
+travelers:
+  - Simon
+items:
+  - accomodation:
+      notes: Request a ground-floor room.
+      tags:
+        - beach
+        - quiet
+      attachments:
+        - path: tickets/hotel.pdf
+          sha256: 2c26b46b68ffc68ff99b453c1d30413413422d706483bfa0f98a5e886266e7ae
+      record:
+        provider: Hotel Xcaret
+        start_date_time: "2022-06-24T16:00:00"
+        start_location: Playa del Carmen
+        end_date_time: "2022-07-01T11:00:00"
+"##,
+        )
+        .unwrap();
+        assert_eq!(
+            itinerary.annotations().notes(),
+            Some(&"Anniversary trip, *no* work email.".to_string())
+        );
+        assert!(itinerary.annotations().has_tag("personal"));
+
+        let annotations = itinerary.items().next().unwrap().common().annotations();
+        assert_eq!(annotations.tags().count(), 2);
+        assert!(annotations.has_tag("quiet"));
+        let attachment = annotations.attachments().next().unwrap();
+        assert_eq!(
+            attachment
+                .resolve(&PathBuf::from("trips/playa.yml"))
+                .unwrap(),
+            PathBuf::from("trips/tickets/hotel.pdf")
+        );
+        assert_eq!(
+            attachment.resolve(&PathBuf::from("playa.yml")).unwrap(),
+            PathBuf::from("./tickets/hotel.pdf")
+        );
+
+        assert_eq!(
+            from_str(&to_string(&itinerary).unwrap()).unwrap(),
+            itinerary
+        );
+        assert!(Annotations::default().is_empty());
+
+        for path in ["../secrets.pdf", "/etc/passwd", "tickets/../../secrets.pdf"] {
+            let yaml = format!(
+                "path: \"{}\"\nsha256: 2c26b46b68ffc68ff99b453c1d30413413422d706483bfa0f98a5e886266e7ae\n",
+                path
+            );
+            assert!(serde_yaml::from_str::<Attachment>(&yaml).is_err());
+        }
+    }
+
+    #[test]
+    fn test_attachment_hash() {
+        let dir = std::env::temp_dir().join(format!("tt-attachment-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("tickets")).unwrap();
+        let itinerary_file = dir.join("playa.yml");
+        let ticket = dir.join("tickets/hotel.pdf");
+        std::fs::write(&ticket, "foo").unwrap();
+
+        let attachment = Attachment::new(&itinerary_file, &ticket).unwrap();
+        assert_eq!(attachment.path(), &PathBuf::from("tickets/hotel.pdf"));
+        assert_eq!(
+            attachment.sha256(),
+            "2c26b46b68ffc68ff99b453c1d30413413422d706483bfa0f98a5e886266e7ae"
+        );
+        assert!(attachment.verify(&itinerary_file).unwrap());
+
+        std::fs::write(&ticket, "bar").unwrap();
+        assert!(!attachment.verify(&itinerary_file).unwrap());
+
+        assert!(Attachment::new(&dir.join("tickets/playa.yml"), &itinerary_file).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,513 @@
+/*!
+An asynchronous form of the itinerary visitor, for visitors that need to await on other services
+(such as the `amadeus` flight schedule API) as they go.
+
+An `AsyncItineraryVisitor` has the same callbacks, made in the same order, as
+`visitor::ItineraryVisitorMut`. In addition, it may `fetch` data for each item before the item
+is visited. `visit_itinerary_concurrently` instead takes a separate `ItemFetcher`, so that it
+can fetch for many items at once while still making every callback in order, and one at a time,
+as soon as the fetches it depends on are complete.
+ */
+
+use crate::error::Result;
+use crate::itinerary::annotation::Annotations;
+use crate::itinerary::filter::ItemFilter;
+use crate::itinerary::metadata::{Metadata, Summary};
+use crate::itinerary::traveler::Traveler;
+use crate::itinerary::visitor::{dispatch_visit, plan_visits, Gap, ItemEdge, Visit, VisitOrder};
+use crate::itinerary::{
+    Accomodation, Booking, Connection, Cruise, Date, Event, Flight, Item, Itinerary, Journey,
+    PortCall, Rail, RailChange, Transport, Vehicle,
+};
+use async_trait::async_trait;
+use futures::stream::{self, StreamExt};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+#[async_trait]
+#[allow(unused_variables)]
+pub trait AsyncItineraryVisitor: Send + Sync {
+    ///
+    /// Data fetched for an item before it is visited; use `()` if nothing is fetched.
+    ///
+    type Fetched: Send;
+
+    ///
+    /// Fetch data for `item`, such as the current schedule of a flight, before it is visited. The
+    /// value is passed to `item_fetched`.
+    ///
+    async fn fetch(&self, item: &Item) -> Result<Option<Self::Fetched>> {
+        Ok(None)
+    }
+
+    ///
+    /// Called immediately before the start of `item` with the value returned by `fetch`, if any.
+    ///
+    async fn item_fetched(&mut self, item: &Item, fetched: Self::Fetched) -> Result<()> {
+        Ok(())
+    }
+
+    async fn start(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn identifier(&mut self, id: &str) -> Result<()> {
+        Ok(())
+    }
+
+    async fn name(&mut self, name: &str) -> Result<()> {
+        Ok(())
+    }
+
+    ///
+    /// Called after the name, with the itinerary's metadata and the summary derived from the
+    /// items being visited.
+    ///
+    async fn summary(&mut self, metadata: &Metadata, summary: &Summary) -> Result<()> {
+        Ok(())
+    }
+
+    async fn traveler(&mut self, traveler: &Traveler) -> Result<()> {
+        Ok(())
+    }
+
+    ///
+    /// Called after the travelers, if the itinerary has any notes, tags, or attachments.
+    ///
+    async fn annotations(&mut self, annotations: &Annotations) -> Result<()> {
+        Ok(())
+    }
+
+    async fn booking_start(&mut self, booking: &Booking) -> Result<()> {
+        Ok(())
+    }
+
+    async fn booking_end(&mut self, booking: &Booking) -> Result<()> {
+        Ok(())
+    }
+
+    async fn flight_departs(&mut self, flight: &Flight) -> Result<()> {
+        Ok(())
+    }
+
+    async fn flight_arrives(&mut self, flight: &Flight) -> Result<()> {
+        Ok(())
+    }
+
+    async fn journey_departs(&mut self, journey: &Journey) -> Result<()> {
+        Ok(())
+    }
+
+    async fn journey_connection(
+        &mut self,
+        journey: &Journey,
+        connection: &Connection<'_>,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    async fn journey_arrives(&mut self, journey: &Journey) -> Result<()> {
+        Ok(())
+    }
+
+    async fn rail_departs(&mut self, rail: &Rail) -> Result<()> {
+        Ok(())
+    }
+
+    async fn rail_change(&mut self, rail: &Rail, change: &RailChange<'_>) -> Result<()> {
+        Ok(())
+    }
+
+    async fn rail_arrives(&mut self, rail: &Rail) -> Result<()> {
+        Ok(())
+    }
+
+    async fn cruise_embarks(&mut self, cruise: &Cruise) -> Result<()> {
+        Ok(())
+    }
+
+    async fn cruise_port_arrival(&mut self, cruise: &Cruise, port_call: &PortCall) -> Result<()> {
+        Ok(())
+    }
+
+    async fn cruise_port_departure(&mut self, cruise: &Cruise, port_call: &PortCall) -> Result<()> {
+        Ok(())
+    }
+
+    async fn cruise_disembarks(&mut self, cruise: &Cruise) -> Result<()> {
+        Ok(())
+    }
+
+    async fn transport_departs(&mut self, transport: &Transport) -> Result<()> {
+        Ok(())
+    }
+
+    async fn transport_arrives(&mut self, transport: &Transport) -> Result<()> {
+        Ok(())
+    }
+
+    async fn vehicle_pickup(&mut self, vehicle: &Vehicle) -> Result<()> {
+        Ok(())
+    }
+
+    async fn vehicle_dropoff(&mut self, vehicle: &Vehicle) -> Result<()> {
+        Ok(())
+    }
+
+    async fn accomodation_checkin(&mut self, accomodation: &Accomodation) -> Result<()> {
+        Ok(())
+    }
+
+    async fn accomodation_checkout(&mut self, accomodation: &Accomodation) -> Result<()> {
+        Ok(())
+    }
+
+    async fn event_start(&mut self, event: &Event) -> Result<()> {
+        Ok(())
+    }
+
+    async fn event_end(&mut self, event: &Event) -> Result<()> {
+        Ok(())
+    }
+
+    ///
+    /// Called immediately after the start of an item (`flight_departs`, `event_start`, etc.) if
+    /// the item has any notes, tags, or attachments.
+    ///
+    async fn item_annotations(&mut self, item: &Item, annotations: &Annotations) -> Result<()> {
+        Ok(())
+    }
+
+    ///
+    /// Called, in `VisitOrder::Timing` only, before anything that happens on `date` in the
+    /// traveler's local time. Every day from the start of the first item to the end of the last
+    /// is reported, whether or not anything happens on it.
+    ///
+    async fn day_start(&mut self, date: &Date) -> Result<()> {
+        Ok(())
+    }
+
+    ///
+    /// Called, in `VisitOrder::Timing` only, after everything that happens on `date`.
+    ///
+    async fn day_end(&mut self, date: &Date) -> Result<()> {
+        Ok(())
+    }
+
+    ///
+    /// Called, in `VisitOrder::Timing` only, before an item that starts some time after every
+    /// earlier item has ended, unless the gap is a `layover`. It is reported within the day the
+    /// gap ends, after that day's `day_start`.
+    ///
+    async fn idle_gap(&mut self, gap: &Gap) -> Result<()> {
+        Ok(())
+    }
+
+    ///
+    /// As `idle_gap`, but where the item before the gap and the item after it are both
+    /// transportation; a flight, journey, rail ticket, cruise, or transport.
+    ///
+    async fn layover(&mut self, gap: &Gap) -> Result<()> {
+        Ok(())
+    }
+
+    ///
+    /// Called after `idle_gap` or `layover` if the gap spans one or more nights, that is nights
+    /// with no accomodation or other item in progress.
+    ///
+    async fn unaccounted_nights(&mut self, gap: &Gap) -> Result<()> {
+        Ok(())
+    }
+
+    async fn end(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    ///
+    /// Cancelled items are not visited unless this returns `true`.
+    ///
+    fn include_cancelled(&self) -> bool {
+        false
+    }
+}
+
+///
+/// Fetches data for many items at once on behalf of `visit_itinerary_concurrently`. This is
+/// separate from the visitor as the visitor's callbacks are made while fetches are in progress.
+///
+#[async_trait]
+pub trait ItemFetcher: Sync {
+    ///
+    /// Data fetched for an item before it is visited, as `AsyncItineraryVisitor::Fetched`.
+    ///
+    type Fetched: Send;
+
+    ///
+    /// Fetch data for `item`; the value is passed to the visitor's `item_fetched`.
+    ///
+    async fn fetch(&self, item: &Item) -> Result<Option<Self::Fetched>>;
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+pub async fn visit_itinerary_async<V: AsyncItineraryVisitor>(
+    itinerary: &Itinerary,
+    visitor: &mut V,
+) -> Result<()> {
+    visit_filtered_itinerary_async(
+        itinerary,
+        visitor,
+        VisitOrder::default(),
+        &ItemFilter::default(),
+    )
+    .await
+}
+
+pub async fn visit_ordered_itinerary_async<V: AsyncItineraryVisitor>(
+    itinerary: &Itinerary,
+    visitor: &mut V,
+    order: VisitOrder,
+) -> Result<()> {
+    visit_filtered_itinerary_async(itinerary, visitor, order, &ItemFilter::default()).await
+}
+
+///
+/// Visit the itinerary, fetching the data for each item immediately before it is visited.
+///
+pub async fn visit_filtered_itinerary_async<V: AsyncItineraryVisitor>(
+    itinerary: &Itinerary,
+    visitor: &mut V,
+    order: VisitOrder,
+    filter: &ItemFilter,
+) -> Result<()> {
+    visitor.start().await?;
+    for visit in plan_visits(itinerary, order, filter, visitor.include_cancelled()) {
+        if let Visit::Item(item, ItemEdge::Start) = visit {
+            if let Some(fetched) = visitor.fetch(item).await? {
+                visitor.item_fetched(item, fetched).await?;
+            }
+        }
+        make_visit(&visit, visitor).await?;
+    }
+    visitor.end().await
+}
+
+///
+/// Visit the itinerary, using `fetcher` to fetch the data for items with up to `limit` fetches
+/// running at once. Callbacks are made in the same order, and with the same values, as
+/// `visit_filtered_itinerary_async`; each item's `item_fetched` is made as soon as its own fetch,
+/// and every earlier one, has completed. If a fetch fails no further callbacks are made.
+///
+pub async fn visit_itinerary_concurrently<V, F>(
+    itinerary: &Itinerary,
+    visitor: &mut V,
+    fetcher: &F,
+    order: VisitOrder,
+    filter: &ItemFilter,
+    limit: usize,
+) -> Result<()>
+where
+    V: AsyncItineraryVisitor,
+    F: ItemFetcher<Fetched = V::Fetched>,
+{
+    visitor.start().await?;
+    let visits = plan_visits(itinerary, order, filter, visitor.include_cancelled());
+    let mut fetched = stream::iter(visits.iter().filter_map(|visit| match visit {
+        Visit::Item(item, ItemEdge::Start) => Some(*item),
+        _ => None,
+    }))
+    .map(|item| fetcher.fetch(item))
+    .buffered(limit.max(1));
+    for visit in &visits {
+        if let Visit::Item(item, ItemEdge::Start) = visit {
+            if let Some(fetched) = fetched.next().await.transpose()?.flatten() {
+                visitor.item_fetched(item, fetched).await?;
+            }
+        }
+        make_visit(visit, visitor).await?;
+    }
+    visitor.end().await
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+async fn make_visit<V: AsyncItineraryVisitor>(visit: &Visit<'_>, visitor: &mut V) -> Result<()> {
+    dispatch_visit!(visit, visitor, await)
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        visit_filtered_itinerary_async, visit_itinerary_concurrently, AsyncItineraryVisitor,
+        ItemFetcher,
+    };
+    use crate::error::Result;
+    use crate::itinerary::filter::ItemFilter;
+    use crate::itinerary::io::from_file;
+    use crate::itinerary::visitor::VisitOrder;
+    use crate::itinerary::{Flight, Item, Itinerary};
+    use async_trait::async_trait;
+    use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    ///
+    /// Fetches the arrival time of each flight, taking `delay` milliseconds for `slow` and 1 for
+    /// any other, and logging each fetch as it completes.
+    ///
+    struct ArrivalFetcher {
+        slow: u16,
+        delay: u64,
+        log: Arc<Mutex<Vec<String>>>,
+    }
+
+    #[async_trait]
+    impl ItemFetcher for ArrivalFetcher {
+        type Fetched = String;
+
+        async fn fetch(&self, item: &Item) -> Result<Option<Self::Fetched>> {
+            Ok(match item.as_flight() {
+                Some(flight) => {
+                    let delay = if flight.number() == &self.slow {
+                        self.delay
+                    } else {
+                        1
+                    };
+                    tokio::time::sleep(Duration::from_millis(delay)).await;
+                    let arrival = flight.arrival_date_time().to_string();
+                    self.log
+                        .lock()
+                        .unwrap()
+                        .push(format!("completed {}", arrival));
+                    Some(arrival)
+                }
+                None => None,
+            })
+        }
+    }
+
+    impl ArrivalFetcher {
+        fn new(slow: u16, delay: u64, log: &Arc<Mutex<Vec<String>>>) -> Self {
+            Self {
+                slow,
+                delay,
+                log: log.clone(),
+            }
+        }
+    }
+
+    ///
+    /// Logs the arrival time fetched for, and the departure of, each flight. When visiting
+    /// sequentially this fetches for itself, taking longer for the earlier flight.
+    ///
+    struct Arrivals(Arc<Mutex<Vec<String>>>);
+
+    #[async_trait]
+    impl AsyncItineraryVisitor for Arrivals {
+        type Fetched = String;
+
+        async fn fetch(&self, item: &Item) -> Result<Option<Self::Fetched>> {
+            let log = Arc::new(Mutex::new(Vec::new()));
+            ArrivalFetcher::new(594, 50, &log).fetch(item).await
+        }
+
+        async fn item_fetched(&mut self, _: &Item, fetched: Self::Fetched) -> Result<()> {
+            self.0.lock().unwrap().push(format!("fetched {}", fetched));
+            Ok(())
+        }
+
+        async fn flight_departs(&mut self, flight: &Flight) -> Result<()> {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("{} departs", flight.flight_number_string()));
+            Ok(())
+        }
+
+        async fn end(&mut self) -> Result<()> {
+            self.0.lock().unwrap().push("end".to_string());
+            Ok(())
+        }
+    }
+
+    fn example_itinerary() -> Itinerary {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/data/playa.yml");
+        from_file(&path).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_visit_async_in_order() {
+        let itinerary = example_itinerary();
+        let expected = vec![
+            "fetched 2022-06-24 15:03:00",
+            "DL594 departs",
+            "fetched 2022-07-05 20:23:00",
+            "DL604 departs",
+            "end",
+        ];
+        let filter = ItemFilter::default();
+
+        let log = Arc::new(Mutex::new(Vec::new()));
+        visit_filtered_itinerary_async(
+            &itinerary,
+            &mut Arrivals(log.clone()),
+            VisitOrder::Timing,
+            &filter,
+        )
+        .await
+        .unwrap();
+        assert_eq!(*log.lock().unwrap(), expected);
+
+        let log = Arc::new(Mutex::new(Vec::new()));
+        visit_itinerary_concurrently(
+            &itinerary,
+            &mut Arrivals(log.clone()),
+            &ArrivalFetcher::new(594, 50, &Arc::new(Mutex::new(Vec::new()))),
+            VisitOrder::Timing,
+            &filter,
+            4,
+        )
+        .await
+        .unwrap();
+        assert_eq!(*log.lock().unwrap(), expected);
+    }
+
+    #[tokio::test]
+    async fn test_visit_concurrently_before_all_fetched() {
+        let itinerary = example_itinerary();
+        let log = Arc::new(Mutex::new(Vec::new()));
+        visit_itinerary_concurrently(
+            &itinerary,
+            &mut Arrivals(log.clone()),
+            &ArrivalFetcher::new(604, 200, &log),
+            VisitOrder::Timing,
+            &ItemFilter::default(),
+            4,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec![
+                "completed 2022-06-24 15:03:00",
+                "fetched 2022-06-24 15:03:00",
+                "DL594 departs",
+                "completed 2022-07-05 20:23:00",
+                "fetched 2022-07-05 20:23:00",
+                "DL604 departs",
+                "end",
+            ]
+        );
+    }
+}
@@ -0,0 +1,1362 @@
+/*!
+Builders for creating itineraries, and each kind of item, in code rather than from YAML.
+
+Each builder collects values through chained methods and checks them in `build`, returning an
+error, rather than panicking, if a required value is missing or the values are inconsistent;
+for example an item that ends before it starts. Time zones that are not given are inferred from
+locations, as they are when an itinerary is read from a file, and `ItineraryBuilder` assigns
+identifiers to any items that do not have one.
+
+# Example
+
+```rust
+use terminal_travel::itinerary::builder::{AccomodationBuilder, ItineraryBuilder};
+use terminal_travel::itinerary::location::Location;
+use terminal_travel::itinerary::traveler::Traveler;
+use terminal_travel::itinerary::Date;
+
+let checkin = Date::from_ymd_opt(2022, 6, 24).unwrap().and_hms_opt(16, 0, 0).unwrap();
+let checkout = Date::from_ymd_opt(2022, 7, 1).unwrap().and_hms_opt(11, 0, 0).unwrap();
+
+let itinerary = ItineraryBuilder::new("2022-06", "Playa del Carmen")
+    .traveler(Traveler::new("Simon"))
+    .item(
+        AccomodationBuilder::new("Hotel Xcaret")
+            .address(Location::new("Playa del Carmen"))
+            .checkin(checkin)
+            .checkout(checkout)
+            .build()
+            .unwrap(),
+    )
+    .build()
+    .unwrap();
+assert_eq!(itinerary.items().count(), 1);
+```
+ */
+
+use crate::error::{ErrorKind, Result};
+use crate::itinerary::annotation::{Annotations, Attachment};
+use crate::itinerary::cost::Cost;
+use crate::itinerary::flight_details::{AirportGate, BookingClass, Cabin, Passenger};
+use crate::itinerary::location::Location;
+use crate::itinerary::metadata::Metadata;
+use crate::itinerary::status::ItemStatus;
+use crate::itinerary::traveler::Traveler;
+use crate::itinerary::{
+    Accomodation, AirCarrierCode, AirportCode, Booking, Cruise, DateTime, Duration, Event, Flight,
+    FlightNumber, FlightSegment, Item, ItemCommon, ItemId, ItemLink, Itinerary, Journey,
+    OperatingFlight, PortCall, Rail, RailLeg, TimeZone, TimeZoneLocation, Transport, TransportKind,
+    TravelRecord, Vehicle,
+};
+use std::collections::HashSet;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Clone, Debug)]
+pub struct ItineraryBuilder {
+    identifier: String,
+    name: String,
+    metadata: Metadata,
+    annotations: Annotations,
+    travelers: Vec<Traveler>,
+    bookings: Vec<Booking>,
+    items: Vec<Item>,
+}
+
+#[derive(Clone, Debug)]
+pub struct BookingBuilder {
+    agency: String,
+    confirmation: String,
+    items: Vec<Item>,
+}
+
+#[derive(Clone, Debug)]
+pub struct FlightBuilder {
+    common: ItemCommon,
+    number: FlightNumber,
+    operated_by: Option<OperatingFlight>,
+    cabin: Option<Cabin>,
+    booking_class: Option<BookingClass>,
+    passengers: Vec<Passenger>,
+    departure_gate: Option<AirportGate>,
+    arrival_gate: Option<AirportGate>,
+    record: RecordBuilder<AirCarrierCode, AirportCode>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct JourneyBuilder {
+    common: ItemCommon,
+    record_locator: Option<String>,
+    segments: Vec<FlightSegment>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct RailBuilder {
+    common: ItemCommon,
+    ticket_reference: Option<String>,
+    legs: Vec<RailLeg>,
+}
+
+#[derive(Clone, Debug)]
+pub struct RailLegBuilder {
+    train_number: String,
+    class: Option<String>,
+    coach: Option<String>,
+    seat: Option<String>,
+    record: RecordBuilder<String, Location>,
+}
+
+#[derive(Clone, Debug)]
+pub struct CruiseBuilder {
+    common: ItemCommon,
+    ship: String,
+    cabin: Option<String>,
+    record: RecordBuilder<String, Location>,
+    port_calls: Vec<PortCall>,
+}
+
+#[derive(Clone, Debug)]
+pub struct TransportBuilder {
+    common: ItemCommon,
+    kind: Option<TransportKind>,
+    record: RecordBuilder<String, Location>,
+}
+
+#[derive(Clone, Debug)]
+pub struct VehicleBuilder {
+    common: ItemCommon,
+    record: RecordBuilder<String, Location>,
+}
+
+#[derive(Clone, Debug)]
+pub struct AccomodationBuilder {
+    common: ItemCommon,
+    record: RecordBuilder<String, Location>,
+}
+
+#[derive(Clone, Debug)]
+pub struct EventBuilder {
+    common: ItemCommon,
+    record: RecordBuilder<String, Location>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Clone, Debug)]
+struct RecordBuilder<P, L> {
+    provider: P,
+    confirmation: Option<String>,
+    start_date_time: Option<DateTime>,
+    start_time_zone: Option<TimeZone>,
+    start_location: Option<L>,
+    end_date_time: Option<DateTime>,
+    end_time_zone: Option<TimeZone>,
+    end_location: Option<L>,
+}
+
+///
+/// The names used in errors for a record's kind of item, and its start and end values.
+///
+struct RecordNames {
+    kind: &'static str,
+    start: &'static str,
+    end: &'static str,
+    shared_time_zone: bool,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Setters for the values held by every kind of item, see `ItemCommon`.
+///
+macro_rules! item_common_setters {
+    () => {
+        ///
+        /// Set the item's identifier, otherwise one is assigned when it is added to an itinerary.
+        ///
+        pub fn id(mut self, id: ItemId) -> Self {
+            self.common.id = Some(id);
+            self
+        }
+
+        pub fn link(mut self, link: ItemLink) -> Self {
+            self.common.links.push(link);
+            self
+        }
+
+        ///
+        /// Add a traveler this item covers; if none are added it covers all of them.
+        ///
+        pub fn traveler(mut self, name: &str) -> Self {
+            self.common.travelers.push(name.to_string());
+            self
+        }
+
+        pub fn cost(mut self, cost: Cost) -> Self {
+            self.common.cost = Some(cost);
+            self
+        }
+
+        ///
+        /// Set the initial status of the item, the default is confirmed.
+        ///
+        pub fn status(mut self, status: ItemStatus) -> Self {
+            self.common.status = status;
+            self
+        }
+
+        pub fn notes(mut self, notes: &str) -> Self {
+            self.common.annotations.set_notes(notes);
+            self
+        }
+
+        pub fn tag(mut self, tag: &str) -> Self {
+            self.common.annotations.add_tag(tag);
+            self
+        }
+
+        pub fn attachment(mut self, attachment: Attachment) -> Self {
+            self.common.annotations.add_attachment(attachment);
+            self
+        }
+    };
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl ItineraryBuilder {
+    pub fn new(identifier: &str, name: &str) -> Self {
+        Self {
+            identifier: identifier.to_string(),
+            name: name.to_string(),
+            metadata: Default::default(),
+            annotations: Default::default(),
+            travelers: Default::default(),
+            bookings: Default::default(),
+            items: Default::default(),
+        }
+    }
+
+    pub fn metadata(mut self, metadata: Metadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    pub fn annotations(mut self, annotations: Annotations) -> Self {
+        self.annotations = annotations;
+        self
+    }
+
+    pub fn traveler(mut self, traveler: Traveler) -> Self {
+        self.travelers.push(traveler);
+        self
+    }
+
+    pub fn booking(mut self, booking: Booking) -> Self {
+        self.bookings.push(booking);
+        self
+    }
+
+    ///
+    /// Add an item that is not part of any booking.
+    ///
+    pub fn item(mut self, item: impl Into<Item>) -> Self {
+        self.items.push(item.into());
+        self
+    }
+
+    ///
+    /// Check that the identifier and name are not blank, that traveler names and item
+    /// identifiers are unique, that every traveler named by an item is in the itinerary, and that
+    /// every link refers to an item in the itinerary.
+    ///
+    pub fn build(self) -> Result<Itinerary> {
+        require_text("itinerary", "identifier", &self.identifier)?;
+        require_text("itinerary", "name", &self.name)?;
+
+        let mut names = HashSet::new();
+        for traveler in &self.travelers {
+            if !names.insert(traveler.name().as_str()) {
+                return Err(ErrorKind::DuplicateTraveler(traveler.name().to_string()).into());
+            }
+        }
+
+        let mut itinerary = Itinerary {
+            identifier: self.identifier,
+            name: self.name,
+            metadata: self.metadata,
+            annotations: self.annotations,
+            travelers: self.travelers,
+            bookings: self.bookings,
+            items: self.items,
+        };
+
+        let mut ids = HashSet::new();
+        for item in itinerary.items() {
+            if let Some(id) = item.id() {
+                if !ids.insert(id) {
+                    return Err(ErrorKind::DuplicateItem(id.to_string()).into());
+                }
+            }
+            for name in item_traveler_names(item) {
+                if itinerary.traveler(name).is_none() {
+                    return Err(ErrorKind::UnknownTraveler(name.to_string()).into());
+                }
+            }
+        }
+
+        itinerary.infer_time_zones();
+        itinerary.assign_item_ids();
+        for link in itinerary.items().flat_map(|item| item.links()) {
+            if !itinerary.contains_item(link.target()) {
+                return Err(ErrorKind::UnknownItem(link.target().to_string()).into());
+            }
+        }
+        Ok(itinerary)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl BookingBuilder {
+    pub fn new(agency: &str, confirmation: &str) -> Self {
+        Self {
+            agency: agency.to_string(),
+            confirmation: confirmation.to_string(),
+            items: Default::default(),
+        }
+    }
+
+    pub fn item(mut self, item: impl Into<Item>) -> Self {
+        self.items.push(item.into());
+        self
+    }
+
+    pub fn build(self) -> Result<Booking> {
+        require_text("booking", "agency", &self.agency)?;
+        require_text("booking", "confirmation", &self.confirmation)?;
+        if self.items.is_empty() {
+            return Err(ErrorKind::MissingValue("booking".to_string(), "items".to_string()).into());
+        }
+        Ok(Booking {
+            agency: self.agency,
+            confirmation: self.confirmation,
+            items: self.items,
+        })
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl FlightBuilder {
+    pub fn new(carrier: AirCarrierCode, number: FlightNumber) -> Self {
+        Self {
+            common: Default::default(),
+            number,
+            operated_by: None,
+            cabin: None,
+            booking_class: None,
+            passengers: Default::default(),
+            departure_gate: None,
+            arrival_gate: None,
+            record: RecordBuilder::new(carrier),
+        }
+    }
+
+    item_common_setters!();
+
+    pub fn record_locator(mut self, record_locator: &str) -> Self {
+        self.record.confirmation = Some(record_locator.to_string());
+        self
+    }
+
+    pub fn departs(mut self, airport: AirportCode, date_time: DateTime) -> Self {
+        self.record.start_location = Some(airport);
+        self.record.start_date_time = Some(date_time);
+        self
+    }
+
+    pub fn departure_time_zone(mut self, time_zone: TimeZone) -> Self {
+        self.record.start_time_zone = Some(time_zone);
+        self
+    }
+
+    pub fn arrives(mut self, airport: AirportCode, date_time: DateTime) -> Self {
+        self.record.end_location = Some(airport);
+        self.record.end_date_time = Some(date_time);
+        self
+    }
+
+    pub fn arrival_time_zone(mut self, time_zone: TimeZone) -> Self {
+        self.record.end_time_zone = Some(time_zone);
+        self
+    }
+
+    pub fn operated_by(mut self, operated_by: OperatingFlight) -> Self {
+        self.operated_by = Some(operated_by);
+        self
+    }
+
+    pub fn cabin(mut self, cabin: Cabin) -> Self {
+        self.cabin = Some(cabin);
+        self
+    }
+
+    pub fn booking_class(mut self, booking_class: BookingClass) -> Self {
+        self.booking_class = Some(booking_class);
+        self
+    }
+
+    pub fn passenger(mut self, passenger: Passenger) -> Self {
+        self.passengers.push(passenger);
+        self
+    }
+
+    pub fn departure_gate(mut self, gate: AirportGate) -> Self {
+        self.departure_gate = Some(gate);
+        self
+    }
+
+    pub fn arrival_gate(mut self, gate: AirportGate) -> Self {
+        self.arrival_gate = Some(gate);
+        self
+    }
+
+    pub fn build(mut self) -> Result<Flight> {
+        let common = std::mem::take(&mut self.common);
+        Ok(Flight {
+            common,
+            segment: self.build_segment()?,
+        })
+    }
+
+    ///
+    /// Build a segment of a journey; as a segment has no values of its own common to items it is
+    /// an error if any were set.
+    ///
+    pub fn build_segment(self) -> Result<FlightSegment> {
+        if self.common != ItemCommon::default() {
+            return Err(invalid(
+                "journey segment",
+                "the id, links, travelers, cost, status and annotations belong to the journey",
+            ));
+        }
+        let record = self.record.build(&RecordNames {
+            kind: "flight",
+            start: "departure",
+            end: "arrival",
+            shared_time_zone: false,
+        })?;
+        if record.end_location.is_none() {
+            return Err(
+                ErrorKind::MissingValue("flight".to_string(), "arrival".to_string()).into(),
+            );
+        }
+        let mut travelers = HashSet::new();
+        for passenger in &self.passengers {
+            if !travelers.insert(passenger.traveler()) {
+                return Err(ErrorKind::DuplicateTraveler(passenger.traveler().to_string()).into());
+            }
+        }
+        Ok(FlightSegment {
+            number: self.number,
+            operated_by: self.operated_by,
+            cabin: self.cabin,
+            booking_class: self.booking_class,
+            passengers: self.passengers,
+            departure_gate: self.departure_gate,
+            arrival_gate: self.arrival_gate,
+            record,
+        })
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl JourneyBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    item_common_setters!();
+
+    pub fn record_locator(mut self, record_locator: &str) -> Self {
+        self.record_locator = Some(record_locator.to_string());
+        self
+    }
+
+    ///
+    /// Add the next flight of the journey.
+    ///
+    pub fn segment(mut self, segment: FlightSegment) -> Self {
+        self.segments.push(segment);
+        self
+    }
+
+    ///
+    /// Check there is at least one segment, and that each departs from the airport the previous
+    /// one arrived at, and after it arrived.
+    ///
+    pub fn build(self) -> Result<Journey> {
+        if self.segments.is_empty() {
+            return Err(
+                ErrorKind::MissingValue("journey".to_string(), "segments".to_string()).into(),
+            );
+        }
+        for pair in self.segments.windows(2) {
+            let (arriving, departing) = (&pair[0], &pair[1]);
+            let arrival_airport = arriving.arrival_airport().ok_or_else(|| {
+                crate::error::Error::from(ErrorKind::MissingValue(
+                    "journey segment".to_string(),
+                    "arrival".to_string(),
+                ))
+            })?;
+            if arrival_airport != departing.departure_airport() {
+                return Err(invalid(
+                    "journey",
+                    &format!(
+                        "flight {} departs {}, not {} where flight {} arrives",
+                        departing.flight_number_string(),
+                        departing.departure_airport(),
+                        arrival_airport,
+                        arriving.flight_number_string()
+                    ),
+                ));
+            }
+            if is_before(departing.departure_instant(), arriving.arrival_instant()) {
+                return Err(invalid(
+                    "journey",
+                    &format!(
+                        "flight {} departs before flight {} arrives",
+                        departing.flight_number_string(),
+                        arriving.flight_number_string()
+                    ),
+                ));
+            }
+        }
+        Ok(Journey {
+            common: self.common,
+            record_locator: self.record_locator,
+            segments: self.segments,
+        })
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl RailBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    item_common_setters!();
+
+    pub fn ticket_reference(mut self, ticket_reference: &str) -> Self {
+        self.ticket_reference = Some(ticket_reference.to_string());
+        self
+    }
+
+    ///
+    /// Add the next train of the ticket.
+    ///
+    pub fn leg(mut self, leg: RailLeg) -> Self {
+        self.legs.push(leg);
+        self
+    }
+
+    ///
+    /// Check there is at least one leg, and that each departs after the previous one arrived.
+    ///
+    pub fn build(self) -> Result<Rail> {
+        if self.legs.is_empty() {
+            return Err(ErrorKind::MissingValue("rail".to_string(), "legs".to_string()).into());
+        }
+        for pair in self.legs.windows(2) {
+            let (arriving, departing) = (&pair[0], &pair[1]);
+            if is_before(departing.departure_instant(), arriving.arrival_instant())
+                || (departing.departure_date_time() < arriving.arrival_date_time()
+                    && departing.departure_time_zone() == arriving.arrival_time_zone())
+            {
+                return Err(invalid(
+                    "rail",
+                    &format!(
+                        "{} departs before {} arrives",
+                        departing.train_string(),
+                        arriving.train_string()
+                    ),
+                ));
+            }
+        }
+        Ok(Rail {
+            common: self.common,
+            ticket_reference: self.ticket_reference,
+            legs: self.legs,
+        })
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl RailLegBuilder {
+    pub fn new(operator: &str, train_number: &str) -> Self {
+        Self {
+            train_number: train_number.to_string(),
+            class: None,
+            coach: None,
+            seat: None,
+            record: RecordBuilder::new(operator.to_string()),
+        }
+    }
+
+    pub fn confirmation(mut self, confirmation: &str) -> Self {
+        self.record.confirmation = Some(confirmation.to_string());
+        self
+    }
+
+    pub fn class(mut self, class: &str) -> Self {
+        self.class = Some(class.to_string());
+        self
+    }
+
+    pub fn coach(mut self, coach: &str) -> Self {
+        self.coach = Some(coach.to_string());
+        self
+    }
+
+    pub fn seat(mut self, seat: &str) -> Self {
+        self.seat = Some(seat.to_string());
+        self
+    }
+
+    pub fn departs(mut self, station: Location, date_time: DateTime) -> Self {
+        self.record.start_location = Some(station);
+        self.record.start_date_time = Some(date_time);
+        self
+    }
+
+    pub fn departure_time_zone(mut self, time_zone: TimeZone) -> Self {
+        self.record.start_time_zone = Some(time_zone);
+        self
+    }
+
+    pub fn arrives(mut self, station: Location, date_time: DateTime) -> Self {
+        self.record.end_location = Some(station);
+        self.record.end_date_time = Some(date_time);
+        self
+    }
+
+    pub fn arrival_time_zone(mut self, time_zone: TimeZone) -> Self {
+        self.record.end_time_zone = Some(time_zone);
+        self
+    }
+
+    pub fn build(self) -> Result<RailLeg> {
+        require_text("rail leg", "train_number", &self.train_number)?;
+        let record = self.record.build(&RecordNames {
+            kind: "rail leg",
+            start: "departure",
+            end: "arrival",
+            shared_time_zone: false,
+        })?;
+        if record.end_location.is_none() {
+            return Err(
+                ErrorKind::MissingValue("rail leg".to_string(), "arrival".to_string()).into(),
+            );
+        }
+        Ok(RailLeg {
+            train_number: self.train_number,
+            class: self.class,
+            coach: self.coach,
+            seat: self.seat,
+            record,
+        })
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl CruiseBuilder {
+    pub fn new(cruise_line: &str, ship: &str) -> Self {
+        Self {
+            common: Default::default(),
+            ship: ship.to_string(),
+            cabin: None,
+            record: RecordBuilder::new(cruise_line.to_string()),
+            port_calls: Default::default(),
+        }
+    }
+
+    item_common_setters!();
+
+    pub fn confirmation(mut self, confirmation: &str) -> Self {
+        self.record.confirmation = Some(confirmation.to_string());
+        self
+    }
+
+    pub fn cabin(mut self, cabin: &str) -> Self {
+        self.cabin = Some(cabin.to_string());
+        self
+    }
+
+    pub fn embarks(mut self, port: Location, date_time: DateTime) -> Self {
+        self.record.start_location = Some(port);
+        self.record.start_date_time = Some(date_time);
+        self
+    }
+
+    pub fn embarkation_time_zone(mut self, time_zone: TimeZone) -> Self {
+        self.record.start_time_zone = Some(time_zone);
+        self
+    }
+
+    pub fn disembarks(mut self, port: Location, date_time: DateTime) -> Self {
+        self.record.end_location = Some(port);
+        self.record.end_date_time = Some(date_time);
+        self
+    }
+
+    pub fn disembarkation_time_zone(mut self, time_zone: TimeZone) -> Self {
+        self.record.end_time_zone = Some(time_zone);
+        self
+    }
+
+    ///
+    /// Add the next port the ship calls at.
+    ///
+    pub fn port_call(mut self, port_call: PortCall) -> Self {
+        self.port_calls.push(port_call);
+        self
+    }
+
+    ///
+    /// Check that each port call departs after it arrives, and that the calls are in order
+    /// between embarkation and disembarkation. As ships change clocks gradually these checks use
+    /// local times.
+    ///
+    pub fn build(self) -> Result<Cruise> {
+        require_text("cruise", "ship", &self.ship)?;
+        let record = self.record.build(&RecordNames {
+            kind: "cruise",
+            start: "embarkation",
+            end: "disembarkation",
+            shared_time_zone: false,
+        })?;
+        let mut previous = &record.start_date_time;
+        for port_call in &self.port_calls {
+            if port_call.departure_date_time < port_call.arrival_date_time {
+                return Err(invalid(
+                    "cruise",
+                    &format!("departs {} before arriving", port_call.port),
+                ));
+            }
+            if &port_call.arrival_date_time < previous {
+                return Err(invalid(
+                    "cruise",
+                    &format!("arrives {} out of order", port_call.port),
+                ));
+            }
+            previous = &port_call.departure_date_time;
+        }
+        if &record.end_date_time < previous {
+            return Err(invalid("cruise", "disembarks before the last port call"));
+        }
+        let mut port_calls = self.port_calls;
+        for port_call in &mut port_calls {
+            if port_call.time_zone.is_none() {
+                port_call.time_zone = port_call.port.time_zone();
+            }
+        }
+        Ok(Cruise {
+            common: self.common,
+            ship: self.ship,
+            cabin: self.cabin,
+            record,
+            port_calls,
+        })
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl TransportBuilder {
+    pub fn new(company_or_agency: &str) -> Self {
+        Self {
+            common: Default::default(),
+            kind: None,
+            record: RecordBuilder::new(company_or_agency.to_string()),
+        }
+    }
+
+    item_common_setters!();
+
+    pub fn kind(mut self, kind: TransportKind) -> Self {
+        self.kind = Some(kind);
+        self
+    }
+
+    pub fn confirmation(mut self, confirmation: &str) -> Self {
+        self.record.confirmation = Some(confirmation.to_string());
+        self
+    }
+
+    pub fn departs(mut self, address: Location, date_time: DateTime) -> Self {
+        self.record.start_location = Some(address);
+        self.record.start_date_time = Some(date_time);
+        self
+    }
+
+    pub fn arrives(mut self, address: Location, date_time: DateTime) -> Self {
+        self.record.end_location = Some(address);
+        self.record.end_date_time = Some(date_time);
+        self
+    }
+
+    ///
+    /// Set the time zone of both departure and arrival.
+    ///
+    pub fn time_zone(mut self, time_zone: TimeZone) -> Self {
+        self.record.start_time_zone = Some(time_zone);
+        self.record.end_time_zone = Some(time_zone);
+        self
+    }
+
+    pub fn build(self) -> Result<Transport> {
+        let record = self.record.build(&RecordNames {
+            kind: "transport",
+            start: "departure",
+            end: "arrival",
+            shared_time_zone: true,
+        })?;
+        if record.end_location.is_none() {
+            return Err(
+                ErrorKind::MissingValue("transport".to_string(), "arrival".to_string()).into(),
+            );
+        }
+        Ok(Transport {
+            common: self.common,
+            record,
+            kind: self.kind,
+        })
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl VehicleBuilder {
+    pub fn new(rental_agency: &str) -> Self {
+        Self {
+            common: Default::default(),
+            record: RecordBuilder::new(rental_agency.to_string()),
+        }
+    }
+
+    item_common_setters!();
+
+    pub fn confirmation(mut self, confirmation: &str) -> Self {
+        self.record.confirmation = Some(confirmation.to_string());
+        self
+    }
+
+    pub fn pickup(mut self, address: Location, date_time: DateTime) -> Self {
+        self.record.start_location = Some(address);
+        self.record.start_date_time = Some(date_time);
+        self
+    }
+
+    pub fn dropoff(mut self, address: Location, date_time: DateTime) -> Self {
+        self.record.end_location = Some(address);
+        self.record.end_date_time = Some(date_time);
+        self
+    }
+
+    ///
+    /// Set the time zone of both pickup and dropoff.
+    ///
+    pub fn time_zone(mut self, time_zone: TimeZone) -> Self {
+        self.record.start_time_zone = Some(time_zone);
+        self.record.end_time_zone = Some(time_zone);
+        self
+    }
+
+    pub fn build(self) -> Result<Vehicle> {
+        let record = self.record.build(&RecordNames {
+            kind: "vehicle",
+            start: "pickup",
+            end: "dropoff",
+            shared_time_zone: true,
+        })?;
+        if record.end_location.is_none() {
+            return Err(
+                ErrorKind::MissingValue("vehicle".to_string(), "dropoff".to_string()).into(),
+            );
+        }
+        Ok(Vehicle {
+            common: self.common,
+            record,
+        })
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl AccomodationBuilder {
+    pub fn new(company: &str) -> Self {
+        Self {
+            common: Default::default(),
+            record: RecordBuilder::new(company.to_string()),
+        }
+    }
+
+    item_common_setters!();
+
+    pub fn confirmation(mut self, confirmation: &str) -> Self {
+        self.record.confirmation = Some(confirmation.to_string());
+        self
+    }
+
+    pub fn address(mut self, address: Location) -> Self {
+        self.record.start_location = Some(address);
+        self
+    }
+
+    pub fn checkin(mut self, date_time: DateTime) -> Self {
+        self.record.start_date_time = Some(date_time);
+        self
+    }
+
+    pub fn checkout(mut self, date_time: DateTime) -> Self {
+        self.record.end_date_time = Some(date_time);
+        self
+    }
+
+    pub fn time_zone(mut self, time_zone: TimeZone) -> Self {
+        self.record.start_time_zone = Some(time_zone);
+        self.record.end_time_zone = Some(time_zone);
+        self
+    }
+
+    pub fn build(self) -> Result<Accomodation> {
+        let record = self.record.build(&RecordNames {
+            kind: "accomodation",
+            start: "checkin",
+            end: "checkout",
+            shared_time_zone: true,
+        })?;
+        Ok(Accomodation {
+            common: self.common,
+            record,
+        })
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl EventBuilder {
+    pub fn new(company: &str) -> Self {
+        Self {
+            common: Default::default(),
+            record: RecordBuilder::new(company.to_string()),
+        }
+    }
+
+    item_common_setters!();
+
+    pub fn confirmation(mut self, confirmation: &str) -> Self {
+        self.record.confirmation = Some(confirmation.to_string());
+        self
+    }
+
+    pub fn address(mut self, address: Location) -> Self {
+        self.record.start_location = Some(address);
+        self
+    }
+
+    pub fn starts(mut self, date_time: DateTime) -> Self {
+        self.record.start_date_time = Some(date_time);
+        self
+    }
+
+    pub fn ends(mut self, date_time: DateTime) -> Self {
+        self.record.end_date_time = Some(date_time);
+        self
+    }
+
+    pub fn time_zone(mut self, time_zone: TimeZone) -> Self {
+        self.record.start_time_zone = Some(time_zone);
+        self.record.end_time_zone = Some(time_zone);
+        self
+    }
+
+    pub fn build(self) -> Result<Event> {
+        let record = self.record.build(&RecordNames {
+            kind: "event",
+            start: "start",
+            end: "end",
+            shared_time_zone: true,
+        })?;
+        Ok(Event {
+            common: self.common,
+            record,
+        })
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl<P, L: TimeZoneLocation> RecordBuilder<P, L> {
+    fn new(provider: P) -> Self {
+        Self {
+            provider,
+            confirmation: None,
+            start_date_time: None,
+            start_time_zone: None,
+            start_location: None,
+            end_date_time: None,
+            end_time_zone: None,
+            end_location: None,
+        }
+    }
+
+    ///
+    /// Check the start and end are present, infer any missing time zones, and check the record
+    /// does not end before it starts. Where a time zone is unknown this last check can only be
+    /// made on local times if both ends are assumed to share a zone.
+    ///
+    fn build(self, names: &RecordNames) -> Result<TravelRecord<P, L>> {
+        let missing = |field: &str| -> crate::error::Error {
+            ErrorKind::MissingValue(names.kind.to_string(), field.to_string()).into()
+        };
+        let mut record = TravelRecord {
+            provider: self.provider,
+            confirmation: self.confirmation,
+            start_date_time: self.start_date_time.ok_or_else(|| missing(names.start))?,
+            start_time_zone: self.start_time_zone,
+            start_location: self.start_location.ok_or_else(|| missing(names.start))?,
+            end_date_time: self.end_date_time.ok_or_else(|| missing(names.end))?,
+            end_time_zone: self.end_time_zone,
+            end_location: self.end_location,
+            start_time_zone_inferred: false,
+            end_time_zone_inferred: false,
+        };
+        record.infer_time_zones(names.shared_time_zone);
+        let ends_before_start = match (record.start_instant(), record.end_instant()) {
+            (Some(start), Some(end)) => end < start,
+            _ => names.shared_time_zone && record.duration() < Duration::zero(),
+        };
+        if ends_before_start {
+            return Err(invalid(
+                names.kind,
+                &format!("{} is before {}", names.end, names.start),
+            ));
+        }
+        Ok(record)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn require_text(kind: &str, field: &str, value: &str) -> Result<()> {
+    if value.trim().is_empty() {
+        Err(ErrorKind::MissingValue(kind.to_string(), field.to_string()).into())
+    } else {
+        Ok(())
+    }
+}
+
+fn invalid(kind: &str, reason: &str) -> crate::error::Error {
+    ErrorKind::InvalidValue(kind.to_string(), reason.to_string()).into()
+}
+
+///
+/// Returns `true` only if both instants are known and `lhs` is before `rhs`.
+///
+fn is_before(
+    lhs: Option<crate::itinerary::Instant>,
+    rhs: Option<crate::itinerary::Instant>,
+) -> bool {
+    matches!((lhs, rhs), (Some(lhs), Some(rhs)) if lhs < rhs)
+}
+
+///
+/// The names of all travelers an item refers to, including flight passengers.
+///
+fn item_traveler_names(item: &Item) -> Vec<&String> {
+    let mut names: Vec<&String> = item.common().travelers().collect();
+    match item {
+        Item::Flight(v) => names.extend(v.passengers().map(|v| v.traveler())),
+        Item::Journey(v) => names.extend(
+            v.segments()
+                .flat_map(|segment| segment.passengers().map(|v| v.traveler())),
+        ),
+        _ => {}
+    }
+    names
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        AccomodationBuilder, BookingBuilder, FlightBuilder, ItineraryBuilder, JourneyBuilder,
+        TransportBuilder,
+    };
+    use crate::itinerary::flight_details::{Cabin, Passenger};
+    use crate::itinerary::io::{from_str, to_string};
+    use crate::itinerary::location::Location;
+    use crate::itinerary::traveler::Traveler;
+    use crate::itinerary::{AirCarrierCode, AirportCode, Date, DateTime, ItemId, ItemLink};
+    use pretty_assertions::assert_eq;
+    use std::str::FromStr;
+
+    fn date_time(month: u32, day: u32, hour: u32, min: u32) -> DateTime {
+        Date::from_ymd_opt(2022, month, day)
+            .unwrap()
+            .and_hms_opt(hour, min, 0)
+            .unwrap()
+    }
+
+    fn airport(code: &str) -> AirportCode {
+        AirportCode::from_str(code).unwrap()
+    }
+
+    fn flight(
+        number: u16,
+        from: &str,
+        departs: DateTime,
+        to: &str,
+        arrives: DateTime,
+    ) -> FlightBuilder {
+        FlightBuilder::new(AirCarrierCode::from_str("DL").unwrap(), number)
+            .departs(airport(from), departs)
+            .arrives(airport(to), arrives)
+    }
+
+    #[test]
+    fn test_build_matches_loaded() {
+        let itinerary = ItineraryBuilder::new("2022-06", "Playa del Carmen")
+            .traveler(Traveler::new("Simon"))
+            .booking(
+                BookingBuilder::new("Expedia", "7261998331")
+                    .item(
+                        flight(
+                            594,
+                            "SEA",
+                            date_time(6, 24, 7, 15),
+                            "CUN",
+                            date_time(6, 24, 15, 3),
+                        )
+                        .record_locator("GROVQY")
+                        .cabin(Cabin::Economy)
+                        .passenger(Passenger::new("Simon"))
+                        .build()
+                        .unwrap(),
+                    )
+                    .build()
+                    .unwrap(),
+            )
+            .item(
+                TransportBuilder::new("Happy Shuttle")
+                    .link(ItemLink::Meets(
+                        ItemId::from_str("flight-dl594-20220624").unwrap(),
+                    ))
+                    .departs(Location::new("Cancun airport"), date_time(6, 24, 15, 30))
+                    .arrives(Location::new("Playa del Carmen"), date_time(6, 24, 16, 30))
+                    .build()
+                    .unwrap(),
+            )
+            .item(
+                AccomodationBuilder::new("Hotel Xcaret")
+                    .traveler("Simon")
+                    .tag("beach")
+                    .address(Location::new("Playa del Carmen"))
+                    .checkin(date_time(6, 24, 16, 0))
+                    .checkout(date_time(7, 1, 11, 0))
+                    .build()
+                    .unwrap(),
+            )
+            .build()
+            .unwrap();
+
+        let loaded = from_str(
+            r##"---
+identifier: 2022-06
+name: Playa del Carmen
+travelers:
+  - Simon
+bookings:
+  - agency: Expedia
+    confirmation: "7261998331"
+    items:
+      - flight:
+          number: 594
+          cabin: economy
+          passengers:
+            - traveler: Simon
+          record:
+            provider: DL
+            confirmation: GROVQY
+            start_date_time: "2022-06-24T07:15:00"
+            start_location: SEA
+            end_date_time: "2022-06-24T15:03:00"
+            end_location: CUN
+items:
+  - transport:
+      links:
+        - meets: flight-dl594-20220624
+      record:
+        provider: Happy Shuttle
+        start_date_time: "2022-06-24T15:30:00"
+        start_location: Cancun airport
+        end_date_time: "2022-06-24T16:30:00"
+        end_location: Playa del Carmen
+  - accomodation:
+      travelers:
+        - Simon
+      tags:
+        - beach
+      record:
+        provider: Hotel Xcaret
+        start_date_time: "2022-06-24T16:00:00"
+        start_location: Playa del Carmen
+        end_date_time: "2022-07-01T11:00:00"
+"##,
+        )
+        .unwrap();
+        assert_eq!(itinerary, loaded);
+        assert_eq!(
+            from_str(&to_string(&itinerary).unwrap()).unwrap(),
+            itinerary
+        );
+    }
+
+    #[test]
+    fn test_item_invariants() {
+        // missing arrival
+        assert!(
+            FlightBuilder::new(AirCarrierCode::from_str("DL").unwrap(), 594)
+                .departs(airport("SEA"), date_time(6, 24, 7, 15))
+                .build()
+                .is_err()
+        );
+        // arrives before it departs, once time zones are known
+        assert!(flight(
+            594,
+            "SEA",
+            date_time(6, 24, 7, 15),
+            "CUN",
+            date_time(6, 24, 9, 0)
+        )
+        .build()
+        .is_err());
+        // checks out before checking in
+        assert!(AccomodationBuilder::new("Hotel Xcaret")
+            .address(Location::new("Xcaret"))
+            .checkin(date_time(7, 1, 16, 0))
+            .checkout(date_time(6, 24, 11, 0))
+            .build()
+            .is_err());
+
+        let first = flight(
+            1471,
+            "SEA",
+            date_time(6, 24, 8, 0),
+            "ATL",
+            date_time(6, 24, 16, 0),
+        )
+        .build_segment()
+        .unwrap();
+        let wrong_airport = flight(
+            594,
+            "DFW",
+            date_time(6, 24, 17, 0),
+            "CUN",
+            date_time(6, 24, 19, 0),
+        )
+        .build_segment()
+        .unwrap();
+        let too_early = flight(
+            594,
+            "ATL",
+            date_time(6, 24, 15, 0),
+            "CUN",
+            date_time(6, 24, 17, 0),
+        )
+        .build_segment()
+        .unwrap();
+        assert!(JourneyBuilder::new().build().is_err());
+        assert!(JourneyBuilder::new()
+            .segment(first.clone())
+            .segment(wrong_airport)
+            .build()
+            .is_err());
+        assert!(JourneyBuilder::new()
+            .segment(first)
+            .segment(too_early)
+            .build()
+            .is_err());
+
+        // values common to items belong to the journey, not its segments
+        assert!(flight(
+            594,
+            "ATL",
+            date_time(6, 24, 17, 0),
+            "CUN",
+            date_time(6, 24, 19, 0),
+        )
+        .traveler("Bob")
+        .build_segment()
+        .is_err());
+    }
+
+    #[test]
+    fn test_itinerary_invariants() {
+        let hotel = AccomodationBuilder::new("Hotel Xcaret")
+            .address(Location::new("Playa del Carmen"))
+            .checkin(date_time(6, 24, 16, 0))
+            .checkout(date_time(7, 1, 11, 0));
+
+        assert!(ItineraryBuilder::new(" ", "Playa del Carmen")
+            .build()
+            .is_err());
+        assert!(ItineraryBuilder::new("2022-06", "Playa del Carmen")
+            .traveler(Traveler::new("Simon"))
+            .traveler(Traveler::new("Simon"))
+            .build()
+            .is_err());
+        assert!(ItineraryBuilder::new("2022-06", "Playa del Carmen")
+            .traveler(Traveler::new("Simon"))
+            .item(hotel.clone().traveler("Jane").build().unwrap())
+            .build()
+            .is_err());
+        assert!(ItineraryBuilder::new("2022-06", "Playa del Carmen")
+            .item(
+                hotel
+                    .clone()
+                    .link(ItemLink::Related(ItemId::from_str("event-1").unwrap()))
+                    .build()
+                    .unwrap()
+            )
+            .build()
+            .is_err());
+        let id = ItemId::from_str("hotel").unwrap();
+        assert!(ItineraryBuilder::new("2022-06", "Playa del Carmen")
+            .item(hotel.clone().id(id.clone()).build().unwrap())
+            .item(hotel.id(id).build().unwrap())
+            .build()
+            .is_err());
+        assert!(BookingBuilder::new("Expedia", "7261998331")
+            .build()
+            .is_err());
+    }
+}
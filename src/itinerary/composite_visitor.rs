@@ -0,0 +1,481 @@
+/*!
+A visitor that passes every callback on to a list of child visitors, so that several results
+(say a table, some statistics, and a validation report) can be produced from a single traversal.
+
+Children are called in the order they were added. What happens when a child returns an error
+depends on the composite's `ErrorPolicy`:
+
+* `ShortCircuit`; the error is returned at once, so no other child is called and the traversal
+  stops.
+* `CollectAll`; the child that failed is not called again but the others continue, and `end`
+  returns an error listing every failure. The individual errors are available from `errors`.
+
+Booking frames, day boundaries, and gaps are worked out once, for the composite as a whole, so
+every child must agree on whether cancelled items are included; if they do not, `start` returns
+an error before anything is visited.
+
+# Example
+
+```rust
+use terminal_travel::itinerary::composite_visitor::{CompositeVisitor, ErrorPolicy};
+use terminal_travel::itinerary::visitor::{visit_ordered_itinerary_mut, ItineraryVisitorMut, VisitOrder};
+use terminal_travel::itinerary::io::from_file;
+use terminal_travel::itinerary::Flight;
+use terminal_travel::error::Result;
+# use std::path::PathBuf;
+
+#[derive(Default)]
+struct Flights(usize);
+
+impl ItineraryVisitorMut for Flights {
+    fn flight_departs(&mut self, _: &Flight) -> Result<()> {
+        self.0 += 1;
+        Ok(())
+    }
+}
+
+# let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/data/playa.yml");
+let itinerary = from_file(&path).unwrap();
+let mut first = Flights::default();
+let mut second = Flights::default();
+let mut composite = CompositeVisitor::new(ErrorPolicy::ShortCircuit)
+    .with_visitor(&mut first)
+    .with_visitor(&mut second);
+visit_ordered_itinerary_mut(&itinerary, &mut composite, VisitOrder::Timing).unwrap();
+drop(composite);
+assert_eq!((first.0, second.0), (2, 2));
+```
+ */
+
+use super::{
+    Accomodation, Booking, Connection, Cruise, Date, Event, Flight, Item, Journey, PortCall, Rail,
+    RailChange, Transport, Vehicle,
+};
+use crate::error::{Error, ErrorKind, Result};
+use crate::itinerary::annotation::Annotations;
+use crate::itinerary::metadata::{Metadata, Summary};
+use crate::itinerary::traveler::Traveler;
+use crate::itinerary::visitor::{Gap, ItineraryVisitorMut};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// What a `CompositeVisitor` does when one of its children returns an error.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ErrorPolicy {
+    /// Return the first error immediately, stopping the traversal.
+    #[default]
+    ShortCircuit,
+    /// Stop calling the child that failed, carry on with the rest, and report every error at
+    /// the end.
+    CollectAll,
+}
+
+pub struct CompositeVisitor<'a> {
+    policy: ErrorPolicy,
+    children: Vec<Child<'a>>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+struct Child<'a> {
+    visitor: &'a mut dyn ItineraryVisitorMut,
+    error: Option<Error>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl<'a> CompositeVisitor<'a> {
+    pub fn new(policy: ErrorPolicy) -> Self {
+        Self {
+            policy,
+            children: Default::default(),
+        }
+    }
+
+    pub fn with_visitor(mut self, visitor: &'a mut dyn ItineraryVisitorMut) -> Self {
+        self.children.push(Child {
+            visitor,
+            error: None,
+        });
+        self
+    }
+
+    pub fn policy(&self) -> ErrorPolicy {
+        self.policy
+    }
+
+    pub fn len(&self) -> usize {
+        self.children.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.children.is_empty()
+    }
+
+    ///
+    /// The errors returned by children under `ErrorPolicy::CollectAll`, with the index of the
+    /// child that returned each.
+    ///
+    pub fn errors(&self) -> Vec<(usize, &Error)> {
+        self.children
+            .iter()
+            .enumerate()
+            .filter_map(|(index, child)| child.error.as_ref().map(|error| (index, error)))
+            .collect()
+    }
+
+    fn each<F>(&mut self, mut callback: F) -> Result<()>
+    where
+        F: FnMut(&mut dyn ItineraryVisitorMut) -> Result<()>,
+    {
+        let policy = self.policy;
+        for child in self
+            .children
+            .iter_mut()
+            .filter(|child| child.error.is_none())
+        {
+            if let Err(error) = callback(child.visitor) {
+                match policy {
+                    ErrorPolicy::ShortCircuit => return Err(error),
+                    ErrorPolicy::CollectAll => child.error = Some(error),
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl ItineraryVisitorMut for CompositeVisitor<'_> {
+    fn start(&mut self) -> Result<()> {
+        let include_cancelled = self.include_cancelled();
+        if self
+            .children
+            .iter()
+            .any(|child| child.visitor.include_cancelled() != include_cancelled)
+        {
+            return Err(ErrorKind::InvalidValue(
+                "composite visitor".to_string(),
+                "children must agree on whether cancelled items are included".to_string(),
+            )
+            .into());
+        }
+        self.each(|visitor| visitor.start())
+    }
+
+    fn identifier(&mut self, id: &str) -> Result<()> {
+        self.each(|visitor| visitor.identifier(id))
+    }
+
+    fn name(&mut self, name: &str) -> Result<()> {
+        self.each(|visitor| visitor.name(name))
+    }
+
+    fn summary(&mut self, metadata: &Metadata, summary: &Summary) -> Result<()> {
+        self.each(|visitor| visitor.summary(metadata, summary))
+    }
+
+    fn traveler(&mut self, traveler: &Traveler) -> Result<()> {
+        self.each(|visitor| visitor.traveler(traveler))
+    }
+
+    fn annotations(&mut self, annotations: &Annotations) -> Result<()> {
+        self.each(|visitor| visitor.annotations(annotations))
+    }
+
+    fn booking_start(&mut self, booking: &Booking) -> Result<()> {
+        self.each(|visitor| visitor.booking_start(booking))
+    }
+
+    fn booking_end(&mut self, booking: &Booking) -> Result<()> {
+        self.each(|visitor| visitor.booking_end(booking))
+    }
+
+    fn flight_departs(&mut self, flight: &Flight) -> Result<()> {
+        self.each(|visitor| visitor.flight_departs(flight))
+    }
+
+    fn flight_arrives(&mut self, flight: &Flight) -> Result<()> {
+        self.each(|visitor| visitor.flight_arrives(flight))
+    }
+
+    fn journey_departs(&mut self, journey: &Journey) -> Result<()> {
+        self.each(|visitor| visitor.journey_departs(journey))
+    }
+
+    fn journey_connection(&mut self, journey: &Journey, connection: &Connection<'_>) -> Result<()> {
+        self.each(|visitor| visitor.journey_connection(journey, connection))
+    }
+
+    fn journey_arrives(&mut self, journey: &Journey) -> Result<()> {
+        self.each(|visitor| visitor.journey_arrives(journey))
+    }
+
+    fn rail_departs(&mut self, rail: &Rail) -> Result<()> {
+        self.each(|visitor| visitor.rail_departs(rail))
+    }
+
+    fn rail_change(&mut self, rail: &Rail, change: &RailChange<'_>) -> Result<()> {
+        self.each(|visitor| visitor.rail_change(rail, change))
+    }
+
+    fn rail_arrives(&mut self, rail: &Rail) -> Result<()> {
+        self.each(|visitor| visitor.rail_arrives(rail))
+    }
+
+    fn cruise_embarks(&mut self, cruise: &Cruise) -> Result<()> {
+        self.each(|visitor| visitor.cruise_embarks(cruise))
+    }
+
+    fn cruise_port_arrival(&mut self, cruise: &Cruise, port_call: &PortCall) -> Result<()> {
+        self.each(|visitor| visitor.cruise_port_arrival(cruise, port_call))
+    }
+
+    fn cruise_port_departure(&mut self, cruise: &Cruise, port_call: &PortCall) -> Result<()> {
+        self.each(|visitor| visitor.cruise_port_departure(cruise, port_call))
+    }
+
+    fn cruise_disembarks(&mut self, cruise: &Cruise) -> Result<()> {
+        self.each(|visitor| visitor.cruise_disembarks(cruise))
+    }
+
+    fn transport_departs(&mut self, transport: &Transport) -> Result<()> {
+        self.each(|visitor| visitor.transport_departs(transport))
+    }
+
+    fn transport_arrives(&mut self, transport: &Transport) -> Result<()> {
+        self.each(|visitor| visitor.transport_arrives(transport))
+    }
+
+    fn vehicle_pickup(&mut self, vehicle: &Vehicle) -> Result<()> {
+        self.each(|visitor| visitor.vehicle_pickup(vehicle))
+    }
+
+    fn vehicle_dropoff(&mut self, vehicle: &Vehicle) -> Result<()> {
+        self.each(|visitor| visitor.vehicle_dropoff(vehicle))
+    }
+
+    fn accomodation_checkin(&mut self, accomodation: &Accomodation) -> Result<()> {
+        self.each(|visitor| visitor.accomodation_checkin(accomodation))
+    }
+
+    fn accomodation_checkout(&mut self, accomodation: &Accomodation) -> Result<()> {
+        self.each(|visitor| visitor.accomodation_checkout(accomodation))
+    }
+
+    fn event_start(&mut self, event: &Event) -> Result<()> {
+        self.each(|visitor| visitor.event_start(event))
+    }
+
+    fn event_end(&mut self, event: &Event) -> Result<()> {
+        self.each(|visitor| visitor.event_end(event))
+    }
+
+    fn item_annotations(&mut self, item: &Item, annotations: &Annotations) -> Result<()> {
+        self.each(|visitor| visitor.item_annotations(item, annotations))
+    }
+
+    fn day_start(&mut self, date: &Date) -> Result<()> {
+        self.each(|visitor| visitor.day_start(date))
+    }
+
+    fn day_end(&mut self, date: &Date) -> Result<()> {
+        self.each(|visitor| visitor.day_end(date))
+    }
+
+    fn idle_gap(&mut self, gap: &Gap) -> Result<()> {
+        self.each(|visitor| visitor.idle_gap(gap))
+    }
+
+    fn layover(&mut self, gap: &Gap) -> Result<()> {
+        self.each(|visitor| visitor.layover(gap))
+    }
+
+    fn unaccounted_nights(&mut self, gap: &Gap) -> Result<()> {
+        self.each(|visitor| visitor.unaccounted_nights(gap))
+    }
+
+    fn end(&mut self) -> Result<()> {
+        self.each(|visitor| visitor.end())?;
+        let errors: Vec<String> = self
+            .errors()
+            .into_iter()
+            .map(|(index, error)| format!("visitor {}: {}", index, error))
+            .collect();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ErrorKind::VisitorsFailed(errors).into())
+        }
+    }
+
+    fn include_cancelled(&self) -> bool {
+        self.children
+            .iter()
+            .any(|child| child.visitor.include_cancelled())
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::{CompositeVisitor, ErrorPolicy};
+    use crate::error::{ErrorKind, Result};
+    use crate::itinerary::io::from_str;
+    use crate::itinerary::visitor::{visit_ordered_itinerary_mut, ItineraryVisitorMut, VisitOrder};
+    use crate::itinerary::{Accomodation, Flight, Itinerary};
+    use pretty_assertions::assert_eq;
+
+    #[derive(Default)]
+    struct Recorder(Vec<String>, bool);
+
+    impl ItineraryVisitorMut for Recorder {
+        fn flight_departs(&mut self, flight: &Flight) -> Result<()> {
+            self.0
+                .push(format!("{} departs", flight.flight_number_string()));
+            Ok(())
+        }
+
+        fn accomodation_checkin(&mut self, accomodation: &Accomodation) -> Result<()> {
+            self.0.push(format!("{} checkin", accomodation.company()));
+            Ok(())
+        }
+
+        fn end(&mut self) -> Result<()> {
+            self.0.push("end".to_string());
+            Ok(())
+        }
+
+        fn include_cancelled(&self) -> bool {
+            self.1
+        }
+    }
+
+    struct FailsOnFlight;
+
+    impl ItineraryVisitorMut for FailsOnFlight {
+        fn flight_departs(&mut self, flight: &Flight) -> Result<()> {
+            Err(ErrorKind::UnknownItem(flight.flight_number_string()).into())
+        }
+    }
+
+    fn itinerary() -> Itinerary {
+        from_str(
+            r##"---
+identifier: 2022-06
+name: Playa del Carmen
+travelers: []
+bookings:
+  - agency: Booking.com
+    confirmation: "6015334"
+    items:
+      - accomodation:
+          status: cancelled
+          record:
+            provider: bric
+            start_date_time: "2022-06-26T16:00:00"
+            start_location: Playa del Carmen
+            end_date_time: "2022-07-05T11:00:00"
+items:
+  - flight:
+      number: 594
+      record:
+        provider: DL
+        start_date_time: "2022-06-24T07:15:00"
+        start_location: SEA
+        end_date_time: "2022-06-24T15:03:00"
+        end_location: CUN
+"##,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_fan_out_in_every_order() {
+        let itinerary = itinerary();
+        let orders = || vec![VisitOrder::Timing, VisitOrder::Booking, VisitOrder::AsIs];
+        for (order, same_order) in orders().into_iter().zip(orders()) {
+            let mut first = Recorder::default();
+            let mut second = Recorder::default();
+            let mut composite = CompositeVisitor::new(ErrorPolicy::ShortCircuit)
+                .with_visitor(&mut first)
+                .with_visitor(&mut second);
+            assert_eq!(composite.len(), 2);
+            visit_ordered_itinerary_mut(&itinerary, &mut composite, order).unwrap();
+            drop(composite);
+            assert_eq!(first.0, vec!["DL594 departs", "end"]);
+            assert_eq!(second.0, first.0);
+
+            let mut first = Recorder(Vec::new(), true);
+            let mut second = Recorder(Vec::new(), true);
+            let mut composite = CompositeVisitor::new(ErrorPolicy::ShortCircuit)
+                .with_visitor(&mut first)
+                .with_visitor(&mut second);
+            visit_ordered_itinerary_mut(&itinerary, &mut composite, same_order).unwrap();
+            drop(composite);
+            assert_eq!(first.0.len(), 3);
+            assert!(first.0.contains(&"bric checkin".to_string()));
+            assert_eq!(second.0, first.0);
+        }
+    }
+
+    #[test]
+    fn test_children_must_agree_on_cancelled() {
+        let itinerary = itinerary();
+        let mut first = Recorder::default();
+        let mut second = Recorder(Vec::new(), true);
+        let mut composite = CompositeVisitor::new(ErrorPolicy::CollectAll)
+            .with_visitor(&mut first)
+            .with_visitor(&mut second);
+        assert!(
+            visit_ordered_itinerary_mut(&itinerary, &mut composite, VisitOrder::Booking).is_err()
+        );
+        drop(composite);
+        assert!(first.0.is_empty());
+        assert!(second.0.is_empty());
+    }
+
+    #[test]
+    fn test_error_policies() {
+        let itinerary = itinerary();
+
+        let mut failing = FailsOnFlight;
+        let mut recorder = Recorder::default();
+        let mut composite = CompositeVisitor::new(ErrorPolicy::ShortCircuit)
+            .with_visitor(&mut failing)
+            .with_visitor(&mut recorder);
+        assert!(visit_ordered_itinerary_mut(&itinerary, &mut composite, VisitOrder::AsIs).is_err());
+        drop(composite);
+        assert!(recorder.0.is_empty());
+
+        let mut failing = FailsOnFlight;
+        let mut recorder = Recorder::default();
+        let mut composite = CompositeVisitor::new(ErrorPolicy::CollectAll)
+            .with_visitor(&mut failing)
+            .with_visitor(&mut recorder);
+        let result = visit_ordered_itinerary_mut(&itinerary, &mut composite, VisitOrder::AsIs);
+        assert!(result.is_err());
+        assert_eq!(
+            composite
+                .errors()
+                .into_iter()
+                .map(|(index, _)| index)
+                .collect::<Vec<usize>>(),
+            vec![0]
+        );
+        drop(composite);
+        assert_eq!(recorder.0, vec!["DL594 departs", "end"]);
+    }
+}
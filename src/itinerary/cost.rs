@@ -0,0 +1,609 @@
+/*!
+The cost of items in an itinerary, and what has been paid towards them.
+
+Amounts are held as decimals along with their ISO 4217 currency code; in an itinerary file each
+amount is a string such as `"1234.50 USD"`. Amounts in different currencies are never converted,
+so any rollup of costs is always grouped by currency.
+ */
+
+use_required!();
+use crate::error::{Error, ErrorKind};
+use crate::itinerary::{Date, Item, Itinerary};
+pub use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+is_valid_newstring!(
+    CurrencyCode,
+    is_valid_currency_code,
+    Hash,
+    Deserialize,
+    Serialize
+);
+
+///
+/// An amount of money in a single currency.
+///
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub struct Money {
+    amount: Decimal,
+    currency: CurrencyCode,
+}
+
+///
+/// The price of an item and the state of its payment.
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Cost {
+    total: Money,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    taxes: Option<Money>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    deposit_paid: Option<Money>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    balance_due: Option<Date>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    payment_method: Option<String>,
+}
+
+///
+/// The summed costs, in one currency, of a number of items.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct CostTotals {
+    currency: CurrencyCode,
+    total: Decimal,
+    taxes: Decimal,
+    deposit_paid: Decimal,
+    balance_due: Option<Date>,
+}
+
+///
+/// The costs of all the items in an itinerary, summed by currency and by traveler. An item that
+/// covers more than one traveler is split evenly between them; cancelled items are not included.
+///
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CostRollup {
+    by_currency: BTreeMap<CurrencyCode, CostTotals>,
+    by_traveler: Vec<(String, BTreeMap<CurrencyCode, CostTotals>)>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Display for Money {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.amount.round_dp(2), self.currency)
+    }
+}
+
+impl FromStr for Money {
+    type Err = Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let mut parts = s.split_whitespace();
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(amount), Some(currency), None) => {
+                let amount = Decimal::from_str(amount)
+                    .map_err(|_| Error::from(ErrorKind::InvalidMoney(s.to_string())))?;
+                let currency = CurrencyCode::from_str(&currency.to_uppercase())
+                    .map_err(|_| Error::from(ErrorKind::InvalidMoney(s.to_string())))?;
+                Ok(Self::new(amount, currency))
+            }
+            _ => Err(ErrorKind::InvalidMoney(s.to_string()).into()),
+        }
+    }
+}
+
+impl From<Money> for String {
+    fn from(v: Money) -> Self {
+        format!("{} {}", v.amount, v.currency)
+    }
+}
+
+impl TryFrom<String> for Money {
+    type Error = Error;
+
+    fn try_from(v: String) -> std::result::Result<Self, Self::Error> {
+        Self::from_str(&v)
+    }
+}
+
+impl Money {
+    pub fn new(amount: Decimal, currency: CurrencyCode) -> Self {
+        Self { amount, currency }
+    }
+
+    pub fn zero(currency: CurrencyCode) -> Self {
+        Self::new(Decimal::ZERO, currency)
+    }
+
+    pub fn amount(&self) -> &Decimal {
+        &self.amount
+    }
+
+    pub fn currency(&self) -> &CurrencyCode {
+        &self.currency
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.amount.is_zero()
+    }
+
+    ///
+    /// Add `other` to this amount, it is an error for the two to be in different currencies.
+    ///
+    pub fn checked_add(&self, other: &Money) -> crate::error::Result<Money> {
+        self.check_currency(other)?;
+        Ok(Self::new(self.amount + other.amount, self.currency.clone()))
+    }
+
+    ///
+    /// Subtract `other` from this amount, it is an error for the two to be in different
+    /// currencies.
+    ///
+    pub fn checked_sub(&self, other: &Money) -> crate::error::Result<Money> {
+        self.check_currency(other)?;
+        Ok(Self::new(self.amount - other.amount, self.currency.clone()))
+    }
+
+    fn check_currency(&self, other: &Money) -> crate::error::Result<()> {
+        if self.currency == other.currency {
+            Ok(())
+        } else {
+            Err(
+                ErrorKind::CurrencyMismatch(self.currency.to_string(), other.currency.to_string())
+                    .into(),
+            )
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Cost {
+    pub fn new(total: Money) -> Self {
+        Self {
+            total,
+            taxes: None,
+            deposit_paid: None,
+            balance_due: None,
+            payment_method: None,
+        }
+    }
+
+    pub fn total(&self) -> &Money {
+        &self.total
+    }
+
+    pub fn taxes(&self) -> Option<&Money> {
+        self.taxes.as_ref()
+    }
+
+    pub fn set_taxes(&mut self, taxes: Money) {
+        self.taxes = Some(taxes);
+    }
+
+    pub fn deposit_paid(&self) -> Option<&Money> {
+        self.deposit_paid.as_ref()
+    }
+
+    pub fn set_deposit_paid(&mut self, deposit_paid: Money) {
+        self.deposit_paid = Some(deposit_paid);
+    }
+
+    ///
+    /// The date by which any remaining balance must be paid.
+    ///
+    pub fn balance_due(&self) -> Option<&Date> {
+        self.balance_due.as_ref()
+    }
+
+    pub fn set_balance_due(&mut self, balance_due: Date) {
+        self.balance_due = Some(balance_due);
+    }
+
+    ///
+    /// A label for how the item was, or will be, paid for; e.g. "Amex x1004".
+    ///
+    pub fn payment_method(&self) -> Option<&String> {
+        self.payment_method.as_ref()
+    }
+
+    pub fn set_payment_method(&mut self, payment_method: &str) {
+        self.payment_method = Some(payment_method.to_string());
+    }
+
+    ///
+    /// The amount still to be paid; the total less any deposit. It is an error for the deposit to
+    /// be in a different currency to the total.
+    ///
+    pub fn balance(&self) -> crate::error::Result<Money> {
+        match &self.deposit_paid {
+            None => Ok(self.total.clone()),
+            Some(deposit_paid) => self.total.checked_sub(deposit_paid),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl CostTotals {
+    fn new(currency: CurrencyCode) -> Self {
+        Self {
+            currency,
+            total: Decimal::ZERO,
+            taxes: Decimal::ZERO,
+            deposit_paid: Decimal::ZERO,
+            balance_due: None,
+        }
+    }
+
+    pub fn currency(&self) -> &CurrencyCode {
+        &self.currency
+    }
+
+    pub fn total(&self) -> Money {
+        Money::new(self.total, self.currency.clone())
+    }
+
+    pub fn taxes(&self) -> Money {
+        Money::new(self.taxes, self.currency.clone())
+    }
+
+    pub fn deposit_paid(&self) -> Money {
+        Money::new(self.deposit_paid, self.currency.clone())
+    }
+
+    pub fn balance(&self) -> Money {
+        Money::new(self.total - self.deposit_paid, self.currency.clone())
+    }
+
+    ///
+    /// The earliest due date of any item with an outstanding balance in this currency.
+    ///
+    pub fn balance_due(&self) -> Option<&Date> {
+        self.balance_due.as_ref()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl CostRollup {
+    pub fn new(itinerary: &Itinerary) -> Self {
+        Self::new_of(itinerary, |_| true)
+    }
+
+    ///
+    /// The rollup, as for `new`, of only those items for which `is_included` returns `true`.
+    ///
+    pub(crate) fn new_of<F>(itinerary: &Itinerary, is_included: F) -> Self
+    where
+        F: Fn(&&Item) -> bool,
+    {
+        let mut rollup = Self {
+            by_currency: Default::default(),
+            by_traveler: itinerary
+                .travelers()
+                .map(|traveler| (traveler.name().clone(), Default::default()))
+                .collect(),
+        };
+        let all_travelers: Vec<String> = itinerary
+            .travelers()
+            .map(|traveler| traveler.name().clone())
+            .collect();
+        for item in itinerary
+            .items()
+            .filter(|item| !item.common().is_cancelled())
+            .filter(is_included)
+        {
+            let common = item.common();
+            if let Some(cost) = common.cost() {
+                let travelers: Vec<String> = if common.travelers().count() == 0 {
+                    all_travelers.clone()
+                } else {
+                    common.travelers().cloned().collect()
+                };
+                rollup.add(cost, &travelers);
+            }
+        }
+        rollup
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_currency.is_empty()
+    }
+
+    ///
+    /// The totals for the whole itinerary, one per currency used.
+    ///
+    pub fn currencies(&self) -> impl Iterator<Item = &CostTotals> {
+        self.by_currency.values()
+    }
+
+    pub fn currency(&self, currency: &CurrencyCode) -> Option<&CostTotals> {
+        self.by_currency.get(currency)
+    }
+
+    pub fn travelers(&self) -> impl Iterator<Item = &String> {
+        self.by_traveler.iter().map(|(name, _)| name)
+    }
+
+    ///
+    /// The totals for the named traveler, one per currency used by items covering them.
+    ///
+    pub fn traveler<'a>(&'a self, name: &str) -> impl Iterator<Item = &'a CostTotals> {
+        self.by_traveler
+            .iter()
+            .filter(|(traveler, _)| traveler == name)
+            .flat_map(|(_, totals)| totals.values())
+            .collect::<Vec<&'a CostTotals>>()
+            .into_iter()
+    }
+
+    fn add(&mut self, cost: &Cost, travelers: &[String]) {
+        add_cost(&mut self.by_currency, cost, 0, 1);
+        if !travelers.is_empty() {
+            for (share, name) in travelers.iter().enumerate() {
+                let index = match self.by_traveler.iter().position(|(v, _)| v == name) {
+                    Some(index) => index,
+                    None => {
+                        self.by_traveler.push((name.clone(), Default::default()));
+                        self.by_traveler.len() - 1
+                    }
+                };
+                add_cost(&mut self.by_traveler[index].1, cost, share, travelers.len());
+            }
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Add share number `share`, of `shares` equal shares, of `cost` to `totals`.
+///
+fn add_cost(
+    totals: &mut BTreeMap<CurrencyCode, CostTotals>,
+    cost: &Cost,
+    share: usize,
+    shares: usize,
+) {
+    fn entry<'a>(
+        totals: &'a mut BTreeMap<CurrencyCode, CostTotals>,
+        money: &Money,
+    ) -> &'a mut CostTotals {
+        totals
+            .entry(money.currency.clone())
+            .or_insert_with(|| CostTotals::new(money.currency.clone()))
+    }
+    entry(totals, &cost.total).total += share_of(&cost.total, share, shares);
+    if let Some(taxes) = &cost.taxes {
+        entry(totals, taxes).taxes += share_of(taxes, share, shares);
+    }
+    if let Some(deposit_paid) = &cost.deposit_paid {
+        entry(totals, deposit_paid).deposit_paid += share_of(deposit_paid, share, shares);
+    }
+    if let (Some(balance_due), Ok(balance)) = (&cost.balance_due, cost.balance()) {
+        if !balance.is_zero() {
+            let totals = entry(totals, &cost.total);
+            totals.balance_due = match totals.balance_due {
+                Some(current) if current <= *balance_due => Some(current),
+                _ => Some(*balance_due),
+            };
+        }
+    }
+}
+
+///
+/// Share number `share`, of `shares` equal shares, of `money`, kept to the same number of
+/// decimal places as the original amount. The last share takes any remainder left by rounding,
+/// so that the shares always add up to the original amount.
+///
+fn share_of(money: &Money, share: usize, shares: usize) -> Decimal {
+    let each = (money.amount / Decimal::from(shares)).round_dp(money.amount.scale());
+    if share + 1 < shares {
+        each
+    } else {
+        money.amount - each * Decimal::from(shares - 1)
+    }
+}
+
+#[inline]
+fn is_valid_currency_code(s: &str) -> bool {
+    s.len() == 3 && s.chars().all(|c| c.is_ascii_uppercase())
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::{Cost, CurrencyCode, Decimal, Money};
+    use crate::itinerary::filter::ItemFilter;
+    use crate::itinerary::io::from_str;
+    use crate::itinerary::{Date, ItemKind};
+    use pretty_assertions::assert_eq;
+    use std::str::FromStr;
+
+    fn money(s: &str) -> Money {
+        Money::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn test_money_strings() {
+        let value = money("1234.50 usd");
+        assert_eq!(value.amount(), &Decimal::new(123450, 2));
+        assert_eq!(value.currency(), &CurrencyCode::from_str("USD").unwrap());
+        assert_eq!(value.to_string(), "1234.50 USD".to_string());
+        assert_eq!(String::from(money("1000 JPY")), "1000 JPY".to_string());
+
+        assert!(Money::from_str("1234.50").is_err());
+        assert!(Money::from_str("USD 1234.50").is_err());
+        assert!(Money::from_str("12 US").is_err());
+        assert!(money("10 USD").checked_add(&money("10 EUR")).is_err());
+    }
+
+    #[test]
+    fn test_cost_balance() {
+        let yaml = r##"---
+total: 1200.00 USD
+taxes: 96.00 USD
+deposit_paid: 300.00 USD
+balance_due: 2022-05-24
+payment_method: Amex x1004
+"##;
+        let cost: Cost = serde_yaml::from_str(yaml).unwrap();
+
+        let mut expected = Cost::new(money("1200.00 USD"));
+        expected.set_taxes(money("96.00 USD"));
+        expected.set_deposit_paid(money("300.00 USD"));
+        expected.set_balance_due(Date::from_ymd_opt(2022, 5, 24).unwrap());
+        expected.set_payment_method("Amex x1004");
+
+        assert_eq!(cost, expected);
+        assert_eq!(cost.balance().unwrap(), money("900.00 USD"));
+        assert_eq!(serde_yaml::to_string(&cost).unwrap(), yaml.to_string());
+    }
+
+    #[test]
+    fn test_itinerary_rollup() {
+        let itinerary = from_str(
+            r##"---
+identifier: 2022-06
+name: Playa del Carmen
+travelers:
+  - Simon
+  - Alice
+items:
+  - flight:
+      number: 594
+      cost:
+        total: 800.00 USD
+        taxes: 120.00 USD
+      record:
+        provider: DL
+        start_date_time: "2022-06-24T07:15:00"
+        start_location: SEA
+        end_date_time: "2022-06-24T15:03:00"
+        end_location: CUN
+  - accomodation:
+      cost:
+        total: 24000 MXN
+        deposit_paid: 6000 MXN
+        balance_due: 2022-06-20
+      record:
+        provider: Hotel Xcaret
+        start_date_time: "2022-06-24T16:00:00"
+        start_location: Playa del Carmen
+        end_date_time: "2022-07-01T11:00:00"
+  - event:
+      travelers:
+        - Alice
+      cost:
+        total: 150.00 USD
+      record:
+        provider: Xcaret
+        start_date_time: "2022-06-27T09:00:00"
+        start_location: Xcaret Park
+        end_date_time: "2022-06-27T17:00:00"
+"##,
+        )
+        .unwrap();
+        let rollup = itinerary.cost_rollup();
+
+        let usd = rollup
+            .currency(&CurrencyCode::from_str("USD").unwrap())
+            .unwrap();
+        assert_eq!(usd.total(), money("950.00 USD"));
+        assert_eq!(usd.taxes(), money("120.00 USD"));
+        assert_eq!(usd.balance(), money("950.00 USD"));
+        assert_eq!(usd.balance_due(), None);
+
+        let mxn = rollup
+            .currency(&CurrencyCode::from_str("MXN").unwrap())
+            .unwrap();
+        assert_eq!(mxn.balance(), money("18000 MXN"));
+        assert_eq!(mxn.balance_due(), Date::from_ymd_opt(2022, 6, 20).as_ref());
+
+        assert_eq!(
+            rollup
+                .traveler("Alice")
+                .map(|totals| totals.total().to_string())
+                .collect::<Vec<String>>(),
+            vec!["12000 MXN".to_string(), "550.00 USD".to_string()]
+        );
+        assert_eq!(
+            rollup
+                .traveler("Simon")
+                .map(|totals| totals.total().to_string())
+                .collect::<Vec<String>>(),
+            vec!["12000 MXN".to_string(), "400.00 USD".to_string()]
+        );
+
+        let filter = ItemFilter::default().with_kind(ItemKind::Event);
+        let rollup = itinerary.cost_rollup_of(&filter);
+        assert_eq!(
+            rollup
+                .currencies()
+                .map(|totals| totals.total().to_string())
+                .collect::<Vec<String>>(),
+            vec!["150.00 USD".to_string()]
+        );
+        assert_eq!(rollup.traveler("Simon").count(), 0);
+    }
+
+    #[test]
+    fn test_traveler_shares_add_up() {
+        let itinerary = from_str(
+            r##"---
+identifier: 2022-06
+name: Playa del Carmen
+travelers:
+  - Simon
+  - Alice
+  - Bob
+items:
+  - event:
+      cost:
+        total: 100.00 USD
+        taxes: 10.00 USD
+      record:
+        provider: Xcaret
+        start_date_time: "2022-06-27T09:00:00"
+        start_location: Xcaret Park
+        end_date_time: "2022-06-27T17:00:00"
+"##,
+        )
+        .unwrap();
+        let rollup = itinerary.cost_rollup();
+        let shares: Vec<(String, String)> = ["Simon", "Alice", "Bob"]
+            .iter()
+            .flat_map(|name| rollup.traveler(name))
+            .map(|totals| (totals.total().to_string(), totals.taxes().to_string()))
+            .collect();
+        assert_eq!(
+            shares,
+            vec![
+                ("33.33 USD".to_string(), "3.33 USD".to_string()),
+                ("33.33 USD".to_string(), "3.33 USD".to_string()),
+                ("33.34 USD".to_string(), "3.34 USD".to_string()),
+            ]
+        );
+        let sum = ["Simon", "Alice", "Bob"]
+            .iter()
+            .flat_map(|name| rollup.traveler(name))
+            .fold(Decimal::ZERO, |sum, totals| sum + totals.total().amount());
+        assert_eq!(sum, *money("100.00 USD").amount());
+    }
+}
@@ -4,12 +4,26 @@ More detailed description, with
 # Example
  */
 
-use crate::error::Result;
-use crate::itinerary::visitor::{visit_ordered_itinerary, ItineraryVisitor, VisitOrder};
+use crate::error::{ErrorKind, Result};
+use crate::itinerary::annotation::Annotations;
+use crate::itinerary::cost::{Cost, CostRollup};
+use crate::itinerary::filter::ItemFilter;
+use crate::itinerary::flight_details::AirportGate;
+use crate::itinerary::metadata::{Metadata, Summary};
+use crate::itinerary::status::ItemStatus;
+use crate::itinerary::traveler::Traveler;
+use crate::itinerary::visitor::{
+    visit_filtered_itinerary, visit_filtered_itinerary_mut, Gap, ItineraryVisitor,
+    ItineraryVisitorMut, VisitOrder,
+};
 use crate::itinerary::Itinerary;
-use crate::itinerary::{Accomodation, Event, Flight, Transport, TravelRecord, Vehicle};
+use crate::itinerary::{
+    Accomodation, Booking, Connection, Cruise, Date, DateTime, Duration, Event, Flight,
+    FlightSegment, Item, ItemCommon, Journey, PortCall, Rail, RailChange, RailLeg, Transport,
+    TravelRecord, Vehicle,
+};
 use console::Term;
-use std::cell::RefCell;
+use std::cell::Cell;
 use std::collections::BTreeMap;
 use std::fmt::Display;
 use std::str::FromStr;
@@ -18,30 +32,89 @@ use std::str::FromStr;
 // Public Types
 // ------------------------------------------------------------------------------------------------
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub enum DisplayFormat {
+    #[default]
     NuTable,
     Cards,
     Indented,
 }
 
+///
+/// Options that apply to every display format.
+///
+#[derive(Clone, Debug, Default)]
+pub struct DisplayOptions {
+    traveler: Option<String>,
+    show_notes: bool,
+    filter: ItemFilter,
+}
+
 // ------------------------------------------------------------------------------------------------
 // Public Functions
 // ------------------------------------------------------------------------------------------------
 
 pub fn display_itinerary(itinerary: &Itinerary, format: DisplayFormat) -> Result<()> {
+    display_itinerary_with(itinerary, format, &DisplayOptions::default())
+}
+
+///
+/// Display the itinerary as seen by the named traveler; only the items that cover them are shown.
+///
+pub fn display_traveler_itinerary(
+    itinerary: &Itinerary,
+    traveler: &str,
+    format: DisplayFormat,
+) -> Result<()> {
+    display_itinerary_with(
+        itinerary,
+        format,
+        &DisplayOptions::default().for_traveler(traveler),
+    )
+}
+
+pub fn display_itinerary_with(
+    itinerary: &Itinerary,
+    format: DisplayFormat,
+    options: &DisplayOptions,
+) -> Result<()> {
+    let traveler_itinerary;
+    let itinerary = match &options.traveler {
+        Some(traveler) => match itinerary.for_traveler(traveler) {
+            Some(v) => {
+                traveler_itinerary = v;
+                &traveler_itinerary
+            }
+            None => return Err(ErrorKind::UnknownTraveler(traveler.to_string()).into()),
+        },
+        None => itinerary,
+    };
     match format {
         DisplayFormat::NuTable => {
-            let visitor = NuTable(Term::stdout(), Default::default());
-            visit_ordered_itinerary(itinerary, &visitor, VisitOrder::AsIs)?;
+            let mut visitor = NuTable(Term::stdout(), Default::default());
+            visit_filtered_itinerary_mut(
+                itinerary,
+                &mut visitor,
+                VisitOrder::AsIs,
+                &options.filter,
+            )?;
         }
         DisplayFormat::Cards => {
-            let visitor = Cards(Term::stdout());
-            visit_ordered_itinerary(itinerary, &visitor, VisitOrder::Booking)?;
+            let visitor = Cards(
+                Term::stdout(),
+                itinerary.cost_rollup_of(&options.filter),
+                options.show_notes,
+            );
+            visit_filtered_itinerary(itinerary, &visitor, VisitOrder::Booking, &options.filter)?;
         }
         DisplayFormat::Indented => {
-            let visitor = Indented(Term::stdout());
-            visit_ordered_itinerary(itinerary, &visitor, VisitOrder::Timing)?;
+            let visitor = Indented(
+                Term::stdout(),
+                itinerary.cost_rollup_of(&options.filter),
+                options.show_notes,
+                Default::default(),
+            );
+            visit_filtered_itinerary(itinerary, &visitor, VisitOrder::Timing, &options.filter)?;
         }
     }
     Ok(())
@@ -51,25 +124,28 @@ pub fn display_itinerary(itinerary: &Itinerary, format: DisplayFormat) -> Result
 // Private Types
 // ------------------------------------------------------------------------------------------------
 
+const DISPLAY_DATE_TIME_FORMAT: &str = "%a %e %b %Y %H:%M";
+
+const DISPLAY_DATE_FORMAT: &str = "%a %e %b %Y";
+
+const DISPLAY_TIME_FORMAT: &str = "%H:%M";
+
 #[derive(Debug)]
-pub struct NuTable(Term, RefCell<Vec<BTreeMap<String, String>>>);
+pub struct NuTable(Term, Vec<BTreeMap<String, String>>);
 
 #[derive(Debug)]
-pub struct Cards(Term);
+pub struct Cards(Term, CostRollup, bool);
 
+///
+/// The itinerary day by day, in timing order; the last field is the day currently being written.
+///
 #[derive(Debug)]
-pub struct Indented(Term);
+pub struct Indented(Term, CostRollup, bool, Cell<Option<Date>>);
 
 // ------------------------------------------------------------------------------------------------
 // Implementations
 // ------------------------------------------------------------------------------------------------
 
-impl Default for DisplayFormat {
-    fn default() -> Self {
-        Self::NuTable
-    }
-}
-
 impl Display for DisplayFormat {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -99,49 +175,194 @@ impl FromStr for DisplayFormat {
 
 // ------------------------------------------------------------------------------------------------
 
-impl ItineraryVisitor for NuTable {
-    fn flight_departs(&self, flight: &Flight) -> Result<()> {
+impl DisplayOptions {
+    ///
+    /// Only show the items that cover the named traveler.
+    ///
+    pub fn for_traveler(self, traveler: &str) -> Self {
+        Self {
+            traveler: Some(traveler.to_string()),
+            ..self
+        }
+    }
+
+    ///
+    /// Show the notes, tags, and attachments of the itinerary and its items.
+    ///
+    pub fn with_notes(self, show_notes: bool) -> Self {
+        Self { show_notes, ..self }
+    }
+
+    ///
+    /// Only show the items selected by `filter`.
+    ///
+    pub fn with_filter(self, filter: ItemFilter) -> Self {
+        Self { filter, ..self }
+    }
+
+    pub fn traveler(&self) -> Option<&String> {
+        self.traveler.as_ref()
+    }
+
+    pub fn filter(&self) -> &ItemFilter {
+        &self.filter
+    }
+
+    pub fn show_notes(&self) -> bool {
+        self.show_notes
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl ItineraryVisitorMut for NuTable {
+    fn flight_departs(&mut self, flight: &Flight) -> Result<()> {
         let record = flight.inner();
-        let row = to_hashmap("flight", record, &flight.number().to_string(), "");
-        let _ = self.1.borrow_mut().push(row);
+        let mut row = to_hashmap(
+            "flight",
+            flight.common(),
+            record,
+            &flight.number().to_string(),
+            "",
+        );
+        let _ = row.insert(
+            "cabin".to_string(),
+            flight.cabin().map(|v| v.to_string()).unwrap_or_default(),
+        );
+        let _ = row.insert(
+            "seats".to_string(),
+            flight
+                .passengers()
+                .filter_map(|v| v.seat().map(|seat| format!("{} {}", v.traveler(), seat)))
+                .collect::<Vec<String>>()
+                .join(","),
+        );
+        self.1.push(row);
         Ok(())
     }
 
-    fn transport_departs(&self, transport: &Transport) -> Result<()> {
+    fn journey_departs(&mut self, journey: &Journey) -> Result<()> {
+        let first = journey.first_segment().inner();
+        let last = journey.last_segment().inner();
+        let mut row = to_hashmap(
+            "journey",
+            journey.common(),
+            first,
+            &journey
+                .segments()
+                .map(|segment| segment.number().to_string())
+                .collect::<Vec<String>>()
+                .join("/"),
+            "",
+        );
+        let _ = row.insert(
+            "confirmation".to_string(),
+            journey.record_locator().cloned().unwrap_or_default(),
+        );
+        let _ = row.insert("end_date_time".to_string(), last.end_date_time.to_string());
+        let _ = row.insert(
+            "end_time_zone".to_string(),
+            last.end_time_zone()
+                .map(|v| v.to_string())
+                .unwrap_or_default(),
+        );
+        let _ = row.insert(
+            "end_location".to_string(),
+            or_unknown(journey.arrival_airport()),
+        );
+        let _ = row.insert(
+            "connections".to_string(),
+            journey
+                .connection_airports()
+                .map(|v| v.to_string())
+                .collect::<Vec<String>>()
+                .join(","),
+        );
+        self.1.push(row);
+        Ok(())
+    }
+
+    fn rail_departs(&mut self, rail: &Rail) -> Result<()> {
+        let first = rail.first_leg().inner();
+        let last = rail.last_leg().inner();
+        let mut row = to_hashmap("rail", rail.common(), first, "", "");
+        let _ = row.insert(
+            "confirmation".to_string(),
+            rail.ticket_reference().cloned().unwrap_or_default(),
+        );
+        let _ = row.insert("train_number".to_string(), rail.train_numbers_string());
+        let _ = row.insert("end_date_time".to_string(), last.end_date_time.to_string());
+        let _ = row.insert(
+            "end_time_zone".to_string(),
+            last.end_time_zone()
+                .map(|v| v.to_string())
+                .unwrap_or_default(),
+        );
+        let _ = row.insert(
+            "end_location".to_string(),
+            or_unknown(rail.arrival_station()),
+        );
+        let _ = row.insert(
+            "connections".to_string(),
+            rail.change_stations()
+                .map(|v| v.to_string())
+                .collect::<Vec<String>>()
+                .join(","),
+        );
+        self.1.push(row);
+        Ok(())
+    }
+
+    fn cruise_embarks(&mut self, cruise: &Cruise) -> Result<()> {
+        let mut row = to_hashmap("cruise", cruise.common(), cruise.inner(), "", "");
+        let _ = row.insert(
+            "connections".to_string(),
+            cruise
+                .port_calls()
+                .map(|v| v.port().to_string())
+                .collect::<Vec<String>>()
+                .join(","),
+        );
+        self.1.push(row);
+        Ok(())
+    }
+
+    fn transport_departs(&mut self, transport: &Transport) -> Result<()> {
         let record = transport.inner();
         let row = to_hashmap(
             "transport",
+            transport.common(),
             record,
             "",
             &transport.kind().map(|v| v.to_string()).unwrap_or_default(),
         );
-        let _ = self.1.borrow_mut().push(row);
+        self.1.push(row);
         Ok(())
     }
 
-    fn vehicle_pickup(&self, vehicle: &Vehicle) -> Result<()> {
+    fn vehicle_pickup(&mut self, vehicle: &Vehicle) -> Result<()> {
         let record = vehicle.inner();
-        let row = to_hashmap("vehicle", record, "", "");
-        let _ = self.1.borrow_mut().push(row);
+        let row = to_hashmap("vehicle", vehicle.common(), record, "", "");
+        self.1.push(row);
         Ok(())
     }
 
-    fn accomodation_checkin(&self, accomodation: &Accomodation) -> Result<()> {
+    fn accomodation_checkin(&mut self, accomodation: &Accomodation) -> Result<()> {
         let record = accomodation.inner();
-        let row = to_hashmap("accomodation", record, "", "");
-        let _ = self.1.borrow_mut().push(row);
+        let row = to_hashmap("accomodation", accomodation.common(), record, "", "");
+        self.1.push(row);
         Ok(())
     }
 
-    fn event_start(&self, event: &Event) -> Result<()> {
+    fn event_start(&mut self, event: &Event) -> Result<()> {
         let record = event.inner();
-        let row = to_hashmap("event", record, "", "");
-        let _ = self.1.borrow_mut().push(row);
+        let row = to_hashmap("event", event.common(), record, "", "");
+        self.1.push(row);
         Ok(())
     }
 
-    fn end(&self) -> Result<()> {
-        let inner = self.1.replace(Default::default());
+    fn end(&mut self) -> Result<()> {
+        let inner = std::mem::take(&mut self.1);
         self.0.write_line(&serde_json::to_string(&inner)?)?;
         Ok(())
     }
@@ -149,18 +370,889 @@ impl ItineraryVisitor for NuTable {
 
 // ------------------------------------------------------------------------------------------------
 
-impl ItineraryVisitor for Cards {}
+impl ItineraryVisitor for Cards {
+    fn name(&self, name: &str) -> Result<()> {
+        self.0.write_line(name)?;
+        Ok(())
+    }
+
+    fn summary(&self, metadata: &Metadata, summary: &Summary) -> Result<()> {
+        write_summary(&self.0, "  ", metadata, summary)
+    }
+
+    fn traveler(&self, traveler: &Traveler) -> Result<()> {
+        self.0.write_line(&format!("  {}", traveler))?;
+        Ok(())
+    }
+
+    fn annotations(&self, annotations: &Annotations) -> Result<()> {
+        if self.2 {
+            write_annotations(&self.0, "  ", annotations)?;
+        }
+        Ok(())
+    }
+
+    fn item_annotations(&self, _: &Item, annotations: &Annotations) -> Result<()> {
+        if self.2 {
+            write_annotations(&self.0, "    ", annotations)?;
+        }
+        Ok(())
+    }
+
+    fn booking_start(&self, booking: &Booking) -> Result<()> {
+        self.0.write_line("")?;
+        self.0.write_line(&format!(
+            "== {} ({}) ==",
+            booking.agency(),
+            booking.confirmation()
+        ))?;
+        Ok(())
+    }
+
+    fn booking_end(&self, booking: &Booking) -> Result<()> {
+        self.0
+            .write_line(&format!("== end of {} ==", booking.confirmation()))?;
+        Ok(())
+    }
+
+    fn flight_departs(&self, flight: &Flight) -> Result<()> {
+        self.write_card(
+            &format!("Flight {}", flight.flight_number_string()),
+            flight.common(),
+            flight.inner(),
+            &flight_details(flight),
+        )
+    }
+
+    fn journey_departs(&self, journey: &Journey) -> Result<()> {
+        self.0.write_line("")?;
+        self.0.write_line(&match journey.record_locator() {
+            Some(record_locator) => format!(
+                "  Journey {} [{}]{}",
+                journey.flight_numbers_string(),
+                record_locator,
+                status_marker(journey.common())
+            ),
+            None => format!(
+                "  Journey {}{}",
+                journey.flight_numbers_string(),
+                status_marker(journey.common())
+            ),
+        })?;
+        for (index, segment) in journey.segments().enumerate() {
+            if index > 0 {
+                let connection = &journey.connections()[index - 1];
+                self.0.write_line(&format!(
+                    "      connect at {}, layover {}",
+                    connection.airport(),
+                    format_duration(&connection.layover())
+                ))?;
+            }
+            self.0.write_line(&format!(
+                "    {}, {} -> {}",
+                segment_description(segment),
+                segment
+                    .departure_date_time()
+                    .format(DISPLAY_DATE_TIME_FORMAT),
+                segment.arrival_date_time().format(DISPLAY_DATE_TIME_FORMAT),
+            ))?;
+            for detail in flight_details(segment) {
+                self.0.write_line(&format!("      {}", detail))?;
+            }
+        }
+        self.write_cost(journey.common())
+    }
+
+    fn rail_departs(&self, rail: &Rail) -> Result<()> {
+        self.0.write_line("")?;
+        self.0.write_line(&match rail.ticket_reference() {
+            Some(ticket_reference) => format!(
+                "  Rail {} [{}]{}",
+                rail.train_numbers_string(),
+                ticket_reference,
+                status_marker(rail.common())
+            ),
+            None => format!(
+                "  Rail {}{}",
+                rail.train_numbers_string(),
+                status_marker(rail.common())
+            ),
+        })?;
+        for (index, leg) in rail.legs().enumerate() {
+            if index > 0 {
+                let change = &rail.changes()[index - 1];
+                self.0.write_line(&format!(
+                    "      change at {}, {}",
+                    change.station(),
+                    format_duration(&change.change_time())
+                ))?;
+            }
+            self.0.write_line(&format!(
+                "    {}, {} -> {}",
+                leg_description(leg),
+                leg.departure_date_time().format(DISPLAY_DATE_TIME_FORMAT),
+                leg.arrival_date_time().format(DISPLAY_DATE_TIME_FORMAT),
+            ))?;
+        }
+        self.write_cost(rail.common())
+    }
+
+    fn cruise_embarks(&self, cruise: &Cruise) -> Result<()> {
+        self.0.write_line("")?;
+        self.0.write_line(&match cruise.confirmation() {
+            Some(confirmation) => format!(
+                "  {} {} [{}]{}",
+                cruise.cruise_line(),
+                cruise.ship(),
+                confirmation,
+                status_marker(cruise.common())
+            ),
+            None => format!(
+                "  {} {}{}",
+                cruise.cruise_line(),
+                cruise.ship(),
+                status_marker(cruise.common())
+            ),
+        })?;
+        if let Some(cabin) = cruise.cabin() {
+            self.0.write_line(&format!("    cabin {}", cabin))?;
+        }
+        self.0.write_line(&format!(
+            "    embark {}, {}",
+            cruise.embarkation_port(),
+            cruise
+                .embarkation_date_time()
+                .format(DISPLAY_DATE_TIME_FORMAT)
+        ))?;
+        for port_call in cruise.port_calls() {
+            self.0.write_line(&format!(
+                "      {}, {} -> {}",
+                port_call.port(),
+                port_call
+                    .arrival_date_time()
+                    .format(DISPLAY_DATE_TIME_FORMAT),
+                port_call
+                    .departure_date_time()
+                    .format(DISPLAY_DATE_TIME_FORMAT)
+            ))?;
+        }
+        self.0.write_line(&format!(
+            "    disembark {}, {}",
+            cruise.disembarkation_port(),
+            cruise
+                .disembarkation_date_time()
+                .format(DISPLAY_DATE_TIME_FORMAT)
+        ))?;
+        let days_at_sea = cruise.days_at_sea();
+        if !days_at_sea.is_empty() {
+            self.0.write_line(&format!(
+                "    {} at sea: {}",
+                plural(days_at_sea.len(), "day"),
+                days_at_sea
+                    .iter()
+                    .map(|date| date.format(DISPLAY_DATE_FORMAT).to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ))?;
+        }
+        self.write_cost(cruise.common())
+    }
+
+    fn transport_departs(&self, transport: &Transport) -> Result<()> {
+        self.write_card(
+            &match transport.kind() {
+                Some(kind) => format!("{} {}", transport.company_or_agency(), kind),
+                None => transport.company_or_agency().to_string(),
+            },
+            transport.common(),
+            transport.inner(),
+            &[],
+        )
+    }
+
+    fn vehicle_pickup(&self, vehicle: &Vehicle) -> Result<()> {
+        self.write_card(
+            &format!("Vehicle {}", vehicle.rental_agency()),
+            vehicle.common(),
+            vehicle.inner(),
+            &[],
+        )
+    }
+
+    fn accomodation_checkin(&self, accomodation: &Accomodation) -> Result<()> {
+        self.write_card(
+            accomodation.company(),
+            accomodation.common(),
+            accomodation.inner(),
+            &[],
+        )
+    }
+
+    fn event_start(&self, event: &Event) -> Result<()> {
+        self.write_card(event.company(), event.common(), event.inner(), &[])
+    }
+
+    fn end(&self) -> Result<()> {
+        write_cost_totals(&self.0, &self.1)
+    }
+}
+
+impl Cards {
+    fn write_card<P, L>(
+        &self,
+        title: &str,
+        common: &ItemCommon,
+        record: &TravelRecord<P, L>,
+        details: &[String],
+    ) -> Result<()>
+    where
+        L: Display,
+    {
+        self.0.write_line("")?;
+        self.0.write_line(&match &record.confirmation {
+            Some(confirmation) => {
+                format!("  {} [{}]{}", title, confirmation, status_marker(common))
+            }
+            None => format!("  {}{}", title, status_marker(common)),
+        })?;
+        self.0.write_line(&match &record.end_location {
+            Some(end_location) => format!("    {} -> {}", record.start_location, end_location),
+            None => format!("    {}", record.start_location),
+        })?;
+        self.0.write_line(&format!(
+            "    {} -> {}",
+            record.start_date_time.format(DISPLAY_DATE_TIME_FORMAT),
+            record.end_date_time.format(DISPLAY_DATE_TIME_FORMAT),
+        ))?;
+        for detail in details {
+            self.0.write_line(&format!("    {}", detail))?;
+        }
+        self.write_cost(common)
+    }
+
+    fn write_cost(&self, common: &ItemCommon) -> Result<()> {
+        if let Some(cost) = common.cost() {
+            self.0
+                .write_line(&format!("    Cost {}", cost_description(cost)))?;
+        }
+        Ok(())
+    }
+}
 
 // ------------------------------------------------------------------------------------------------
 
-impl ItineraryVisitor for Indented {}
+impl ItineraryVisitor for Indented {
+    fn identifier(&self, id: &str) -> Result<()> {
+        self.0.write_line(&format!("[{}]", id))?;
+        Ok(())
+    }
+
+    fn name(&self, name: &str) -> Result<()> {
+        self.0.write_line(name)?;
+        Ok(())
+    }
+
+    fn summary(&self, metadata: &Metadata, summary: &Summary) -> Result<()> {
+        write_summary(&self.0, "  ", metadata, summary)
+    }
+
+    fn traveler(&self, traveler: &Traveler) -> Result<()> {
+        self.0.write_line(&format!("  {}", traveler))?;
+        Ok(())
+    }
+
+    fn annotations(&self, annotations: &Annotations) -> Result<()> {
+        if self.2 {
+            write_annotations(&self.0, "  ", annotations)?;
+        }
+        Ok(())
+    }
+
+    fn item_annotations(&self, _: &Item, annotations: &Annotations) -> Result<()> {
+        if self.2 {
+            write_annotations(&self.0, "             ", annotations)?;
+        }
+        Ok(())
+    }
+
+    fn day_start(&self, date: &Date) -> Result<()> {
+        self.0.write_line("")?;
+        self.0
+            .write_line(&format!("  {}", date.format(DISPLAY_DATE_FORMAT)))?;
+        self.3.set(Some(*date));
+        Ok(())
+    }
+
+    fn day_end(&self, _: &Date) -> Result<()> {
+        self.3.set(None);
+        Ok(())
+    }
+
+    fn idle_gap(&self, gap: &Gap) -> Result<()> {
+        self.write_gap(&format!("Free for {}", format_duration(&gap.duration())))
+    }
+
+    fn layover(&self, gap: &Gap) -> Result<()> {
+        self.write_gap(&format!("Layover of {}", format_duration(&gap.duration())))
+    }
+
+    fn unaccounted_nights(&self, gap: &Gap) -> Result<()> {
+        self.write_gap(&format!(
+            "No accomodation for {}",
+            plural(gap.nights() as usize, "night")
+        ))
+    }
+
+    fn flight_departs(&self, flight: &Flight) -> Result<()> {
+        self.write_indented(
+            flight.common(),
+            flight.departure_date_time(),
+            &format!(
+                "Flight {} departs {}{}",
+                flight.flight_number_string(),
+                flight.departure_airport(),
+                gate_description(flight.departure_gate())
+            ),
+        )
+    }
+
+    fn flight_arrives(&self, flight: &Flight) -> Result<()> {
+        self.write_indented(
+            flight.common(),
+            flight.arrival_date_time(),
+            &format!(
+                "Flight {} arrives {}{}",
+                flight.flight_number_string(),
+                or_unknown(flight.arrival_airport()),
+                gate_description(flight.arrival_gate())
+            ),
+        )
+    }
+
+    fn journey_departs(&self, journey: &Journey) -> Result<()> {
+        self.write_indented(
+            journey.common(),
+            journey.departure_date_time(),
+            &format!(
+                "Journey {} departs {}",
+                journey.flight_numbers_string(),
+                journey.departure_airport()
+            ),
+        )?;
+        self.write_indented(
+            journey.common(),
+            journey.departure_date_time(),
+            &format!("  {}", segment_description(journey.first_segment())),
+        )
+    }
+
+    fn journey_connection(&self, journey: &Journey, connection: &Connection<'_>) -> Result<()> {
+        self.write_indented(
+            journey.common(),
+            connection.arriving().arrival_date_time(),
+            &format!(
+                "  Connect at {}, layover {}",
+                connection.airport(),
+                format_duration(&connection.layover())
+            ),
+        )?;
+        self.write_indented(
+            journey.common(),
+            connection.departing().departure_date_time(),
+            &format!("  {}", segment_description(connection.departing())),
+        )
+    }
+
+    fn journey_arrives(&self, journey: &Journey) -> Result<()> {
+        self.write_indented(
+            journey.common(),
+            journey.arrival_date_time(),
+            &format!(
+                "Journey {} arrives {}",
+                journey.flight_numbers_string(),
+                or_unknown(journey.arrival_airport())
+            ),
+        )
+    }
+
+    fn rail_departs(&self, rail: &Rail) -> Result<()> {
+        self.write_indented(
+            rail.common(),
+            rail.departure_date_time(),
+            &format!(
+                "Rail {} departs {}",
+                rail.train_numbers_string(),
+                rail.departure_station()
+            ),
+        )?;
+        self.write_indented(
+            rail.common(),
+            rail.departure_date_time(),
+            &format!("  {}", leg_description(rail.first_leg())),
+        )
+    }
+
+    fn rail_change(&self, rail: &Rail, change: &RailChange<'_>) -> Result<()> {
+        self.write_indented(
+            rail.common(),
+            change.arriving().arrival_date_time(),
+            &format!(
+                "  Change at {}, {}",
+                change.station(),
+                format_duration(&change.change_time())
+            ),
+        )?;
+        self.write_indented(
+            rail.common(),
+            change.departing().departure_date_time(),
+            &format!("  {}", leg_description(change.departing())),
+        )
+    }
+
+    fn rail_arrives(&self, rail: &Rail) -> Result<()> {
+        self.write_indented(
+            rail.common(),
+            rail.arrival_date_time(),
+            &format!(
+                "Rail {} arrives {}",
+                rail.train_numbers_string(),
+                or_unknown(rail.arrival_station())
+            ),
+        )
+    }
+
+    fn cruise_embarks(&self, cruise: &Cruise) -> Result<()> {
+        self.write_indented(
+            cruise.common(),
+            cruise.embarkation_date_time(),
+            &format!(
+                "Embark {} {} at {}",
+                cruise.cruise_line(),
+                cruise.ship(),
+                cruise.embarkation_port()
+            ),
+        )?;
+        self.write_days_at_sea(cruise, cruise.embarkation_date_time())
+    }
+
+    fn cruise_port_arrival(&self, cruise: &Cruise, port_call: &PortCall) -> Result<()> {
+        self.write_indented(
+            cruise.common(),
+            port_call.arrival_date_time(),
+            &format!("  {} arrives {}", cruise.ship(), port_call.port()),
+        )
+    }
+
+    fn cruise_port_departure(&self, cruise: &Cruise, port_call: &PortCall) -> Result<()> {
+        self.write_indented(
+            cruise.common(),
+            port_call.departure_date_time(),
+            &format!("  {} departs {}", cruise.ship(), port_call.port()),
+        )?;
+        self.write_days_at_sea(cruise, port_call.departure_date_time())
+    }
+
+    fn cruise_disembarks(&self, cruise: &Cruise) -> Result<()> {
+        self.write_indented(
+            cruise.common(),
+            cruise.disembarkation_date_time(),
+            &format!(
+                "Disembark {} {} at {}",
+                cruise.cruise_line(),
+                cruise.ship(),
+                cruise.disembarkation_port()
+            ),
+        )
+    }
+
+    fn transport_departs(&self, transport: &Transport) -> Result<()> {
+        self.write_indented(
+            transport.common(),
+            transport.departure_date_time(),
+            &format!(
+                "{} {} departs {}",
+                transport.company_or_agency(),
+                transport.kind().map(|v| v.to_string()).unwrap_or_default(),
+                transport.departure_address()
+            ),
+        )
+    }
+
+    fn transport_arrives(&self, transport: &Transport) -> Result<()> {
+        self.write_indented(
+            transport.common(),
+            transport.arrival_date_time(),
+            &format!(
+                "{} {} arrives {}",
+                transport.company_or_agency(),
+                transport.kind().map(|v| v.to_string()).unwrap_or_default(),
+                or_unknown(transport.arrival_address())
+            ),
+        )
+    }
+
+    fn vehicle_pickup(&self, vehicle: &Vehicle) -> Result<()> {
+        self.write_indented(
+            vehicle.common(),
+            vehicle.pickup_date_time(),
+            &format!(
+                "{} vehicle pickup at {}",
+                vehicle.rental_agency(),
+                vehicle.pickup_address()
+            ),
+        )
+    }
+
+    fn vehicle_dropoff(&self, vehicle: &Vehicle) -> Result<()> {
+        self.write_indented(
+            vehicle.common(),
+            vehicle.dropoff_date_time(),
+            &format!(
+                "{} vehicle dropoff at {}",
+                vehicle.rental_agency(),
+                vehicle.dropoff_address()
+            ),
+        )
+    }
+
+    fn accomodation_checkin(&self, accomodation: &Accomodation) -> Result<()> {
+        self.write_indented(
+            accomodation.common(),
+            accomodation.checkin_date_time(),
+            &format!(
+                "Check in to {}, {}",
+                accomodation.company(),
+                accomodation.address()
+            ),
+        )
+    }
+
+    fn accomodation_checkout(&self, accomodation: &Accomodation) -> Result<()> {
+        self.write_indented(
+            accomodation.common(),
+            accomodation.checkout_date_time(),
+            &format!("Check out of {}", accomodation.company()),
+        )
+    }
+
+    fn event_start(&self, event: &Event) -> Result<()> {
+        self.write_indented(
+            event.common(),
+            event.start_date_time(),
+            &format!("{} starts at {}", event.company(), event.address()),
+        )
+    }
+
+    fn event_end(&self, event: &Event) -> Result<()> {
+        self.write_indented(
+            event.common(),
+            event.end_date_time(),
+            &format!("{} ends", event.company()),
+        )
+    }
+
+    fn end(&self) -> Result<()> {
+        write_cost_totals(&self.0, &self.1)
+    }
+}
+
+impl Indented {
+    ///
+    /// Following a departure, note the run of days the ship is then at sea, if any.
+    ///
+    fn write_days_at_sea(&self, cruise: &Cruise, departs: &DateTime) -> Result<()> {
+        if cruise.port_calls().any(|port_call| {
+            port_call.arrival_date_time() > departs
+                && port_call.arrival_date_time().date() == departs.date()
+        }) {
+            return Ok(());
+        }
+        let days_at_sea = cruise.days_at_sea();
+        let mut date = departs.date();
+        let mut count = 0;
+        while let Some(next) = date.succ_opt().filter(|next| days_at_sea.contains(next)) {
+            count += 1;
+            date = next;
+        }
+        if count > 0 {
+            self.write_gap(&format!("  At sea, {}", plural(count, "day")))?;
+        }
+        Ok(())
+    }
+
+    ///
+    /// Write a line for an item; within the day's heading only the time is shown, although an
+    /// edge reported within a later day, such as an arrival after crossing the date line, needs
+    /// its date as well.
+    ///
+    fn write_indented(&self, common: &ItemCommon, date_time: &DateTime, text: &str) -> Result<()> {
+        let format = if self.3.get() == Some(date_time.date()) {
+            DISPLAY_TIME_FORMAT
+        } else {
+            DISPLAY_DATE_TIME_FORMAT
+        };
+        self.0.write_line(&format!(
+            "    {:5}  {}{}",
+            date_time.format(format).to_string(),
+            text,
+            status_marker(common)
+        ))?;
+        Ok(())
+    }
+
+    fn write_gap(&self, text: &str) -> Result<()> {
+        self.0.write_line(&format!("    {:5}  {}", "", text))?;
+        Ok(())
+    }
+}
 
 // ------------------------------------------------------------------------------------------------
 // Private Functions
 // ------------------------------------------------------------------------------------------------
 
+fn segment_description(segment: &FlightSegment) -> String {
+    match segment.operated_by() {
+        Some(operating) => format!(
+            "{} {} -> {} (operated by {})",
+            segment.flight_number_string(),
+            segment.departure_airport(),
+            or_unknown(segment.arrival_airport()),
+            operating.flight_number_string()
+        ),
+        None => format!(
+            "{} {} -> {}",
+            segment.flight_number_string(),
+            segment.departure_airport(),
+            or_unknown(segment.arrival_airport())
+        ),
+    }
+}
+
+///
+/// Lines describing the cabin, terminals and gates, and each passenger's booking, if known.
+///
+fn flight_details(flight: &FlightSegment) -> Vec<String> {
+    let mut details = Vec::new();
+    match (flight.cabin(), flight.booking_class()) {
+        (Some(cabin), Some(booking_class)) => {
+            details.push(format!("{}, booking class {}", cabin, booking_class))
+        }
+        (Some(cabin), None) => details.push(cabin.to_string()),
+        (None, Some(booking_class)) => details.push(format!("booking class {}", booking_class)),
+        (None, None) => {}
+    }
+    if let Some(gate) = flight.departure_gate() {
+        details.push(format!("departs {}", gate));
+    }
+    if let Some(gate) = flight.arrival_gate() {
+        details.push(format!("arrives {}", gate));
+    }
+    for passenger in flight.passengers() {
+        let mut parts = Vec::new();
+        if let Some(seat) = passenger.seat() {
+            parts.push(format!("seat {}", seat));
+        }
+        if let Some(ticket_number) = passenger.ticket_number() {
+            parts.push(format!("ticket {}", ticket_number));
+        }
+        match passenger.checked_bags() {
+            Some(1) => parts.push("1 checked bag".to_string()),
+            Some(bags) => parts.push(format!("{} checked bags", bags)),
+            None => {}
+        }
+        if !parts.is_empty() {
+            details.push(format!("{}: {}", passenger.traveler(), parts.join(", ")));
+        }
+    }
+    details
+}
+
+fn gate_description(gate: Option<&AirportGate>) -> String {
+    match gate {
+        Some(gate) if !gate.is_empty() => format!(" ({})", gate),
+        _ => String::new(),
+    }
+}
+
+fn write_annotations(term: &Term, indent: &str, annotations: &Annotations) -> Result<()> {
+    if let Some(notes) = annotations.notes() {
+        for line in notes.lines() {
+            term.write_line(&format!("{}{}", indent, line))?;
+        }
+    }
+    let tags: Vec<String> = annotations.tags().map(|tag| format!("#{}", tag)).collect();
+    if !tags.is_empty() {
+        term.write_line(&format!("{}{}", indent, tags.join(" ")))?;
+    }
+    for attachment in annotations.attachments() {
+        term.write_line(&format!(
+            "{}attachment: {}",
+            indent,
+            attachment.path().display()
+        ))?;
+    }
+    Ok(())
+}
+
+///
+/// The header lines describing the trip as a whole; its purpose, owner and so on, then its dates
+/// and where it goes.
+///
+fn write_summary(term: &Term, indent: &str, metadata: &Metadata, summary: &Summary) -> Result<()> {
+    let mut about: Vec<String> = Vec::new();
+    if let Some(purpose) = metadata.purpose() {
+        about.push(format!("{} trip", purpose));
+    }
+    if let Some(owner) = metadata.owner() {
+        about.push(format!("owner {}", owner));
+    }
+    if let Some(cost_center) = metadata.cost_center() {
+        about.push(format!("cost center {}", cost_center));
+    }
+    if !about.is_empty() {
+        term.write_line(&format!("{}{}", indent, about.join(", ")))?;
+    }
+    let destinations: Vec<String> = metadata.destinations().map(|v| v.to_string()).collect();
+    if !destinations.is_empty() {
+        term.write_line(&format!("{}To {}", indent, destinations.join(", ")))?;
+    }
+    if let (Some(start), Some(end)) = (summary.start(), summary.end()) {
+        let mut dates = format!(
+            "{}{} to {}, {}",
+            indent,
+            start.format(DISPLAY_DATE_FORMAT),
+            end.format(DISPLAY_DATE_FORMAT),
+            plural(summary.nights().unwrap_or_default() as usize, "night")
+        );
+        let countries: Vec<String> = summary.countries().map(|v| v.to_string()).collect();
+        if !countries.is_empty() {
+            dates.push_str(&format!(", visiting {}", countries.join(", ")));
+        }
+        term.write_line(&dates)?;
+    }
+    Ok(())
+}
+
+fn status_marker(common: &ItemCommon) -> &'static str {
+    match common.status() {
+        ItemStatus::Tentative => " (TENTATIVE)",
+        ItemStatus::Cancelled => " (CANCELLED)",
+        _ => "",
+    }
+}
+
+fn cost_description(cost: &Cost) -> String {
+    let mut description = cost.total().to_string();
+    if let Some(taxes) = cost.taxes() {
+        description.push_str(&format!(" (incl. {} taxes)", taxes));
+    }
+    if let Some(deposit_paid) = cost.deposit_paid() {
+        description.push_str(&format!(", paid {}", deposit_paid));
+    }
+    if let Ok(balance) = cost.balance() {
+        if !balance.is_zero() && cost.deposit_paid().is_some() {
+            description.push_str(&format!(", balance {}", balance));
+        }
+    }
+    if let Some(balance_due) = cost.balance_due() {
+        description.push_str(&format!(" due {}", balance_due.format(DISPLAY_DATE_FORMAT)));
+    }
+    if let Some(payment_method) = cost.payment_method() {
+        description.push_str(&format!(", {}", payment_method));
+    }
+    description
+}
+
+fn write_cost_totals(term: &Term, rollup: &CostRollup) -> Result<()> {
+    if rollup.is_empty() {
+        return Ok(());
+    }
+    term.write_line("")?;
+    term.write_line("Total cost")?;
+    for totals in rollup.currencies() {
+        term.write_line(&match totals.balance_due() {
+            Some(balance_due) => format!(
+                "  {}, paid {}, balance {} due {}",
+                totals.total(),
+                totals.deposit_paid(),
+                totals.balance(),
+                balance_due.format(DISPLAY_DATE_FORMAT)
+            ),
+            None => format!(
+                "  {}, paid {}, balance {}",
+                totals.total(),
+                totals.deposit_paid(),
+                totals.balance()
+            ),
+        })?;
+    }
+    for traveler in rollup.travelers() {
+        let totals: Vec<String> = rollup
+            .traveler(traveler)
+            .map(|totals| totals.total().to_string())
+            .collect();
+        if !totals.is_empty() {
+            term.write_line(&format!("  {}: {}", traveler, totals.join(", ")))?;
+        }
+    }
+    Ok(())
+}
+
+fn leg_description(leg: &RailLeg) -> String {
+    let details: Vec<String> = leg
+        .coach()
+        .map(|v| format!("coach {}", v))
+        .into_iter()
+        .chain(leg.seat().map(|v| format!("seat {}", v)))
+        .chain(leg.class().map(|v| format!("class {}", v)))
+        .collect();
+    if details.is_empty() {
+        format!(
+            "{} {} -> {}",
+            leg.train_string(),
+            leg.departure_station(),
+            or_unknown(leg.arrival_station())
+        )
+    } else {
+        format!(
+            "{} {} -> {} ({})",
+            leg.train_string(),
+            leg.departure_station(),
+            or_unknown(leg.arrival_station()),
+            details.join(", ")
+        )
+    }
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    if count == 1 {
+        format!("{} {}", count, noun)
+    } else {
+        format!("{} {}s", count, noun)
+    }
+}
+
+///
+/// Show a missing value, which can only occur in an invalid itinerary, as "?".
+///
+fn or_unknown<T: Display>(value: Option<T>) -> String {
+    value
+        .map(|v| v.to_string())
+        .unwrap_or_else(|| "?".to_string())
+}
+
+fn format_duration(duration: &Duration) -> String {
+    let minutes = duration.num_minutes();
+    if minutes >= 60 {
+        format!("{}h {:02}m", minutes / 60, minutes % 60)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
 fn to_hashmap<P, L>(
     item_type: &str,
+    common: &ItemCommon,
     record: &TravelRecord<P, L>,
     flight_number: &str,
     transport_kind: &str,
@@ -171,6 +1263,41 @@ where
 {
     let row: BTreeMap<String, String> = [
         ("__type".to_string(), item_type.to_string()),
+        (
+            "id".to_string(),
+            common.id().map(|v| v.to_string()).unwrap_or_default(),
+        ),
+        (
+            "travelers".to_string(),
+            common
+                .travelers()
+                .cloned()
+                .collect::<Vec<String>>()
+                .join(","),
+        ),
+        ("status".to_string(), common.status().to_string()),
+        (
+            "tags".to_string(),
+            common
+                .annotations()
+                .tags()
+                .cloned()
+                .collect::<Vec<String>>()
+                .join(","),
+        ),
+        (
+            "notes".to_string(),
+            common.annotations().notes().cloned().unwrap_or_default(),
+        ),
+        (
+            "attachments".to_string(),
+            common
+                .annotations()
+                .attachments()
+                .map(|v| v.path().display().to_string())
+                .collect::<Vec<String>>()
+                .join(","),
+        ),
         ("provider".to_string(), record.provider.to_string()),
         (
             "confirmation".to_string(),
@@ -184,6 +1311,13 @@ where
             "start_date_time".to_string(),
             record.start_date_time.to_string(),
         ),
+        (
+            "start_time_zone".to_string(),
+            record
+                .start_time_zone()
+                .map(|v| v.to_string())
+                .unwrap_or_default(),
+        ),
         (
             "start_location".to_string(),
             record.start_location.to_string(),
@@ -192,6 +1326,13 @@ where
             "end_date_time".to_string(),
             record.end_date_time.to_string(),
         ),
+        (
+            "end_time_zone".to_string(),
+            record
+                .end_time_zone()
+                .map(|v| v.to_string())
+                .unwrap_or_default(),
+        ),
         (
             "end_location".to_string(),
             record
@@ -201,7 +1342,26 @@ where
                 .unwrap_or_default(),
         ),
         ("flight_number".to_string(), flight_number.to_string()),
+        ("cabin".to_string(), String::new()),
+        ("seats".to_string(), String::new()),
+        ("train_number".to_string(), String::new()),
         ("transport_kind".to_string(), transport_kind.to_string()),
+        ("connections".to_string(), String::new()),
+        (
+            "cost".to_string(),
+            common
+                .cost()
+                .map(|v| v.total().to_string())
+                .unwrap_or_default(),
+        ),
+        (
+            "balance".to_string(),
+            common
+                .cost()
+                .and_then(|v| v.balance().ok())
+                .map(|v| v.to_string())
+                .unwrap_or_default(),
+        ),
     ]
     .into();
     row
@@ -1,35 +1,726 @@
 /*!
-One-line description.
-More detailed description, with
+Changes to an itinerary, as commands that can be undone and logged.
+
+Each change is an `EditCommand` value; applying it to an itinerary either fails, leaving the
+itinerary unchanged, or succeeds and returns the command that reverses it. For example applying
+`RemoveItem` returns an `AddItem` holding the removed item and its original position. Commands are
+serializable, and display as a short description, so that a sequence of changes can be recorded.
+
+Commands keep the itinerary consistent: items are given an identifier when added, an item that
+others link to cannot be removed, and a traveler that items refer to cannot be removed.
+
 # Example
+
+```rust
+use terminal_travel::itinerary::edit::EditCommand;
+use terminal_travel::itinerary::io::from_str;
+
+let mut itinerary = from_str("identifier: 2022-06\nname: Playa\ntravelers: []\nitems: []\n").unwrap();
+let undo = EditCommand::Rename { name: "Playa del Carmen".to_string() }
+    .apply(&mut itinerary)
+    .unwrap();
+assert_eq!(itinerary.name(), "Playa del Carmen");
+
+let _ = undo.apply(&mut itinerary).unwrap();
+assert_eq!(itinerary.name(), "Playa");
+```
  */
 
-// use ...
+use_required!();
+use crate::error::{ErrorKind, Result};
+use crate::itinerary::traveler::Traveler;
+use crate::itinerary::{Booking, Item, ItemId, Itinerary};
+use serde::{Deserialize, Serialize};
 
 // ------------------------------------------------------------------------------------------------
 // Public Types
 // ------------------------------------------------------------------------------------------------
 
-// ------------------------------------------------------------------------------------------------
-// Public Functions
-// ------------------------------------------------------------------------------------------------
+///
+/// Where an item is; either within the booking with the given confirmation, or among the items
+/// that are not part of any booking. An index past the end of the list means the end.
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ItemPosition {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    booking: Option<String>,
+    index: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EditCommand {
+    /// Change the name of the itinerary.
+    Rename { name: String },
+    /// Add a traveler, at `index` or at the end.
+    AddTraveler {
+        traveler: Traveler,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        index: Option<usize>,
+    },
+    /// Remove a traveler no item refers to.
+    RemoveTraveler { name: String },
+    /// Replace the details of a traveler; if the name changes, items referring to the traveler
+    /// are updated.
+    ReplaceTraveler { name: String, traveler: Traveler },
+    /// Add an item at `position`, or at the end of the unbooked items. The item is given an
+    /// identifier if it does not have one.
+    AddItem {
+        item: Item,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        position: Option<ItemPosition>,
+    },
+    /// Remove an item no other item links to.
+    RemoveItem { id: ItemId },
+    /// Replace an item; the replacement keeps the identifier of the item it replaces.
+    ReplaceItem { id: ItemId, item: Item },
+    /// Move an item, within or between bookings.
+    MoveItem { id: ItemId, position: ItemPosition },
+    /// Add a booking, along with its items, at `index` or at the end.
+    AddBooking {
+        booking: Booking,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        index: Option<usize>,
+    },
+    /// Remove a booking, along with its items.
+    RemoveBooking { confirmation: String },
+    /// A group of commands applied, and reversed, as one.
+    Batch { commands: Vec<EditCommand> },
+}
 
 // ------------------------------------------------------------------------------------------------
 // Implementations
 // ------------------------------------------------------------------------------------------------
 
+impl ItemPosition {
+    ///
+    /// A position among the items that are not part of any booking.
+    ///
+    pub fn unbooked(index: usize) -> Self {
+        Self {
+            booking: None,
+            index,
+        }
+    }
+
+    ///
+    /// A position within the booking with confirmation `confirmation`.
+    ///
+    pub fn in_booking(confirmation: &str, index: usize) -> Self {
+        Self {
+            booking: Some(confirmation.to_string()),
+            index,
+        }
+    }
+
+    pub fn booking(&self) -> Option<&String> {
+        self.booking.as_ref()
+    }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for EditCommand {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EditCommand::Rename { name } => write!(f, "rename itinerary to '{}'", name),
+            EditCommand::AddTraveler { traveler, .. } => {
+                write!(f, "add traveler '{}'", traveler.name())
+            }
+            EditCommand::RemoveTraveler { name } => write!(f, "remove traveler '{}'", name),
+            EditCommand::ReplaceTraveler { name, .. } => write!(f, "replace traveler '{}'", name),
+            EditCommand::AddItem { item, .. } => match item.id() {
+                Some(id) => write!(f, "add item {}", id),
+                None => write!(f, "add item {}", item.default_id_base()),
+            },
+            EditCommand::RemoveItem { id } => write!(f, "remove item {}", id),
+            EditCommand::ReplaceItem { id, .. } => write!(f, "replace item {}", id),
+            EditCommand::MoveItem { id, position } => match &position.booking {
+                Some(booking) => write!(
+                    f,
+                    "move item {} to position {} of booking {}",
+                    id, position.index, booking
+                ),
+                None => write!(f, "move item {} to position {}", id, position.index),
+            },
+            EditCommand::AddBooking { booking, .. } => {
+                write!(f, "add booking {}", booking.confirmation())
+            }
+            EditCommand::RemoveBooking { confirmation } => {
+                write!(f, "remove booking {}", confirmation)
+            }
+            EditCommand::Batch { commands } => write!(
+                f,
+                "{}",
+                commands
+                    .iter()
+                    .map(|command| command.to_string())
+                    .collect::<Vec<String>>()
+                    .join("; ")
+            ),
+        }
+    }
+}
+
+impl EditCommand {
+    ///
+    /// Apply this command to `itinerary`, returning the command that reverses it. If this
+    /// returns an error the itinerary is unchanged.
+    ///
+    pub fn apply(&self, itinerary: &mut Itinerary) -> Result<EditCommand> {
+        match self {
+            EditCommand::Rename { name } => {
+                if name.trim().is_empty() {
+                    return Err(ErrorKind::MissingValue(
+                        "itinerary".to_string(),
+                        "name".to_string(),
+                    )
+                    .into());
+                }
+                let previous = std::mem::replace(&mut itinerary.name, name.clone());
+                Ok(EditCommand::Rename { name: previous })
+            }
+            EditCommand::AddTraveler { traveler, index } => {
+                if itinerary.traveler(traveler.name()).is_some() {
+                    return Err(ErrorKind::DuplicateTraveler(traveler.name().clone()).into());
+                }
+                let index = clamp(*index, itinerary.travelers.len());
+                itinerary.travelers.insert(index, traveler.clone());
+                Ok(EditCommand::RemoveTraveler {
+                    name: traveler.name().clone(),
+                })
+            }
+            EditCommand::RemoveTraveler { name } => {
+                let index = traveler_index(itinerary, name)?;
+                if itinerary
+                    .items()
+                    .any(|item| item_traveler_names(item).any(|v| v == name))
+                {
+                    return Err(ErrorKind::TravelerInUse(name.clone()).into());
+                }
+                let traveler = itinerary.travelers.remove(index);
+                Ok(EditCommand::AddTraveler {
+                    traveler,
+                    index: Some(index),
+                })
+            }
+            EditCommand::ReplaceTraveler { name, traveler } => {
+                let index = traveler_index(itinerary, name)?;
+                if traveler.name() != name && itinerary.traveler(traveler.name()).is_some() {
+                    return Err(ErrorKind::DuplicateTraveler(traveler.name().clone()).into());
+                }
+                let previous = std::mem::replace(&mut itinerary.travelers[index], traveler.clone());
+                if traveler.name() != name {
+                    rename_traveler(itinerary, name, traveler.name());
+                }
+                Ok(EditCommand::ReplaceTraveler {
+                    name: traveler.name().clone(),
+                    traveler: previous,
+                })
+            }
+            EditCommand::AddItem { item, position } => {
+                check_new_item(itinerary, item)?;
+                let position = position
+                    .clone()
+                    .unwrap_or_else(|| ItemPosition::unbooked(itinerary.items.len()));
+                let list = item_list_mut(itinerary, position.booking.as_deref())?;
+                let index = clamp(Some(position.index), list.len());
+                list.insert(index, item.clone());
+                itinerary.assign_item_ids();
+                itinerary.infer_time_zones();
+                let id = item_list_mut(itinerary, position.booking.as_deref())?[index]
+                    .id()
+                    .cloned()
+                    .unwrap();
+                Ok(EditCommand::RemoveItem { id })
+            }
+            EditCommand::RemoveItem { id } => {
+                check_not_linked(itinerary, std::iter::once(id))?;
+                let position = item_position(itinerary, id)?;
+                let item =
+                    item_list_mut(itinerary, position.booking.as_deref())?.remove(position.index);
+                Ok(EditCommand::AddItem {
+                    item,
+                    position: Some(position),
+                })
+            }
+            EditCommand::ReplaceItem { id, item } => {
+                let position = item_position(itinerary, id)?;
+                let mut item = item.clone();
+                item.common_mut().id = Some(id.clone());
+                check_travelers(itinerary, &item)?;
+                check_links(itinerary, &item)?;
+                let list = item_list_mut(itinerary, position.booking.as_deref())?;
+                let previous = std::mem::replace(&mut list[position.index], item);
+                itinerary.infer_time_zones();
+                Ok(EditCommand::ReplaceItem {
+                    id: id.clone(),
+                    item: previous,
+                })
+            }
+            EditCommand::MoveItem { id, position } => {
+                let current = item_position(itinerary, id)?;
+                let _ = item_list_mut(itinerary, position.booking.as_deref())?;
+                let item =
+                    item_list_mut(itinerary, current.booking.as_deref())?.remove(current.index);
+                let list = item_list_mut(itinerary, position.booking.as_deref())?;
+                let index = clamp(Some(position.index), list.len());
+                list.insert(index, item);
+                Ok(EditCommand::MoveItem {
+                    id: id.clone(),
+                    position: current,
+                })
+            }
+            EditCommand::AddBooking { booking, index } => {
+                if itinerary
+                    .bookings()
+                    .any(|v| v.confirmation() == booking.confirmation())
+                {
+                    return Err(ErrorKind::DuplicateBooking(booking.confirmation().clone()).into());
+                }
+                for item in booking.items() {
+                    check_new_item(itinerary, item)?;
+                }
+                let index = clamp(*index, itinerary.bookings.len());
+                itinerary.bookings.insert(index, booking.clone());
+                itinerary.assign_item_ids();
+                itinerary.infer_time_zones();
+                Ok(EditCommand::RemoveBooking {
+                    confirmation: booking.confirmation().clone(),
+                })
+            }
+            EditCommand::RemoveBooking { confirmation } => {
+                let index = booking_index(itinerary, confirmation)?;
+                check_not_linked(
+                    itinerary,
+                    itinerary.bookings[index]
+                        .items()
+                        .filter_map(|item| item.id()),
+                )?;
+                let booking = itinerary.bookings.remove(index);
+                Ok(EditCommand::AddBooking {
+                    booking,
+                    index: Some(index),
+                })
+            }
+            EditCommand::Batch { commands } => {
+                // restoring a copy, rather than applying inverses, can not itself fail part way.
+                let original = itinerary.clone();
+                let mut inverses: Vec<EditCommand> = Vec::with_capacity(commands.len());
+                for command in commands {
+                    match command.apply(itinerary) {
+                        Ok(inverse) => inverses.push(inverse),
+                        Err(e) => {
+                            *itinerary = original;
+                            return Err(e);
+                        }
+                    }
+                }
+                inverses.reverse();
+                Ok(EditCommand::Batch { commands: inverses })
+            }
+        }
+    }
+}
+
 // ------------------------------------------------------------------------------------------------
-// Modules
+// Private Functions
 // ------------------------------------------------------------------------------------------------
 
+fn clamp(index: Option<usize>, len: usize) -> usize {
+    index.map(|index| index.min(len)).unwrap_or(len)
+}
+
+fn traveler_index(itinerary: &Itinerary, name: &str) -> Result<usize> {
+    itinerary
+        .travelers
+        .iter()
+        .position(|v| v.name() == name)
+        .ok_or_else(|| ErrorKind::UnknownTraveler(name.to_string()).into())
+}
+
+fn booking_index(itinerary: &Itinerary, confirmation: &str) -> Result<usize> {
+    itinerary
+        .bookings
+        .iter()
+        .position(|v| v.confirmation() == confirmation)
+        .ok_or_else(|| ErrorKind::UnknownBooking(confirmation.to_string()).into())
+}
+
+fn item_list_mut<'a>(
+    itinerary: &'a mut Itinerary,
+    booking: Option<&str>,
+) -> Result<&'a mut Vec<Item>> {
+    match booking {
+        Some(confirmation) => {
+            let index = booking_index(itinerary, confirmation)?;
+            Ok(&mut itinerary.bookings[index].items)
+        }
+        None => Ok(&mut itinerary.items),
+    }
+}
+
+fn item_position(itinerary: &Itinerary, id: &ItemId) -> Result<ItemPosition> {
+    let find = |items: &Vec<Item>| items.iter().position(|item| item.id() == Some(id));
+    for booking in &itinerary.bookings {
+        if let Some(index) = find(&booking.items) {
+            return Ok(ItemPosition::in_booking(&booking.confirmation, index));
+        }
+    }
+    find(&itinerary.items)
+        .map(ItemPosition::unbooked)
+        .ok_or_else(|| ErrorKind::UnknownItem(id.to_string()).into())
+}
+
+///
+/// The names of all travelers an item refers to, including flight passengers.
+///
+pub(crate) fn item_traveler_names(item: &Item) -> impl Iterator<Item = &String> {
+    let passengers: Vec<&String> = match item {
+        Item::Flight(v) => v.passengers().map(|v| v.traveler()).collect(),
+        Item::Journey(v) => v
+            .segments()
+            .flat_map(|segment| segment.passengers().map(|v| v.traveler()))
+            .collect(),
+        _ => Vec::new(),
+    };
+    item.common().travelers().chain(passengers)
+}
+
+fn rename_traveler(itinerary: &mut Itinerary, from: &str, to: &str) {
+    for item in itinerary.items_mut() {
+        item.common_mut()
+            .travelers
+            .iter_mut()
+            .filter(|name| *name == from)
+            .for_each(|name| *name = to.to_string());
+    }
+    for flight in itinerary.flights_mut() {
+        flight
+            .passengers
+            .iter_mut()
+            .filter(|passenger| passenger.traveler() == from)
+            .for_each(|passenger| passenger.set_traveler(to));
+    }
+}
+
+fn check_new_item(itinerary: &Itinerary, item: &Item) -> Result<()> {
+    if let Some(id) = item.id() {
+        if itinerary.contains_item(id) {
+            return Err(ErrorKind::DuplicateItem(id.to_string()).into());
+        }
+    }
+    check_travelers(itinerary, item)?;
+    check_links(itinerary, item)
+}
+
+fn check_travelers(itinerary: &Itinerary, item: &Item) -> Result<()> {
+    match item_traveler_names(item).find(|name| itinerary.traveler(name).is_none()) {
+        Some(name) => Err(ErrorKind::UnknownTraveler(name.to_string()).into()),
+        None => Ok(()),
+    }
+}
+
+fn check_links(itinerary: &Itinerary, item: &Item) -> Result<()> {
+    match item
+        .links()
+        .find(|link| !itinerary.contains_item(link.target()))
+    {
+        Some(link) => Err(ErrorKind::UnknownItem(link.target().to_string()).into()),
+        None => Ok(()),
+    }
+}
+
+///
+/// Fail if any item, other than those in `ids`, links to any item in `ids`.
+///
+fn check_not_linked<'a>(
+    itinerary: &Itinerary,
+    ids: impl Iterator<Item = &'a ItemId>,
+) -> Result<()> {
+    let ids: Vec<&ItemId> = ids.collect();
+    for item in itinerary.items() {
+        if item.id().map(|id| ids.contains(&id)).unwrap_or_default() {
+            continue;
+        }
+        if let Some(link) = item.links().find(|link| ids.contains(&link.target())) {
+            return Err(ErrorKind::ItemInUse(
+                link.target().to_string(),
+                item.id().map(|v| v.to_string()).unwrap_or_default(),
+            )
+            .into());
+        }
+    }
+    Ok(())
+}
+
 // ------------------------------------------------------------------------------------------------
 // Unit Tests
 // ------------------------------------------------------------------------------------------------
 
 #[cfg(test)]
 mod tests {
-    use super::*;
+    use super::{EditCommand, ItemPosition};
+    use crate::itinerary::builder::{EventBuilder, FlightBuilder};
+    use crate::itinerary::flight_details::Passenger;
+    use crate::itinerary::io::{from_str, to_string};
+    use crate::itinerary::location::Location;
+    use crate::itinerary::traveler::Traveler;
+    use crate::itinerary::{AirCarrierCode, AirportCode, Date, ItemId, Itinerary};
+    use pretty_assertions::assert_eq;
+    use std::str::FromStr;
+
+    fn example() -> Itinerary {
+        from_str(
+            r##"---
+identifier: 2022-06
+name: Playa del Carmen
+travelers:
+  - Simon
+  - Jane
+bookings:
+  - agency: Expedia
+    confirmation: "7261998331"
+    items:
+      - flight:
+          number: 594
+          passengers:
+            - traveler: Jane
+              seat: 12B
+          record:
+            provider: DL
+            confirmation: GROVQY
+            start_date_time: "2022-06-24T07:15:00"
+            start_location: SEA
+            end_date_time: "2022-06-24T15:03:00"
+            end_location: CUN
+items:
+  - transport:
+      links:
+        - meets: flight-dl594-20220624
+      record:
+        provider: Happy Shuttle
+        start_date_time: "2022-06-24T15:30:00"
+        start_location: Cancun airport
+        end_date_time: "2022-06-24T16:30:00"
+        end_location: Playa del Carmen
+  - accomodation:
+      travelers:
+        - Simon
+      record:
+        provider: Hotel Xcaret
+        start_date_time: "2022-06-24T16:00:00"
+        start_location: Playa del Carmen
+        end_date_time: "2022-07-01T11:00:00"
+"##,
+        )
+        .unwrap()
+    }
+
+    fn id(s: &str) -> ItemId {
+        ItemId::from_str(s).unwrap()
+    }
+
+    fn item_ids(itinerary: &Itinerary) -> Vec<String> {
+        itinerary
+            .items()
+            .map(|item| item.id().unwrap().to_string())
+            .collect()
+    }
+
+    ///
+    /// Apply `command`, then its inverse, checking the itinerary is restored; returns the
+    /// itinerary as it was after applying the command.
+    ///
+    fn round_trip(command: EditCommand) -> Itinerary {
+        let original = example();
+        let mut itinerary = original.clone();
+        let inverse = command.apply(&mut itinerary).unwrap();
+        let changed = itinerary.clone();
+        let again = inverse.apply(&mut itinerary).unwrap();
+        assert_eq!(itinerary, original);
+        let _ = again.apply(&mut itinerary).unwrap();
+        assert_eq!(itinerary, changed);
+        changed
+    }
 
     #[test]
-    fn test_something() {}
+    fn test_item_commands() {
+        let dinner = EventBuilder::new("La Cueva del Chango")
+            .address(Location::new("Playa del Carmen"))
+            .starts(
+                Date::from_ymd_opt(2022, 6, 25)
+                    .unwrap()
+                    .and_hms_opt(19, 0, 0)
+                    .unwrap(),
+            )
+            .ends(
+                Date::from_ymd_opt(2022, 6, 25)
+                    .unwrap()
+                    .and_hms_opt(21, 0, 0)
+                    .unwrap(),
+            )
+            .build()
+            .unwrap();
+        let added = round_trip(EditCommand::AddItem {
+            item: dinner.clone().into(),
+            position: Some(ItemPosition::unbooked(1)),
+        });
+        assert_eq!(
+            item_ids(&added),
+            vec![
+                "flight-dl594-20220624",
+                "transport-happy-shuttle-20220624",
+                "event-la-cueva-del-chango-20220625",
+                "accomodation-hotel-xcaret-20220624",
+            ]
+        );
+
+        let removed = round_trip(EditCommand::RemoveItem {
+            id: id("accomodation-hotel-xcaret-20220624"),
+        });
+        assert_eq!(removed.items().count(), 2);
+
+        let replaced = round_trip(EditCommand::ReplaceItem {
+            id: id("accomodation-hotel-xcaret-20220624"),
+            item: dinner.into(),
+        });
+        assert_eq!(
+            item_ids(&replaced)[2],
+            "accomodation-hotel-xcaret-20220624".to_string()
+        );
+        assert!(replaced.items().nth(2).unwrap().is_event());
+
+        let moved = round_trip(EditCommand::MoveItem {
+            id: id("accomodation-hotel-xcaret-20220624"),
+            position: ItemPosition::in_booking("7261998331", 0),
+        });
+        assert_eq!(moved.bookings().next().unwrap().items().count(), 2);
+        assert_eq!(
+            item_ids(&moved)[0],
+            "accomodation-hotel-xcaret-20220624".to_string()
+        );
+
+        let mut itinerary = example();
+        assert!(EditCommand::RemoveItem {
+            id: id("flight-dl594-20220624")
+        }
+        .apply(&mut itinerary)
+        .is_err());
+        assert!(EditCommand::RemoveBooking {
+            confirmation: "7261998331".to_string()
+        }
+        .apply(&mut itinerary)
+        .is_err());
+        assert!(EditCommand::MoveItem {
+            id: id("flight-dl594-20220624"),
+            position: ItemPosition::in_booking("XXXX", 0),
+        }
+        .apply(&mut itinerary)
+        .is_err());
+        assert_eq!(itinerary, example());
+    }
+
+    #[test]
+    fn test_traveler_commands() {
+        let renamed = round_trip(EditCommand::ReplaceTraveler {
+            name: "Jane".to_string(),
+            traveler: Traveler::new("Janet"),
+        });
+        let flight = renamed.items().next().unwrap().as_flight().unwrap();
+        assert_eq!(
+            flight.passenger("Janet").unwrap().seat(),
+            Some(&"12B".to_string())
+        );
+        assert!(flight.passenger("Jane").is_none());
+
+        let added = round_trip(EditCommand::AddTraveler {
+            traveler: Traveler::new("Alice"),
+            index: Some(0),
+        });
+        assert_eq!(added.travelers().next().unwrap().name(), "Alice");
+
+        let mut itinerary = example();
+        assert!(EditCommand::RemoveTraveler {
+            name: "Simon".to_string()
+        }
+        .apply(&mut itinerary)
+        .is_err());
+        assert!(EditCommand::AddTraveler {
+            traveler: Traveler::new("Jane"),
+            index: None,
+        }
+        .apply(&mut itinerary)
+        .is_err());
+    }
+
+    #[test]
+    fn test_batch() {
+        let flight = FlightBuilder::new(AirCarrierCode::from_str("DL").unwrap(), 604)
+            .departs(
+                AirportCode::from_str("CUN").unwrap(),
+                Date::from_ymd_opt(2022, 7, 5)
+                    .unwrap()
+                    .and_hms_opt(14, 3, 0)
+                    .unwrap(),
+            )
+            .arrives(
+                AirportCode::from_str("SEA").unwrap(),
+                Date::from_ymd_opt(2022, 7, 5)
+                    .unwrap()
+                    .and_hms_opt(20, 23, 0)
+                    .unwrap(),
+            )
+            .passenger(Passenger::new("Alice"))
+            .build()
+            .unwrap();
+        let batch = EditCommand::Batch {
+            commands: vec![
+                EditCommand::Rename {
+                    name: "Playa 2022".to_string(),
+                },
+                EditCommand::AddTraveler {
+                    traveler: Traveler::new("Alice"),
+                    index: None,
+                },
+                EditCommand::AddItem {
+                    item: flight.into(),
+                    position: Some(ItemPosition::in_booking("7261998331", 1)),
+                },
+            ],
+        };
+        assert_eq!(
+            batch.to_string(),
+            "rename itinerary to 'Playa 2022'; add traveler 'Alice'; add item flight-dl604-20220705"
+        );
+        let changed = round_trip(batch.clone());
+        assert_eq!(changed.name(), "Playa 2022");
+        assert_eq!(changed.bookings().next().unwrap().items().count(), 2);
+
+        // commands are serializable, so that they may be logged
+        let yaml = serde_yaml::to_string(&batch).unwrap();
+        assert_eq!(serde_yaml::from_str::<EditCommand>(&yaml).unwrap(), batch);
+
+        // a failing command undoes those before it
+        let mut itinerary = example();
+        let failing = EditCommand::Batch {
+            commands: vec![
+                EditCommand::Rename {
+                    name: "Playa 2022".to_string(),
+                },
+                EditCommand::RemoveTraveler {
+                    name: "Nobody".to_string(),
+                },
+            ],
+        };
+        assert!(failing.apply(&mut itinerary).is_err());
+        assert_eq!(
+            to_string(&itinerary).unwrap(),
+            to_string(&example()).unwrap()
+        );
+    }
 }
@@ -28,8 +28,6 @@ More detailed description, with
 
 #[cfg(test)]
 mod tests {
-    use super::*;
-
     #[test]
     fn test_something() {}
 }
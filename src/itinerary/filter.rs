@@ -0,0 +1,288 @@
+/*!
+Select the items of an itinerary that a traversal visits.
+
+A filter is built up from any number of criteria; an item must meet every criterion that has been
+set, and where a criterion has been given more than one value (two kinds, say) the item need only
+match one of them. An empty filter selects every item.
+
+# Example
+
+```rust
+use terminal_travel::itinerary::filter::ItemFilter;
+use terminal_travel::itinerary::{Date, ItemKind};
+
+// flights and rail, next week, for Alice.
+let filter = ItemFilter::default()
+    .with_kind(ItemKind::Flight)
+    .with_kind(ItemKind::Rail)
+    .from_date(Date::from_ymd_opt(2022, 6, 20).unwrap())
+    .to_date(Date::from_ymd_opt(2022, 6, 26).unwrap())
+    .with_traveler("Alice");
+assert!(!filter.is_empty());
+```
+ */
+
+use crate::itinerary::edit::item_traveler_names;
+use crate::itinerary::status::ItemStatus;
+use crate::itinerary::{Date, Item, ItemKind};
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Clone, Default)]
+pub struct ItemFilter {
+    kinds: Vec<ItemKind>,
+    from: Option<Date>,
+    to: Option<Date>,
+    travelers: Vec<String>,
+    statuses: Vec<ItemStatus>,
+    tags: Vec<String>,
+    predicates: Vec<ItemPredicate>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+type ItemPredicate = Arc<dyn Fn(&Item) -> bool + Send + Sync>;
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Debug for ItemFilter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ItemFilter")
+            .field("kinds", &self.kinds)
+            .field("from", &self.from)
+            .field("to", &self.to)
+            .field("travelers", &self.travelers)
+            .field("statuses", &self.statuses)
+            .field("tags", &self.tags)
+            .field("predicates", &self.predicates.len())
+            .finish()
+    }
+}
+
+impl ItemFilter {
+    pub fn with_kind(mut self, kind: ItemKind) -> Self {
+        self.kinds.push(kind);
+        self
+    }
+
+    ///
+    /// Only items that end on, or after, `date` in their local time.
+    ///
+    pub fn from_date(self, date: Date) -> Self {
+        Self {
+            from: Some(date),
+            ..self
+        }
+    }
+
+    ///
+    /// Only items that start on, or before, `date` in their local time.
+    ///
+    pub fn to_date(self, date: Date) -> Self {
+        Self {
+            to: Some(date),
+            ..self
+        }
+    }
+
+    ///
+    /// Only items that cover the named traveler, either in their travelers or, for flights, their
+    /// passengers; an item that names no travelers covers all of them.
+    ///
+    pub fn with_traveler(mut self, traveler: &str) -> Self {
+        self.travelers.push(traveler.to_string());
+        self
+    }
+
+    ///
+    /// Only items with the given status. Selecting `ItemStatus::Cancelled` shows cancelled items
+    /// whether or not the visitor includes them.
+    ///
+    pub fn with_status(mut self, status: ItemStatus) -> Self {
+        self.statuses.push(status);
+        self
+    }
+
+    pub fn with_tag(mut self, tag: &str) -> Self {
+        self.tags.push(tag.to_string());
+        self
+    }
+
+    ///
+    /// Only items for which `predicate` returns `true`; unlike other criteria, each predicate
+    /// added must be met.
+    ///
+    pub fn with_predicate<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&Item) -> bool + Send + Sync + 'static,
+    {
+        self.predicates.push(Arc::new(predicate));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.kinds.is_empty()
+            && self.from.is_none()
+            && self.to.is_none()
+            && self.travelers.is_empty()
+            && self.statuses.is_empty()
+            && self.tags.is_empty()
+            && self.predicates.is_empty()
+    }
+
+    ///
+    /// Returns `true` if this filter explicitly selects cancelled items.
+    ///
+    pub fn selects_cancelled(&self) -> bool {
+        self.statuses.contains(&ItemStatus::Cancelled)
+    }
+
+    pub fn matches(&self, item: &Item) -> bool {
+        let common = item.common();
+        (self.kinds.is_empty() || self.kinds.contains(&item.kind()))
+            && self
+                .from
+                .map(|from| item.end_date_time().date() >= from)
+                .unwrap_or(true)
+            && self
+                .to
+                .map(|to| item.start_date_time().date() <= to)
+                .unwrap_or(true)
+            && (self.travelers.is_empty()
+                || self
+                    .travelers
+                    .iter()
+                    .any(|traveler| is_for_traveler(item, traveler)))
+            && (self.statuses.is_empty() || self.statuses.contains(&common.status()))
+            && (self.tags.is_empty()
+                || self
+                    .tags
+                    .iter()
+                    .any(|tag| common.annotations().has_tag(tag)))
+            && self.predicates.iter().all(|predicate| predicate(item))
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn is_for_traveler(item: &Item, traveler: &str) -> bool {
+    let mut names = item_traveler_names(item).peekable();
+    names.peek().is_none() || names.any(|name| name == traveler)
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::ItemFilter;
+    use crate::itinerary::io::from_str;
+    use crate::itinerary::status::ItemStatus;
+    use crate::itinerary::{Date, Item, ItemKind};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_filter_items() {
+        let itinerary = from_str(
+            r##"---
+identifier: 2022-06
+name: Playa del Carmen
+travelers:
+  - Alice
+  - Bob
+items:
+  - flight:
+      number: 594
+      passengers:
+        - traveler: Alice
+      record:
+        provider: DL
+        start_date_time: "2022-06-24T07:15:00"
+        start_location: SEA
+        end_date_time: "2022-06-24T15:03:00"
+        end_location: CUN
+  - accomodation:
+      tags: [beach]
+      record:
+        provider: bric
+        start_date_time: "2022-06-24T16:00:00"
+        start_location: Playa del Carmen
+        end_date_time: "2022-07-05T11:00:00"
+  - event:
+      status: cancelled
+      travelers: [Bob]
+      record:
+        provider: Day trip
+        start_date_time: "2022-07-02T09:00:00"
+        start_location: Tulum
+        end_date_time: "2022-07-02T17:00:00"
+"##,
+        )
+        .unwrap();
+        let select = |filter: ItemFilter| -> Vec<String> {
+            itinerary
+                .items()
+                .filter(|item| filter.matches(item))
+                .map(|item: &Item| item.kind().to_string())
+                .collect()
+        };
+
+        assert!(ItemFilter::default().is_empty());
+        assert_eq!(select(ItemFilter::default()).len(), 3);
+        assert_eq!(
+            select(
+                ItemFilter::default()
+                    .with_kind(ItemKind::Flight)
+                    .with_kind(ItemKind::Event)
+            ),
+            vec!["flight", "event"]
+        );
+        assert_eq!(
+            select(ItemFilter::default().from_date(Date::from_ymd_opt(2022, 7, 1).unwrap())),
+            vec!["accomodation", "event"]
+        );
+        assert_eq!(
+            select(
+                ItemFilter::default()
+                    .from_date(Date::from_ymd_opt(2022, 6, 25).unwrap())
+                    .to_date(Date::from_ymd_opt(2022, 6, 30).unwrap())
+            ),
+            vec!["accomodation"]
+        );
+        assert_eq!(
+            select(ItemFilter::default().with_traveler("Alice")),
+            vec!["flight", "accomodation"]
+        );
+        assert_eq!(
+            select(ItemFilter::default().with_traveler("Bob")),
+            vec!["accomodation", "event"]
+        );
+        assert_eq!(
+            select(ItemFilter::default().with_status(ItemStatus::Cancelled)),
+            vec!["event"]
+        );
+        assert_eq!(
+            select(ItemFilter::default().with_tag("beach")),
+            vec!["accomodation"]
+        );
+        assert_eq!(
+            select(
+                ItemFilter::default()
+                    .with_traveler("Bob")
+                    .with_predicate(|item| item.is_event())
+            ),
+            vec!["event"]
+        );
+    }
+}
@@ -0,0 +1,292 @@
+/*!
+The booking and airport details of a flight.
+
+Beyond its number and schedule a flight may record the cabin and booking class (the fare bucket)
+it was sold in, and for each traveler their seat, e-ticket number, and checked-bag allowance. The
+terminal and gate at each end are usually only known close to departure; they may be entered by
+hand or filled in from schedule data.
+ */
+
+use_required!();
+use crate::error::{Error, ErrorKind};
+use serde::{Deserialize, Serialize};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Cabin {
+    Economy,
+    PremiumEconomy,
+    Business,
+    First,
+}
+
+is_valid_newstring!(BookingClass, is_valid_booking_class, Deserialize, Serialize);
+
+is_valid_newstring!(TicketNumber, is_valid_ticket_number, Deserialize, Serialize);
+
+///
+/// The booking details of a single traveler on a flight.
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Passenger {
+    traveler: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seat: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ticket_number: Option<TicketNumber>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    checked_bags: Option<u8>,
+}
+
+///
+/// Where a flight departs from, or arrives at, within an airport.
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AirportGate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    terminal: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    gate: Option<String>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Display for Cabin {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Cabin::Economy => "economy",
+                Cabin::PremiumEconomy => "premium economy",
+                Cabin::Business => "business",
+                Cabin::First => "first",
+            }
+        )
+    }
+}
+
+impl FromStr for Cabin {
+    type Err = Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "economy" => Ok(Self::Economy),
+            "premium_economy" | "premium economy" => Ok(Self::PremiumEconomy),
+            "business" => Ok(Self::Business),
+            "first" => Ok(Self::First),
+            _ => Err(ErrorKind::InvalidCabin(s.to_string()).into()),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Passenger {
+    pub fn new(traveler: &str) -> Self {
+        Self {
+            traveler: traveler.to_string(),
+            seat: None,
+            ticket_number: None,
+            checked_bags: None,
+        }
+    }
+
+    ///
+    /// The name of the traveler these details are for.
+    ///
+    pub fn traveler(&self) -> &String {
+        &self.traveler
+    }
+
+    pub(crate) fn set_traveler(&mut self, traveler: &str) {
+        self.traveler = traveler.to_string();
+    }
+
+    pub fn seat(&self) -> Option<&String> {
+        self.seat.as_ref()
+    }
+
+    pub fn set_seat(&mut self, seat: &str) {
+        self.seat = Some(seat.to_string());
+    }
+
+    pub fn ticket_number(&self) -> Option<&TicketNumber> {
+        self.ticket_number.as_ref()
+    }
+
+    pub fn set_ticket_number(&mut self, ticket_number: TicketNumber) {
+        self.ticket_number = Some(ticket_number);
+    }
+
+    ///
+    /// The number of checked bags included for this traveler, if known.
+    ///
+    pub fn checked_bags(&self) -> Option<u8> {
+        self.checked_bags
+    }
+
+    pub fn set_checked_bags(&mut self, checked_bags: u8) {
+        self.checked_bags = Some(checked_bags);
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for AirportGate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match (&self.terminal, &self.gate) {
+            (Some(terminal), Some(gate)) => write!(f, "terminal {}, gate {}", terminal, gate),
+            (Some(terminal), None) => write!(f, "terminal {}", terminal),
+            (None, Some(gate)) => write!(f, "gate {}", gate),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+impl AirportGate {
+    pub fn new(terminal: Option<&str>, gate: Option<&str>) -> Self {
+        Self {
+            terminal: terminal.map(|v| v.to_string()),
+            gate: gate.map(|v| v.to_string()),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terminal.is_none() && self.gate.is_none()
+    }
+
+    pub fn terminal(&self) -> Option<&String> {
+        self.terminal.as_ref()
+    }
+
+    pub fn gate(&self) -> Option<&String> {
+        self.gate.as_ref()
+    }
+
+    ///
+    /// Take any value present in `other` in place of the one here.
+    ///
+    pub fn update_from(&mut self, other: &AirportGate) {
+        if other.terminal.is_some() {
+            self.terminal = other.terminal.clone();
+        }
+        if other.gate.is_some() {
+            self.gate = other.gate.clone();
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+#[inline]
+fn is_valid_booking_class(s: &str) -> bool {
+    s.len() == 1 && s.chars().all(|c| c.is_ascii_uppercase())
+}
+
+///
+/// An e-ticket number is a 3-digit airline prefix followed by a 10-digit serial number.
+///
+#[inline]
+fn is_valid_ticket_number(s: &str) -> bool {
+    s.len() == 13 && s.chars().all(|c| c.is_ascii_digit())
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::{AirportGate, BookingClass, Cabin, TicketNumber};
+    use crate::itinerary::io::{from_str, to_string};
+    use pretty_assertions::assert_eq;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_flight_details() {
+        let itinerary = from_str(
+            r##"---
+identifier: 2022-06
+name: Playa del Carmen
+travelers:
+  - Simon
+  - Jane
+items:
+  - flight:
+      number: 1471
+      cabin: premium_economy
+      booking_class: W
+      passengers:
+        - traveler: Simon
+          seat: 12A
+          ticket_number: "0012345678901"
+          checked_bags: 1
+        - traveler: Jane
+          seat: 12B
+      departure_gate:
+        terminal: "1"
+        gate: C12
+      record:
+        provider: AA
+        confirmation: NTCBUP
+        start_date_time: "2022-06-24T08:00:00"
+        start_location: SEA
+        end_date_time: "2022-06-24T16:40:00"
+        end_location: DFW
+"##,
+        )
+        .unwrap();
+        let flight = itinerary.items().next().unwrap().as_flight().unwrap();
+        assert_eq!(flight.cabin(), Some(Cabin::PremiumEconomy));
+        assert_eq!(
+            flight.booking_class(),
+            Some(&BookingClass::from_str("W").unwrap())
+        );
+        assert_eq!(flight.passengers().count(), 2);
+        let simon = flight.passenger("Simon").unwrap();
+        assert_eq!(simon.seat(), Some(&"12A".to_string()));
+        assert_eq!(
+            simon.ticket_number(),
+            Some(&TicketNumber::from_str("0012345678901").unwrap())
+        );
+        assert_eq!(simon.checked_bags(), Some(1));
+        assert_eq!(flight.passenger("Jane").unwrap().checked_bags(), None);
+        assert_eq!(
+            flight.departure_gate().unwrap().to_string(),
+            "terminal 1, gate C12"
+        );
+        assert!(flight.arrival_gate().is_none());
+
+        assert_eq!(
+            from_str(&to_string(&itinerary).unwrap()).unwrap(),
+            itinerary
+        );
+
+        let for_jane = itinerary.for_traveler("Jane").unwrap();
+        let flight = for_jane.items().next().unwrap().as_flight().unwrap();
+        assert_eq!(flight.passengers().count(), 1);
+        assert!(flight.passenger("Simon").is_none());
+    }
+
+    #[test]
+    fn test_validation() {
+        assert!(BookingClass::from_str("w").is_err());
+        assert!(TicketNumber::from_str("001234567890").is_err());
+        assert_eq!(Cabin::from_str("first").unwrap(), Cabin::First);
+        assert!(Cabin::from_str("steerage").is_err());
+
+        let mut gate = AirportGate::new(Some("1"), None);
+        gate.update_from(&AirportGate::new(None, Some("C12")));
+        assert_eq!(gate, AirportGate::new(Some("1"), Some("C12")));
+        assert!(AirportGate::default().is_empty());
+    }
+}
@@ -4,7 +4,7 @@ More detailed description, with
 # Example
  */
 
-use crate::error::Result;
+use crate::error::{Error, Result};
 use crate::itinerary::Itinerary;
 use std::fs::File;
 use std::io::{Read, Write};
@@ -20,12 +20,12 @@ use std::path::Path;
 
 pub fn from_str(s: &str) -> Result<Itinerary> {
     let i: Itinerary = serde_yaml::from_str(s)?;
-    Ok(i)
+    Ok(complete_loaded(i))
 }
 
 pub fn from_reader(r: impl Read) -> Result<Itinerary> {
     let i: Itinerary = serde_yaml::from_reader(r)?;
-    Ok(i)
+    Ok(complete_loaded(i))
 }
 
 pub fn from_file(p: &Path) -> Result<Itinerary> {
@@ -37,25 +37,64 @@ pub fn from_file(p: &Path) -> Result<Itinerary> {
 // ------------------------------------------------------------------------------------------------
 
 pub fn to_string(i: &Itinerary) -> Result<String> {
-    let s = serde_yaml::to_string(i)?;
+    let s = serde_yaml::to_string(&as_saved(i))?;
     Ok(s)
 }
 
 pub fn to_writer(i: &Itinerary, p: impl Write) -> Result<()> {
-    serde_yaml::to_writer(p, i)?;
+    serde_yaml::to_writer(p, &as_saved(i))?;
     Ok(())
 }
 
+///
+/// Write the itinerary to a temporary file alongside `p`, and then replace `p` with it, so that
+/// a failure part way through leaves the original file intact.
+///
 pub fn to_file(i: &Itinerary, p: &Path) -> Result<()> {
-    let f = File::open(p)?;
-    serde_yaml::to_writer(f, i)?;
-    Ok(())
+    let mut temp_name = p.file_name().map(|v| v.to_os_string()).unwrap_or_default();
+    temp_name.push(".tmp");
+    let temp = p.with_file_name(temp_name);
+    let written = File::create(&temp)
+        .map_err(Error::from)
+        .and_then(|f| {
+            serde_yaml::to_writer(&f, &as_saved(i))?;
+            f.sync_all()?;
+            Ok(())
+        })
+        .and_then(|_| Ok(std::fs::rename(&temp, p)?));
+    if written.is_err() {
+        let _ = std::fs::remove_file(&temp);
+    }
+    written
 }
 
 // ------------------------------------------------------------------------------------------------
 // Implementations
 // ------------------------------------------------------------------------------------------------
 
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// A copy of `itinerary` without the time zones that were inferred when it was loaded, so that
+/// they are not saved as if they had been entered.
+///
+fn as_saved(itinerary: &Itinerary) -> Itinerary {
+    let mut itinerary = itinerary.clone();
+    itinerary.clear_inferred_time_zones();
+    itinerary
+}
+
+///
+/// Fill in anything that older files, or hand-written ones, may have left out.
+///
+pub(crate) fn complete_loaded(mut itinerary: Itinerary) -> Itinerary {
+    itinerary.infer_time_zones();
+    itinerary.assign_item_ids();
+    itinerary
+}
+
 // ------------------------------------------------------------------------------------------------
 // Modules
 // ------------------------------------------------------------------------------------------------
@@ -67,76 +106,142 @@ pub fn to_file(i: &Itinerary, p: &Path) -> Result<()> {
 #[cfg(test)]
 mod tests {
     use super::{from_str, to_string};
+    use crate::itinerary::location::Location;
+    use crate::itinerary::traveler::Traveler;
     use crate::itinerary::{
-        Accomodation, AirCarrierCode, AirportCode, Date, Flight, Itinerary, Transport,
-        TransportKind, TravelRecord,
+        Accomodation, AirCarrierCode, AirportCode, Date, DateTime, Duration, Flight, FlightSegment,
+        ItemCommon, ItemId, ItemLink, Itinerary, TimeZone, Transport, TransportKind, TravelRecord,
     };
     use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
     use std::str::FromStr;
 
+    fn date_time(year: i32, month: u32, day: u32, hour: u32, min: u32) -> DateTime {
+        Date::from_ymd_opt(year, month, day)
+            .unwrap()
+            .and_hms_opt(hour, min, 0)
+            .unwrap()
+    }
+
+    fn item_common(id: &str) -> ItemCommon {
+        ItemCommon {
+            id: Some(ItemId::from_str(id).unwrap()),
+            ..Default::default()
+        }
+    }
+
+    fn time_zone(s: &str) -> Option<TimeZone> {
+        Some(TimeZone::from_str(s).unwrap())
+    }
+
     fn example_itinerary() -> Itinerary {
         Itinerary {
             identifier: "2022-06".to_string(),
             name: "Playa del Carmen".to_string(),
-            travelers: vec!["Simon".to_string()],
+            metadata: Default::default(),
+            annotations: Default::default(),
+            travelers: vec![Traveler::new("Simon")],
+            bookings: Default::default(),
             items: vec![
                 Flight {
-                    number: 594,
-                    record: TravelRecord {
-                        provider: AirCarrierCode::from_str("DL").unwrap(),
-                        confirmation: Some("GROVQY".to_string()),
-                        start_date_time: Date::from_ymd(2022, 6, 24).and_hms(7, 15, 00),
-                        start_location: AirportCode::from_str("SEA").unwrap(),
-                        end_date_time: Date::from_ymd(2022, 6, 24).and_hms(15, 3, 00),
-                        end_location: Some(AirportCode::from_str("CUN").unwrap()),
+                    common: item_common("flight-dl594-20220624"),
+                    segment: FlightSegment {
+                        number: 594,
+                        operated_by: None,
+                        cabin: None,
+                        booking_class: None,
+                        passengers: Default::default(),
+                        departure_gate: None,
+                        arrival_gate: None,
+                        record: TravelRecord {
+                            provider: AirCarrierCode::from_str("DL").unwrap(),
+                            confirmation: Some("GROVQY".to_string()),
+                            start_date_time: date_time(2022, 6, 24, 7, 15),
+                            start_time_zone: time_zone("America/Los_Angeles"),
+                            start_location: AirportCode::from_str("SEA").unwrap(),
+                            end_date_time: date_time(2022, 6, 24, 15, 3),
+                            end_time_zone: time_zone("America/Cancun"),
+                            end_location: Some(AirportCode::from_str("CUN").unwrap()),
+                            start_time_zone_inferred: false,
+                            end_time_zone_inferred: false,
+                        },
                     },
                 }
                 .into(),
                 Flight {
-                    number: 604,
-                    record: TravelRecord {
-                        provider: AirCarrierCode::from_str("DL").unwrap(),
-                        confirmation: Some("GROVQY".to_string()),
-                        start_date_time: Date::from_ymd(2022, 7, 5).and_hms(14, 3, 00),
-                        start_location: AirportCode::from_str("CUN").unwrap(),
-                        end_date_time: Date::from_ymd(2022, 7, 5).and_hms(20, 23, 00),
-                        end_location: Some(AirportCode::from_str("SEA").unwrap()),
+                    common: item_common("flight-dl604-20220705"),
+                    segment: FlightSegment {
+                        number: 604,
+                        operated_by: None,
+                        cabin: None,
+                        booking_class: None,
+                        passengers: Default::default(),
+                        departure_gate: None,
+                        arrival_gate: None,
+                        record: TravelRecord {
+                            provider: AirCarrierCode::from_str("DL").unwrap(),
+                            confirmation: Some("GROVQY".to_string()),
+                            start_date_time: date_time(2022, 7, 5, 14, 3),
+                            start_time_zone: time_zone("America/Cancun"),
+                            start_location: AirportCode::from_str("CUN").unwrap(),
+                            end_date_time: date_time(2022, 7, 5, 20, 23),
+                            end_time_zone: time_zone("America/Los_Angeles"),
+                            end_location: Some(AirportCode::from_str("SEA").unwrap()),
+                            start_time_zone_inferred: false,
+                            end_time_zone_inferred: false,
+                        },
                     },
                 }
                 .into(),
                 Transport {
+                    common: item_common("transport-carm-20220624"),
                     record: TravelRecord {
                         provider: "CARM".to_string(),
                         confirmation: None,
-                        start_date_time: Date::from_ymd(2022, 6, 24).and_hms(16, 00, 00),
-                        start_location: "Cancun airport".to_string(),
-                        end_date_time: Date::from_ymd(2022, 6, 24).and_hms(17, 00, 00),
-                        end_location: Some("The Elements, Playa del Carmen".to_string()),
+                        start_date_time: date_time(2022, 6, 24, 16, 0),
+                        start_time_zone: time_zone("America/Cancun"),
+                        start_location: Location::from("Cancun airport"),
+                        end_date_time: date_time(2022, 6, 24, 17, 0),
+                        end_time_zone: time_zone("America/Cancun"),
+                        end_location: Some(Location::from("The Elements, Playa del Carmen")),
+                        start_time_zone_inferred: false,
+                        end_time_zone_inferred: false,
                     },
                     kind: Some(TransportKind::Shuttle),
                 }
                 .into(),
                 Transport {
+                    common: item_common("transport-carm-20220705"),
                     record: TravelRecord {
                         provider: "CARM".to_string(),
                         confirmation: Some("GROVQY".to_string()),
-                        start_date_time: Date::from_ymd(2022, 7, 5).and_hms(11, 00, 00),
-                        start_location: "The Elements, Playa del Carmen".to_string(),
-                        end_date_time: Date::from_ymd(2022, 7, 5).and_hms(12, 00, 00),
-                        end_location: Some("Cancun airport".to_string()),
+                        start_date_time: date_time(2022, 7, 5, 11, 0),
+                        start_time_zone: time_zone("America/Cancun"),
+                        start_location: Location::from("The Elements, Playa del Carmen"),
+                        end_date_time: date_time(2022, 7, 5, 12, 0),
+                        end_time_zone: time_zone("America/Cancun"),
+                        end_location: Some(Location::from("Cancun airport")),
+                        start_time_zone_inferred: false,
+                        end_time_zone_inferred: false,
                     },
                     kind: Some(TransportKind::Shuttle),
                 }
                 .into(),
                 Accomodation {
+                    common: item_common("accomodation-bric-20220626"),
                     record: TravelRecord {
                         provider: "bric".to_string(),
                         confirmation: Some("6015334".to_string()),
-                        start_date_time: Date::from_ymd(2022, 6, 26).and_hms(16, 00, 00),
-                        start_location: "Garden house 11, The Elements, Playa dl Carmen."
-                            .to_string(),
-                        end_date_time: Date::from_ymd(2022, 7, 5).and_hms(11, 00, 00),
+                        start_date_time: date_time(2022, 6, 26, 16, 0),
+                        start_time_zone: None,
+                        start_location: Location::from(
+                            "Garden house 11, The Elements, Playa dl Carmen.",
+                        ),
+                        end_date_time: date_time(2022, 7, 5, 11, 0),
+                        end_time_zone: None,
                         end_location: None,
+                        start_time_zone_inferred: false,
+                        end_time_zone_inferred: false,
                     },
                 }
                 .into(),
@@ -151,41 +256,54 @@ travelers:
   - Simon
 items:
   - flight:
+      id: flight-dl594-20220624
       number: 594
       record:
         provider: DL
         confirmation: GROVQY
         start_date_time: "2022-06-24T07:15:00"
+        start_time_zone: America/Los_Angeles
         start_location: SEA
         end_date_time: "2022-06-24T15:03:00"
+        end_time_zone: America/Cancun
         end_location: CUN
   - flight:
+      id: flight-dl604-20220705
       number: 604
       record:
         provider: DL
         confirmation: GROVQY
         start_date_time: "2022-07-05T14:03:00"
+        start_time_zone: America/Cancun
         start_location: CUN
         end_date_time: "2022-07-05T20:23:00"
+        end_time_zone: America/Los_Angeles
         end_location: SEA
   - transport:
+      id: transport-carm-20220624
       record:
         provider: CARM
         start_date_time: "2022-06-24T16:00:00"
+        start_time_zone: America/Cancun
         start_location: Cancun airport
         end_date_time: "2022-06-24T17:00:00"
+        end_time_zone: America/Cancun
         end_location: "The Elements, Playa del Carmen"
       kind: shuttle
   - transport:
+      id: transport-carm-20220705
       record:
         provider: CARM
         confirmation: GROVQY
         start_date_time: "2022-07-05T11:00:00"
+        start_time_zone: America/Cancun
         start_location: "The Elements, Playa del Carmen"
         end_date_time: "2022-07-05T12:00:00"
+        end_time_zone: America/Cancun
         end_location: Cancun airport
       kind: shuttle
   - accomodation:
+      id: accomodation-bric-20220626
       record:
         provider: bric
         confirmation: "6015334"
@@ -207,4 +325,360 @@ items:
         println!("{:#?}", itinerary);
         assert_eq!(itinerary, example_itinerary());
     }
+
+    #[test]
+    fn test_deserialization_infers_time_zones() {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/data/playa.yml");
+        let itinerary = super::from_file(&path).unwrap();
+        assert_eq!(itinerary, example_itinerary());
+    }
+
+    #[test]
+    fn test_inferred_time_zones_are_not_saved() {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/data/playa.yml");
+        let itinerary = super::from_file(&path).unwrap();
+        assert!(itinerary.items().any(|item| item.start_instant().is_some()));
+        assert!(!to_string(&itinerary).unwrap().contains("time_zone"));
+
+        let dir = std::env::temp_dir().join(format!("io-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("playa.yml");
+        super::to_file(&itinerary, &file).unwrap();
+        assert_eq!(super::from_file(&file).unwrap(), itinerary);
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_time_zone_strings() {
+        for s in ["America/Cancun", "+05:30", "-07:00", "+00:00"] {
+            assert_eq!(TimeZone::from_str(s).unwrap().to_string(), s);
+        }
+        assert!(TimeZone::from_str("Mars/Olympus_Mons").is_err());
+        assert!(TimeZone::from_str("+05:75").is_err());
+    }
+
+    #[test]
+    fn test_flight_duration_across_zones() {
+        let itinerary = example_itinerary();
+        let flight = itinerary.items().next().unwrap().as_flight().unwrap();
+        assert_eq!(
+            flight.departure_instant().unwrap().to_rfc3339(),
+            "2022-06-24T14:15:00+00:00"
+        );
+        assert_eq!(flight.duration(), Duration::minutes(5 * 60 + 48));
+    }
+
+    #[test]
+    fn test_item_ids_and_links() {
+        let itinerary = from_str(
+            r##"---
+identifier: 2022-06
+name: Playa del Carmen
+travelers: []
+items:
+  - flight:
+      number: 594
+      record:
+        provider: DL
+        start_date_time: "2022-06-24T07:15:00"
+        start_location: SEA
+        end_date_time: "2022-06-24T15:03:00"
+        end_location: CUN
+  - transport:
+      id: shuttle-in
+      links:
+        - meets: flight-dl594-20220624
+      record:
+        provider: CARM
+        start_date_time: "2022-06-24T16:00:00"
+        start_location: Cancun airport
+        end_date_time: "2022-06-24T17:00:00"
+        end_location: "The Elements, Playa del Carmen"
+  - event:
+      record:
+        provider: Xcaret
+        start_date_time: "2022-06-24T18:00:00"
+        start_location: Xcaret Park
+        end_date_time: "2022-06-24T22:00:00"
+  - event:
+      record:
+        provider: Xcaret
+        start_date_time: "2022-06-24T23:00:00"
+        start_location: Xcaret Park
+        end_date_time: "2022-06-24T23:30:00"
+"##,
+        )
+        .unwrap();
+        let ids: Vec<String> = itinerary
+            .items()
+            .map(|item| item.id().unwrap().to_string())
+            .collect();
+        assert_eq!(
+            ids,
+            vec![
+                "flight-dl594-20220624",
+                "shuttle-in",
+                "event-xcaret-20220624",
+                "event-xcaret-20220624-2",
+            ]
+        );
+
+        let flight_id = ItemId::from_str("flight-dl594-20220624").unwrap();
+        let shuttle_id = ItemId::from_str("shuttle-in").unwrap();
+        assert!(itinerary.item(&flight_id).unwrap().is_flight());
+
+        let linked = itinerary.linked_items(&shuttle_id);
+        assert_eq!(linked.len(), 1);
+        assert_eq!(linked[0].0, &ItemLink::Meets(flight_id.clone()));
+        assert_eq!(linked[0].1.id(), Some(&flight_id));
+
+        let linking = itinerary.items_linking_to(&flight_id);
+        assert_eq!(linking.len(), 1);
+        assert_eq!(linking[0].1.id(), Some(&shuttle_id));
+
+        let serial = to_string(&itinerary).unwrap();
+        assert!(serial.contains("id: event-xcaret-20220624-2"));
+        assert_eq!(from_str(&serial).unwrap(), itinerary);
+    }
+
+    const EXAMPLE_JOURNEY_STR: &str = r##"---
+identifier: 2022-06
+name: Playa del Carmen
+travelers: []
+items:
+  - journey:
+      record_locator: GROVQY
+      segments:
+        - number: 1850
+          record:
+            provider: DL
+            start_date_time: "2022-06-24T06:00:00"
+            start_location: SEA
+            end_date_time: "2022-06-24T13:50:00"
+            end_location: ATL
+        - number: 7311
+          operated_by:
+            carrier: AM
+            number: 481
+          record:
+            provider: DL
+            start_date_time: "2022-06-24T15:20:00"
+            start_location: ATL
+            end_date_time: "2022-06-24T17:05:00"
+            end_location: CUN
+"##;
+
+    #[test]
+    fn test_journey_connections() {
+        let itinerary = from_str(EXAMPLE_JOURNEY_STR).unwrap();
+        let item = itinerary.items().next().unwrap();
+        assert_eq!(
+            item.id().unwrap().to_string(),
+            "journey-dl1850-20220624".to_string()
+        );
+        let journey = item.as_journey().unwrap();
+        assert_eq!(journey.record_locator(), Some(&"GROVQY".to_string()));
+        assert_eq!(journey.flight_numbers_string(), "DL1850/DL7311");
+        assert_eq!(journey.departure_airport().to_string(), "SEA");
+        assert_eq!(journey.arrival_airport().unwrap().to_string(), "CUN");
+        assert_eq!(
+            journey
+                .connection_airports()
+                .map(|v| v.to_string())
+                .collect::<Vec<String>>(),
+            vec!["ATL".to_string()]
+        );
+
+        let connections = journey.connections();
+        assert_eq!(connections.len(), 1);
+        assert_eq!(connections[0].layover(), Duration::minutes(90));
+        assert_eq!(
+            connections[0]
+                .departing()
+                .operated_by()
+                .unwrap()
+                .flight_number_string(),
+            "AM481"
+        );
+        // 06:00 PDT to 17:05 EST
+        assert_eq!(journey.duration(), Duration::minutes(9 * 60 + 5));
+        assert_eq!(
+            item.start_instant().unwrap().to_rfc3339(),
+            "2022-06-24T13:00:00+00:00"
+        );
+    }
+
+    #[test]
+    fn test_journey_requires_segments() {
+        let result = from_str(
+            r##"---
+identifier: 2022-06
+name: Playa del Carmen
+travelers: []
+items:
+  - journey:
+      record_locator: GROVQY
+      segments: []
+"##,
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_journey_segments_have_no_common_values() {
+        for common in [
+            "travelers: [Bob]",
+            "status: cancelled",
+            "cost:\n            total: 100 USD",
+        ] {
+            let journey = EXAMPLE_JOURNEY_STR.replace(
+                "        - number: 7311\n",
+                &format!("        - number: 7311\n          {}\n", common),
+            );
+            assert!(from_str(&journey).is_err(), "accepted {}", common);
+        }
+    }
+
+    #[test]
+    fn test_rail_changes() {
+        let itinerary = from_str(
+            r##"---
+identifier: 2023-05
+name: Paris to Munich
+travelers: []
+items:
+  - rail:
+      ticket_reference: QXZ7RT
+      legs:
+        - train_number: TGV 9577
+          class: "1"
+          coach: "12"
+          seat: "45"
+          record:
+            provider: SNCF
+            start_date_time: "2023-05-12T09:55:00"
+            start_location:
+              name: Paris Est
+              station: "8700011"
+            end_date_time: "2023-05-12T13:04:00"
+            end_location:
+              name: Stuttgart Hbf
+              city: Stuttgart
+              country_code: DE
+              station: "8000096"
+        - train_number: ICE 591
+          record:
+            provider: DB
+            start_date_time: "2023-05-12T13:51:00"
+            start_location: Stuttgart Hbf
+            end_date_time: "2023-05-12T16:08:00"
+            end_location: Munich Hbf
+"##,
+        )
+        .unwrap();
+        let item = itinerary.items().next().unwrap();
+        assert_eq!(
+            item.id().unwrap().to_string(),
+            "rail-sncf-tgv-9577-20230512".to_string()
+        );
+        let rail = item.as_rail().unwrap();
+        assert_eq!(rail.ticket_reference(), Some(&"QXZ7RT".to_string()));
+        assert_eq!(rail.train_numbers_string(), "TGV 9577/ICE 591");
+        assert_eq!(
+            rail.departure_station().station(),
+            Some(&"8700011".to_string())
+        );
+        assert_eq!(rail.arrival_station().unwrap().name(), "Munich Hbf");
+        assert_eq!(rail.first_leg().coach(), Some(&"12".to_string()));
+        assert_eq!(rail.first_leg().seat(), Some(&"45".to_string()));
+
+        let changes = rail.changes();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].station().name(), "Stuttgart Hbf");
+        assert_eq!(changes[0].change_time(), Duration::minutes(47));
+        assert_eq!(
+            rail.departure_time_zone(),
+            time_zone("Europe/Paris").as_ref()
+        );
+        assert_eq!(
+            rail.first_leg().arrival_time_zone(),
+            time_zone("Europe/Berlin").as_ref()
+        );
+        assert_eq!(rail.duration(), Duration::minutes(6 * 60 + 13));
+
+        assert!(from_str(
+            r##"---
+identifier: 2023-05
+name: Paris to Munich
+travelers: []
+items:
+  - rail:
+      legs: []
+"##
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_cruise_port_calls() {
+        let itinerary = from_str(
+            r##"---
+identifier: 2023-09
+name: Norwegian fjords
+travelers: []
+items:
+  - cruise:
+      ship: Hurtigruten Nordnorge
+      cabin: "426"
+      record:
+        provider: Hurtigruten
+        confirmation: HX42QP
+        start_date_time: "2023-09-01T20:30:00"
+        start_location:
+          name: Skoltegrunnskaien
+          city: Bergen
+        end_date_time: "2023-09-05T10:00:00"
+        end_location: Trondheim
+      port_calls:
+        - port: Geiranger
+          arrival_date_time: "2023-09-02T09:30:00"
+          departure_date_time: "2023-09-02T13:30:00"
+        - port: Alesund
+          arrival_date_time: "2023-09-02T16:00:00"
+          departure_date_time: "2023-09-03T08:00:00"
+"##,
+        )
+        .unwrap();
+        let item = itinerary.items().next().unwrap();
+        assert_eq!(
+            item.id().unwrap().to_string(),
+            "cruise-hurtigruten-20230901".to_string()
+        );
+        let cruise = item.as_cruise().unwrap();
+        assert_eq!(cruise.ship(), "Hurtigruten Nordnorge");
+        assert_eq!(cruise.cabin(), Some(&"426".to_string()));
+        assert_eq!(cruise.confirmation(), Some(&"HX42QP".to_string()));
+        assert_eq!(cruise.embarkation_port().name(), "Skoltegrunnskaien");
+        assert_eq!(cruise.disembarkation_port().name(), "Trondheim");
+        assert_eq!(
+            cruise.embarkation_time_zone(),
+            time_zone("Europe/Oslo").as_ref()
+        );
+
+        let port_calls: Vec<_> = cruise.port_calls().collect();
+        assert_eq!(port_calls.len(), 2);
+        assert_eq!(port_calls[0].duration(), Duration::hours(4));
+        assert_eq!(port_calls[0].time_zone(), None);
+        assert_eq!(port_calls[1].time_zone(), time_zone("Europe/Oslo").as_ref());
+        assert_eq!(
+            cruise.days_at_sea(),
+            vec![Date::from_ymd_opt(2023, 9, 4).unwrap()]
+        );
+
+        assert_eq!(
+            from_str(&to_string(&itinerary).unwrap()).unwrap(),
+            itinerary
+        );
+    }
 }
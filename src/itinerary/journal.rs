@@ -0,0 +1,501 @@
+/*!
+An append-only journal of the changes made to an itinerary file, allowing them to be undone and
+redone.
+
+The journal for `trip.yml` is kept alongside it as `trip.yml.journal`, one JSON record per line.
+Every edit is recorded together with the command that reverses it, so undoing never has to work
+out how to reverse a change after the fact. Undo and redo are themselves recorded rather than
+removing anything, so the journal is a complete history; the current undo and redo stacks are
+rebuilt by replaying it when the journal is opened.
+
+Each record notes when it was made and the origin of the change, usually the command line that
+made it. The last record written by each save also holds a hash of the itinerary as it was saved,
+so that edits, undo and redo can refuse to act on a file that has been changed by other means
+since; the journal would otherwise record changes on top of one it knows nothing about.
+ */
+
+use_required!();
+use crate::error::{ErrorKind, Result};
+use crate::itinerary::edit::EditCommand;
+use crate::itinerary::io::to_string;
+use crate::itinerary::{Instant, Itinerary};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JournalAction {
+    Edit,
+    Undo,
+    Redo,
+}
+
+///
+/// A change made to the itinerary. `command` is the change that was applied and `reverse` the
+/// command that reverses it; for an undo or redo, `of` is the sequence number of the original
+/// edit. `sha256` is the hash of the itinerary saved after this change, if it was the last one
+/// made before saving.
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    sequence: u64,
+    at: Instant,
+    origin: String,
+    action: JournalAction,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    of: Option<u64>,
+    command: EditCommand,
+    reverse: EditCommand,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sha256: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Journal {
+    path: PathBuf,
+    entries: Vec<JournalEntry>,
+    saved: usize,
+    undo_stack: Vec<usize>,
+    redo_stack: Vec<usize>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Display for JournalEntry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:>4}  {}  ",
+            self.sequence,
+            self.at.format("%Y-%m-%d %H:%M:%S")
+        )?;
+        match (self.action, self.of) {
+            (JournalAction::Undo, Some(of)) => write!(f, "undo {}: ", of)?,
+            (JournalAction::Redo, Some(of)) => write!(f, "redo {}: ", of)?,
+            _ => {}
+        }
+        write!(f, "{}  ({})", self.command, self.origin)
+    }
+}
+
+impl JournalEntry {
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    pub fn at(&self) -> &Instant {
+        &self.at
+    }
+
+    ///
+    /// Where the change came from, usually the command line that made it.
+    ///
+    pub fn origin(&self) -> &String {
+        &self.origin
+    }
+
+    pub fn action(&self) -> JournalAction {
+        self.action
+    }
+
+    ///
+    /// The sequence number of the edit this entry applies, undoes, or redoes.
+    ///
+    pub fn edit_sequence(&self) -> u64 {
+        self.of.unwrap_or(self.sequence)
+    }
+
+    pub fn command(&self) -> &EditCommand {
+        &self.command
+    }
+
+    pub fn reverse(&self) -> &EditCommand {
+        &self.reverse
+    }
+
+    pub fn sha256(&self) -> Option<&String> {
+        self.sha256.as_ref()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Journal {
+    ///
+    /// The path of the journal kept for the itinerary file at `itinerary_file`.
+    ///
+    pub fn path_for(itinerary_file: &Path) -> PathBuf {
+        let mut file_name = itinerary_file
+            .file_name()
+            .map(|v| v.to_os_string())
+            .unwrap_or_default();
+        file_name.push(".journal");
+        itinerary_file.with_file_name(file_name)
+    }
+
+    ///
+    /// Open the journal for the itinerary file at `itinerary_file`; if there is no journal yet the
+    /// result is empty, and the journal file is only created when an entry is saved.
+    ///
+    pub fn open(itinerary_file: &Path) -> Result<Self> {
+        let path = Self::path_for(itinerary_file);
+        let mut entries = Vec::new();
+        if path.is_file() {
+            for line in BufReader::new(File::open(&path)?).lines() {
+                let line = line?;
+                if !line.trim().is_empty() {
+                    entries.push(serde_json::from_str(&line)?);
+                }
+            }
+        }
+        let mut journal = Self {
+            path,
+            saved: entries.len(),
+            entries: Vec::with_capacity(entries.len()),
+            undo_stack: Default::default(),
+            redo_stack: Default::default(),
+        };
+        for entry in entries {
+            journal.push(entry);
+        }
+        Ok(journal)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &JournalEntry> {
+        self.entries.iter()
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    ///
+    /// Apply `command` to `itinerary`, recording it along with the command that reverses it. Any
+    /// edits that were undone can no longer be redone. It is an error if `itinerary` is not the
+    /// one saved with the journal's last entry.
+    ///
+    pub fn edit(
+        &mut self,
+        itinerary: &mut Itinerary,
+        command: EditCommand,
+        origin: &str,
+        at: Instant,
+    ) -> Result<&JournalEntry> {
+        self.check_current(itinerary)?;
+        let reverse = command.apply(itinerary)?;
+        Ok(self.append(origin, at, JournalAction::Edit, None, command, reverse))
+    }
+
+    ///
+    /// Reverse the most recent edit, or redo, that has not already been undone; returns `None`
+    /// if there is nothing to undo. It is an error if `itinerary` is not the one saved with the
+    /// journal's last entry.
+    ///
+    pub fn undo(
+        &mut self,
+        itinerary: &mut Itinerary,
+        origin: &str,
+        at: Instant,
+    ) -> Result<Option<&JournalEntry>> {
+        self.reverse_last(JournalAction::Undo, itinerary, origin, at)
+    }
+
+    ///
+    /// Reapply the most recently undone edit; returns `None` if there is nothing to redo. It is
+    /// an error if `itinerary` is not the one saved with the journal's last entry.
+    ///
+    pub fn redo(
+        &mut self,
+        itinerary: &mut Itinerary,
+        origin: &str,
+        at: Instant,
+    ) -> Result<Option<&JournalEntry>> {
+        self.reverse_last(JournalAction::Redo, itinerary, origin, at)
+    }
+
+    ///
+    /// Returns `true` if `itinerary` is the one saved with the journal's last entry, or if there
+    /// is no record of what was saved. Once entries have been added, and not yet saved, the
+    /// itinerary is expected to differ from the saved one and is not checked.
+    ///
+    pub fn is_current(&self, itinerary: &Itinerary) -> Result<bool> {
+        if self.saved < self.entries.len() {
+            return Ok(true);
+        }
+        match self
+            .entries
+            .iter()
+            .rev()
+            .find_map(|entry| entry.sha256.as_ref())
+        {
+            None => Ok(true),
+            Some(sha256) => Ok(&itinerary_hash(itinerary)? == sha256),
+        }
+    }
+
+    ///
+    /// Append any entries not yet written to the journal file, recording in the last of them the
+    /// content of `itinerary`. This should be called with the changed itinerary just before it
+    /// is saved; if saving the itinerary then fails the journal no longer matches the file, and
+    /// edit, undo and redo will refuse to use it.
+    ///
+    pub fn save(&mut self, itinerary: &Itinerary) -> Result<()> {
+        if self.saved < self.entries.len() {
+            let sha256 = itinerary_hash(itinerary)?;
+            self.entries.last_mut().unwrap().sha256 = Some(sha256);
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)?;
+            for entry in &self.entries[self.saved..] {
+                writeln!(file, "{}", serde_json::to_string(entry)?)?;
+            }
+            self.saved = self.entries.len();
+        }
+        Ok(())
+    }
+
+    ///
+    /// Undo the entry on top of the undo stack, or redo the one on top of the redo stack, by
+    /// applying its reverse.
+    ///
+    fn reverse_last(
+        &mut self,
+        action: JournalAction,
+        itinerary: &mut Itinerary,
+        origin: &str,
+        at: Instant,
+    ) -> Result<Option<&JournalEntry>> {
+        let stack = match action {
+            JournalAction::Redo => &self.redo_stack,
+            _ => &self.undo_stack,
+        };
+        let index = match stack.last() {
+            None => return Ok(None),
+            Some(index) => *index,
+        };
+        self.check_current(itinerary)?;
+        let entry = &self.entries[index];
+        let of = entry.edit_sequence();
+        let command = entry.reverse.clone();
+        let reverse = command.apply(itinerary)?;
+        Ok(Some(self.append(
+            origin,
+            at,
+            action,
+            Some(of),
+            command,
+            reverse,
+        )))
+    }
+
+    fn check_current(&self, itinerary: &Itinerary) -> Result<()> {
+        if self.is_current(itinerary)? {
+            Ok(())
+        } else {
+            Err(ErrorKind::JournalOutOfDate(self.path.display().to_string()).into())
+        }
+    }
+
+    fn append(
+        &mut self,
+        origin: &str,
+        at: Instant,
+        action: JournalAction,
+        of: Option<u64>,
+        command: EditCommand,
+        reverse: EditCommand,
+    ) -> &JournalEntry {
+        let sequence = self.entries.last().map(|v| v.sequence + 1).unwrap_or(1);
+        self.push(JournalEntry {
+            sequence,
+            at,
+            origin: origin.to_string(),
+            action,
+            of,
+            command,
+            reverse,
+            sha256: None,
+        });
+        self.entries.last().unwrap()
+    }
+
+    fn push(&mut self, entry: JournalEntry) {
+        let index = self.entries.len();
+        match entry.action {
+            JournalAction::Edit => {
+                self.redo_stack.clear();
+                self.undo_stack.push(index);
+            }
+            JournalAction::Undo => {
+                let _ = self.undo_stack.pop();
+                self.redo_stack.push(index);
+            }
+            JournalAction::Redo => {
+                let _ = self.redo_stack.pop();
+                self.undo_stack.push(index);
+            }
+        }
+        self.entries.push(entry);
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn itinerary_hash(itinerary: &Itinerary) -> Result<String> {
+    Ok(Sha256::digest(to_string(itinerary)?.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::{Journal, JournalAction};
+    use crate::itinerary::edit::EditCommand;
+    use crate::itinerary::io::{from_file, from_str, to_file};
+    use crate::itinerary::traveler::Traveler;
+    use crate::itinerary::Instant;
+    use chrono::TimeZone;
+    use pretty_assertions::assert_eq;
+    use std::path::Path;
+
+    fn at(minute: u32) -> Instant {
+        chrono::Utc
+            .with_ymd_and_hms(2022, 5, 1, 12, minute, 0)
+            .unwrap()
+    }
+
+    fn rename(name: &str) -> EditCommand {
+        EditCommand::Rename {
+            name: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_journal_path() {
+        assert_eq!(
+            Journal::path_for(Path::new("trips/playa.yml")),
+            Path::new("trips/playa.yml.journal")
+        );
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let dir = std::env::temp_dir().join(format!("journal-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("playa.yml");
+        let _ = std::fs::remove_file(Journal::path_for(&file));
+
+        let mut itinerary =
+            from_str("identifier: 2022-06\nname: Playa\ntravelers: []\nitems: []\n").unwrap();
+        let mut journal = Journal::open(&file).unwrap();
+        assert!(!journal.can_undo());
+        assert!(journal
+            .undo(&mut itinerary, "undo", at(0))
+            .unwrap()
+            .is_none());
+
+        let _ = journal
+            .edit(&mut itinerary, rename("Playa del Carmen"), "edit 1", at(1))
+            .unwrap();
+        let _ = journal
+            .edit(
+                &mut itinerary,
+                EditCommand::AddTraveler {
+                    traveler: Traveler::new("Simon"),
+                    index: None,
+                },
+                "edit 2",
+                at(2),
+            )
+            .unwrap();
+        let undone = journal
+            .undo(&mut itinerary, "undo", at(3))
+            .unwrap()
+            .unwrap();
+        assert_eq!(undone.edit_sequence(), 2);
+        assert_eq!(itinerary.travelers().count(), 0);
+        journal.save(&itinerary).unwrap();
+        to_file(&itinerary, &file).unwrap();
+
+        // the journal survives being reopened, and matches the saved itinerary
+        let mut journal = Journal::open(&file).unwrap();
+        let mut itinerary = from_file(&file).unwrap();
+        assert!(journal.is_current(&itinerary).unwrap());
+        assert_eq!(journal.entries().count(), 3);
+        assert!(journal.can_redo());
+        let redone = journal
+            .redo(&mut itinerary, "redo", at(4))
+            .unwrap()
+            .unwrap();
+        assert_eq!(redone.edit_sequence(), 2);
+        assert_eq!(itinerary.travelers().count(), 1);
+        let _ = journal.undo(&mut itinerary, "undo", at(5)).unwrap();
+        let _ = journal.undo(&mut itinerary, "undo", at(6)).unwrap();
+        assert_eq!(itinerary.name(), "Playa");
+        assert!(!journal.can_undo());
+
+        // a new edit discards what could be redone
+        let _ = journal
+            .edit(&mut itinerary, rename("Cancun"), "edit 3", at(7))
+            .unwrap();
+        assert!(!journal.can_redo());
+        journal.save(&itinerary).unwrap();
+        to_file(&itinerary, &file).unwrap();
+
+        // a file changed outside the journal can not be undone, or edited
+        let mut changed = from_file(&file).unwrap();
+        let _ = rename("Tulum").apply(&mut changed).unwrap();
+        let mut journal = Journal::open(&file).unwrap();
+        assert!(!journal.is_current(&changed).unwrap());
+        assert!(journal.undo(&mut changed, "undo", at(8)).is_err());
+        assert_eq!(changed.name(), "Tulum");
+        assert!(journal
+            .edit(&mut changed, rename("Cozumel"), "edit 4", at(8))
+            .is_err());
+        assert_eq!(changed.name(), "Tulum");
+        journal.save(&changed).unwrap();
+
+        let journal = Journal::open(&file).unwrap();
+        let entries: Vec<String> = journal.entries().map(|v| v.origin().clone()).collect();
+        assert_eq!(
+            entries,
+            vec!["edit 1", "edit 2", "undo", "redo", "undo", "undo", "edit 3"]
+        );
+        let entry = journal.entries().nth(5).unwrap();
+        assert_eq!(entry.action(), JournalAction::Undo);
+        assert_eq!(entry.edit_sequence(), 1);
+        assert_eq!(entry.command(), &rename("Playa"));
+        assert_eq!(
+            journal.entries().last().unwrap().to_string(),
+            "   7  2022-05-01 12:07:00  rename itinerary to 'Cancun'  (edit 3)"
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
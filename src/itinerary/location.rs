@@ -0,0 +1,428 @@
+/*!
+The places that items start and end at.
+
+A location always has a name, such as "Cancun airport" or "The Elements, Playa del Carmen", and
+may add a street address, city, country, coordinates, and the code of the airport or station it
+refers to. For compatibility with earlier files a location with nothing but a name is read from,
+and written to, a plain string.
+ */
+
+use_required!();
+use crate::error::{Error, ErrorKind};
+use crate::itinerary::{AirportCode, TimeZone, TimeZoneLocation};
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(from = "LocationRepr", into = "LocationRepr")]
+pub struct Location {
+    name: String,
+    details: LocationDetails,
+}
+
+is_valid_newstring!(
+    CountryCode,
+    is_valid_country_code,
+    Hash,
+    Deserialize,
+    Serialize
+);
+
+///
+/// A position on the Earth's surface, in decimal degrees.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "CoordinatesRepr")]
+pub struct Coordinates {
+    latitude: f64,
+    longitude: f64,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+struct LocationDetails {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    street_address: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    city: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    country_code: Option<CountryCode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    coordinates: Option<Coordinates>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    airport: Option<AirportCode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    station: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum LocationRepr {
+    Name(String),
+    Full {
+        name: String,
+        #[serde(flatten)]
+        details: LocationDetails,
+    },
+}
+
+#[derive(Deserialize)]
+struct CoordinatesRepr {
+    latitude: f64,
+    longitude: f64,
+}
+
+const EARTH_RADIUS_KM: f64 = 6371.0;
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl From<LocationRepr> for Location {
+    fn from(v: LocationRepr) -> Self {
+        match v {
+            LocationRepr::Name(name) => Self::new(&name),
+            LocationRepr::Full { name, details } => Self { name, details },
+        }
+    }
+}
+
+impl From<Location> for LocationRepr {
+    fn from(v: Location) -> Self {
+        if v.details == LocationDetails::default() {
+            LocationRepr::Name(v.name)
+        } else {
+            LocationRepr::Full {
+                name: v.name,
+                details: v.details,
+            }
+        }
+    }
+}
+
+impl From<&str> for Location {
+    fn from(v: &str) -> Self {
+        Self::new(v)
+    }
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+impl TimeZoneLocation for Location {
+    fn time_zone(&self) -> Option<TimeZone> {
+        self.details
+            .airport
+            .as_ref()
+            .and_then(TimeZone::for_airport)
+            .or_else(|| self.details.city.as_deref().and_then(TimeZone::for_address))
+            .or_else(|| TimeZone::for_address(&self.name))
+            .or_else(|| {
+                self.details
+                    .street_address
+                    .as_deref()
+                    .and_then(TimeZone::for_address)
+            })
+    }
+}
+
+impl Location {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            details: Default::default(),
+        }
+    }
+
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    pub fn street_address(&self) -> Option<&String> {
+        self.details.street_address.as_ref()
+    }
+
+    pub fn set_street_address(&mut self, street_address: &str) {
+        self.details.street_address = Some(street_address.to_string());
+    }
+
+    pub fn city(&self) -> Option<&String> {
+        self.details.city.as_ref()
+    }
+
+    pub fn set_city(&mut self, city: &str) {
+        self.details.city = Some(city.to_string());
+    }
+
+    ///
+    /// The ISO 3166-1 alpha-2 code of the country this location is in.
+    ///
+    pub fn country_code(&self) -> Option<&CountryCode> {
+        self.details.country_code.as_ref()
+    }
+
+    pub fn set_country_code(&mut self, country_code: CountryCode) {
+        self.details.country_code = Some(country_code);
+    }
+
+    pub fn coordinates(&self) -> Option<&Coordinates> {
+        self.details.coordinates.as_ref()
+    }
+
+    pub fn set_coordinates(&mut self, coordinates: Coordinates) {
+        self.details.coordinates = Some(coordinates);
+    }
+
+    ///
+    /// The airport this location is, or is at.
+    ///
+    pub fn airport(&self) -> Option<&AirportCode> {
+        self.details.airport.as_ref()
+    }
+
+    pub fn set_airport(&mut self, airport: AirportCode) {
+        self.details.airport = Some(airport);
+    }
+
+    ///
+    /// The code of the railway station this location is, or is at; e.g. a UIC code.
+    ///
+    pub fn station(&self) -> Option<&String> {
+        self.details.station.as_ref()
+    }
+
+    pub fn set_station(&mut self, station: &str) {
+        self.details.station = Some(station.to_string());
+    }
+
+    ///
+    /// The great-circle distance, in kilometers, between this and another location if both
+    /// have coordinates.
+    ///
+    pub fn distance_km(&self, other: &Location) -> Option<f64> {
+        match (self.coordinates(), other.coordinates()) {
+            (Some(lhs), Some(rhs)) => Some(lhs.distance_km(rhs)),
+            _ => None,
+        }
+    }
+
+    ///
+    /// A link to this location on OpenStreetMap; using its coordinates if known, otherwise a
+    /// search for its address.
+    ///
+    pub fn map_url(&self) -> String {
+        match self.coordinates() {
+            Some(coordinates) => format!(
+                "https://www.openstreetmap.org/?mlat={}&mlon={}#map=17/{}/{}",
+                coordinates.latitude,
+                coordinates.longitude,
+                coordinates.latitude,
+                coordinates.longitude
+            ),
+            None => {
+                let query: Vec<&str> = std::iter::once(self.name.as_str())
+                    .chain(self.details.street_address.as_deref())
+                    .chain(self.details.city.as_deref())
+                    .chain(self.details.country_code.as_deref())
+                    .collect();
+                format!(
+                    "https://www.openstreetmap.org/search?query={}",
+                    url_encode(&query.join(", "))
+                )
+            }
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl TryFrom<CoordinatesRepr> for Coordinates {
+    type Error = Error;
+
+    fn try_from(v: CoordinatesRepr) -> std::result::Result<Self, Self::Error> {
+        Self::new(v.latitude, v.longitude).ok_or_else(|| {
+            ErrorKind::InvalidValue(
+                "coordinates".to_string(),
+                format!("({}, {}) are out of range", v.latitude, v.longitude),
+            )
+            .into()
+        })
+    }
+}
+
+impl Coordinates {
+    ///
+    /// Returns `None` if either value is out of range.
+    ///
+    pub fn new(latitude: f64, longitude: f64) -> Option<Self> {
+        if (-90.0..=90.0).contains(&latitude) && (-180.0..=180.0).contains(&longitude) {
+            Some(Self {
+                latitude,
+                longitude,
+            })
+        } else {
+            None
+        }
+    }
+
+    pub fn latitude(&self) -> f64 {
+        self.latitude
+    }
+
+    pub fn longitude(&self) -> f64 {
+        self.longitude
+    }
+
+    ///
+    /// The great-circle distance, in kilometers, to `other` using the haversine formula.
+    ///
+    pub fn distance_km(&self, other: &Coordinates) -> f64 {
+        let (lat1, lat2) = (self.latitude.to_radians(), other.latitude.to_radians());
+        let d_lat = lat2 - lat1;
+        let d_lon = (other.longitude - self.longitude).to_radians();
+        let a = (d_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (d_lon / 2.0).sin().powi(2);
+        2.0 * EARTH_RADIUS_KM * a.sqrt().asin()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+#[inline]
+fn is_valid_country_code(s: &str) -> bool {
+    s.len() == 2 && s.chars().all(|c| c.is_ascii_uppercase())
+}
+
+fn url_encode(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            b' ' => "+".to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::{Coordinates, CountryCode, Location};
+    use crate::itinerary::{AirportCode, TimeZone, TimeZoneLocation};
+    use pretty_assertions::assert_eq;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_name_only_is_a_string() {
+        let location: Location = serde_yaml::from_str("Cancun airport").unwrap();
+        assert_eq!(location, Location::new("Cancun airport"));
+        assert_eq!(
+            serde_yaml::to_string(&location).unwrap(),
+            "---\nCancun airport\n".to_string()
+        );
+    }
+
+    #[test]
+    fn test_full_location() {
+        let yaml = r##"---
+name: The Elements
+street_address: Calle 38 Norte
+city: Playa del Carmen
+country_code: MX
+coordinates:
+  latitude: 20.6339
+  longitude: -87.0679
+"##;
+        let location: Location = serde_yaml::from_str(yaml).unwrap();
+
+        let mut expected = Location::new("The Elements");
+        expected.set_street_address("Calle 38 Norte");
+        expected.set_city("Playa del Carmen");
+        expected.set_country_code(CountryCode::from_str("MX").unwrap());
+        expected.set_coordinates(Coordinates::new(20.6339, -87.0679).unwrap());
+
+        assert_eq!(location, expected);
+        assert_eq!(serde_yaml::to_string(&location).unwrap(), yaml.to_string());
+        assert_eq!(
+            location.map_url(),
+            "https://www.openstreetmap.org/?mlat=20.6339&mlon=-87.0679#map=17/20.6339/-87.0679"
+        );
+    }
+
+    #[test]
+    fn test_location_time_zone() {
+        let mut airport = Location::new("Terminal 3");
+        airport.set_airport(AirportCode::from_str("CUN").unwrap());
+        assert_eq!(
+            airport.time_zone(),
+            Some(TimeZone::from_str("America/Cancun").unwrap())
+        );
+
+        let mut hotel = Location::new("Hotel Xcaret");
+        hotel.set_city("Cancun");
+        assert_eq!(hotel.time_zone(), airport.time_zone());
+
+        assert_eq!(Location::new("Xcaret Park").time_zone(), None);
+
+        // only whole words match, and the first city named is the most specific
+        let named = |s: &str| Some(TimeZone::from_str(s).unwrap());
+        assert_eq!(
+            TimeZone::for_address("Seattle, Washington"),
+            named("America/Los_Angeles")
+        );
+        assert_eq!(
+            TimeZone::for_address("1600 Pennsylvania Ave, Washington DC"),
+            named("America/New_York")
+        );
+        assert_eq!(TimeZone::for_address("Jerome, Arizona"), None);
+        assert_eq!(
+            TimeZone::for_address("Piazza Navona, Rome"),
+            named("Europe/Rome")
+        );
+        // an airport code is only recognized as the whole address
+        assert_eq!(TimeZone::for_address(" LHR "), named("Europe/London"));
+        assert_eq!(
+            TimeZone::for_address("Near Paris, via LHR"),
+            named("Europe/Paris")
+        );
+        assert_eq!(TimeZone::for_address("HOTEL SAN JUAN"), None);
+        assert_eq!(TimeZone::for_address("SAN JOSE"), None);
+        assert_eq!(
+            Location::new("Xcaret Park").map_url(),
+            "https://www.openstreetmap.org/search?query=Xcaret+Park"
+        );
+    }
+
+    #[test]
+    fn test_distance() {
+        let sea = Coordinates::new(47.4490, -122.3093).unwrap();
+        let cun = Coordinates::new(21.0365, -86.8771).unwrap();
+        assert_eq!(sea.distance_km(&cun).round(), 4321.0);
+        assert_eq!(sea.distance_km(&sea), 0.0);
+        assert!(Coordinates::new(91.0, 0.0).is_none());
+        assert!(serde_yaml::from_str::<Coordinates>("latitude: 91.0\nlongitude: 0.0\n").is_err());
+        assert_eq!(
+            serde_yaml::from_str::<Coordinates>("latitude: 47.449\nlongitude: -122.3093\n")
+                .unwrap(),
+            Coordinates::new(47.449, -122.3093).unwrap()
+        );
+        assert!(Location::new("SEA")
+            .distance_km(&Location::new("CUN"))
+            .is_none());
+    }
+}
@@ -0,0 +1,329 @@
+/*!
+Details about an itinerary as a whole, and a summary derived from its items.
+
+The metadata records why and for whom a trip is being made: its purpose, the cost center it is
+charged to, the person responsible for it, where it is going, and when the itinerary was
+created and last modified. All of it is optional.
+
+The summary is never stored; it is worked out from the items that are not cancelled, see
+`Itinerary::summary`.
+ */
+
+use_required!();
+use crate::error::{Error, ErrorKind};
+use crate::itinerary::location::{CountryCode, Location};
+use crate::itinerary::{DateTime, Instant};
+use serde::{Deserialize, Serialize};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TripPurpose {
+    Business,
+    Personal,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Metadata {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    purpose: Option<TripPurpose>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cost_center: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    owner: Option<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    destinations: Vec<Location>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    created: Option<Instant>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    modified: Option<Instant>,
+}
+
+///
+/// Values derived from the items of an itinerary.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct Summary {
+    start: Option<DateTime>,
+    end: Option<DateTime>,
+    countries: Vec<CountryCode>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Display for TripPurpose {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                TripPurpose::Business => "business",
+                TripPurpose::Personal => "personal",
+            }
+        )
+    }
+}
+
+impl FromStr for TripPurpose {
+    type Err = Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "business" => Ok(Self::Business),
+            "personal" => Ok(Self::Personal),
+            _ => Err(ErrorKind::InvalidValue(
+                "trip purpose".to_string(),
+                format!("expecting business or personal, not '{}'", s),
+            )
+            .into()),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Metadata {
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    pub fn purpose(&self) -> Option<TripPurpose> {
+        self.purpose
+    }
+
+    pub fn set_purpose(&mut self, purpose: TripPurpose) {
+        self.purpose = Some(purpose);
+    }
+
+    pub fn cost_center(&self) -> Option<&String> {
+        self.cost_center.as_ref()
+    }
+
+    pub fn set_cost_center(&mut self, cost_center: &str) {
+        self.cost_center = Some(cost_center.to_string());
+    }
+
+    ///
+    /// The person responsible for the trip; they need not be one of its travelers.
+    ///
+    pub fn owner(&self) -> Option<&String> {
+        self.owner.as_ref()
+    }
+
+    pub fn set_owner(&mut self, owner: &str) {
+        self.owner = Some(owner.to_string());
+    }
+
+    pub fn destinations(&self) -> impl Iterator<Item = &Location> {
+        self.destinations.iter()
+    }
+
+    pub fn add_destination(&mut self, destination: Location) {
+        self.destinations.push(destination);
+    }
+
+    pub fn created(&self) -> Option<&Instant> {
+        self.created.as_ref()
+    }
+
+    pub fn modified(&self) -> Option<&Instant> {
+        self.modified.as_ref()
+    }
+
+    ///
+    /// Record that the itinerary was modified at `at`; if no creation time was recorded, it is
+    /// taken to be created then too.
+    ///
+    pub fn touch(&mut self, at: Instant) {
+        if self.created.is_none() {
+            self.created = Some(at);
+        }
+        self.modified = Some(at);
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Summary {
+    pub(crate) fn new(
+        start: Option<DateTime>,
+        end: Option<DateTime>,
+        countries: Vec<CountryCode>,
+    ) -> Self {
+        Self {
+            start,
+            end,
+            countries,
+        }
+    }
+
+    ///
+    /// When the first item starts, in its local time.
+    ///
+    pub fn start(&self) -> Option<&DateTime> {
+        self.start.as_ref()
+    }
+
+    ///
+    /// When the last item ends, in its local time.
+    ///
+    pub fn end(&self) -> Option<&DateTime> {
+        self.end.as_ref()
+    }
+
+    ///
+    /// The number of nights between the start and end dates.
+    ///
+    pub fn nights(&self) -> Option<i64> {
+        match (&self.start, &self.end) {
+            (Some(start), Some(end)) => Some((end.date() - start.date()).num_days()),
+            _ => None,
+        }
+    }
+
+    ///
+    /// The countries of every location, and destination, that records one; in the order they
+    /// are first visited.
+    ///
+    pub fn countries(&self) -> impl Iterator<Item = &CountryCode> {
+        self.countries.iter()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::TripPurpose;
+    use crate::itinerary::io::{from_str, to_string};
+    use crate::itinerary::Date;
+    use chrono::TimeZone;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_metadata_and_summary() {
+        let mut itinerary = from_str(
+            r##"---
+identifier: 2022-06
+name: Playa del Carmen
+purpose: personal
+owner: Simon
+destinations:
+  - name: Playa del Carmen
+    country_code: MX
+travelers:
+  - Simon
+items:
+  - flight:
+      number: 594
+      record:
+        provider: DL
+        start_date_time: "2022-06-24T07:15:00"
+        start_location: SEA
+        end_date_time: "2022-06-24T15:03:00"
+        end_location: CUN
+  - accomodation:
+      record:
+        provider: Hotel Xcaret
+        start_date_time: "2022-06-24T16:00:00"
+        start_location:
+          name: Hotel Xcaret
+          country_code: MX
+        end_date_time: "2022-07-01T11:00:00"
+  - event:
+      status: cancelled
+      record:
+        provider: Day trip
+        start_date_time: "2022-07-02T09:00:00"
+        start_location:
+          name: Belize City
+          country_code: BZ
+        end_date_time: "2022-07-02T17:00:00"
+"##,
+        )
+        .unwrap();
+        let metadata = itinerary.metadata();
+        assert_eq!(metadata.purpose(), Some(TripPurpose::Personal));
+        assert_eq!(metadata.owner(), Some(&"Simon".to_string()));
+        assert!(metadata.cost_center().is_none());
+        assert!(metadata.created().is_none());
+
+        let summary = itinerary.summary();
+        assert_eq!(
+            summary.start(),
+            Some(
+                &Date::from_ymd_opt(2022, 6, 24)
+                    .unwrap()
+                    .and_hms_opt(7, 15, 0)
+                    .unwrap()
+            )
+        );
+        assert_eq!(
+            summary.end(),
+            Some(
+                &Date::from_ymd_opt(2022, 7, 1)
+                    .unwrap()
+                    .and_hms_opt(11, 0, 0)
+                    .unwrap()
+            )
+        );
+        assert_eq!(summary.nights(), Some(7));
+        let countries: Vec<String> = summary.countries().map(|v| v.to_string()).collect();
+        assert_eq!(countries, vec!["US", "MX"]);
+
+        // the earliest item in time is not the earliest in local time
+        let zoned = from_str(
+            r##"---
+identifier: 2022-06
+name: Playa del Carmen
+travelers: []
+items:
+  - flight:
+      number: 594
+      record:
+        provider: DL
+        start_date_time: "2022-06-24T07:15:00"
+        start_location: SEA
+        end_date_time: "2022-06-24T15:03:00"
+        end_location: CUN
+  - event:
+      record:
+        provider: Breakfast
+        start_date_time: "2022-06-24T09:00:00"
+        start_time_zone: America/Cancun
+        start_location: Playa del Carmen
+        end_date_time: "2022-06-24T10:00:00"
+"##,
+        )
+        .unwrap();
+        let summary = zoned.summary();
+        assert_eq!(
+            summary.start(),
+            Some(
+                &Date::from_ymd_opt(2022, 6, 24)
+                    .unwrap()
+                    .and_hms_opt(9, 0, 0)
+                    .unwrap()
+            )
+        );
+
+        let created = chrono::Utc.with_ymd_and_hms(2022, 5, 1, 12, 0, 0).unwrap();
+        itinerary.metadata_mut().touch(created);
+        itinerary
+            .metadata_mut()
+            .touch(chrono::Utc.with_ymd_and_hms(2022, 5, 2, 12, 0, 0).unwrap());
+        assert_eq!(itinerary.metadata().created(), Some(&created));
+        assert_eq!(
+            from_str(&to_string(&itinerary).unwrap()).unwrap(),
+            itinerary
+        );
+    }
+}
@@ -8,7 +8,22 @@ More detailed description, with
 */
 
 use_required!();
+use crate::error::{Error, ErrorKind};
+use crate::itinerary::annotation::Annotations;
+use crate::itinerary::cost::{Cost, CostRollup};
+use crate::itinerary::filter::ItemFilter;
+use crate::itinerary::flight_details::{AirportGate, BookingClass, Cabin, Passenger};
+use crate::itinerary::location::{CountryCode, Location};
+use crate::itinerary::metadata::{Metadata, Summary};
+use crate::itinerary::status::{ItemStatus, StatusChange};
+use crate::itinerary::traveler::Traveler;
+use crate::itinerary::validation::Diagnostic;
+use crate::itinerary::visitor::ordering_instants;
+use chrono::TimeZone as _;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::convert::TryFrom;
+use std::ops::DerefMut;
 
 // ------------------------------------------------------------------------------------------------
 // Public Types
@@ -22,6 +37,21 @@ pub type DateTime = chrono::NaiveDateTime;
 
 pub type Duration = chrono::Duration;
 
+pub type ZonedDateTime = chrono::DateTime<chrono::FixedOffset>;
+
+pub type Instant = chrono::DateTime<chrono::Utc>;
+
+///
+/// The time zone of one endpoint of a travel record; either a fixed UTC offset or an IANA named
+/// zone. This is serialized as a string, for example `"-07:00"` or `"America/Los_Angeles"`.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub enum TimeZone {
+    Offset(chrono::FixedOffset),
+    Named(chrono_tz::Tz),
+}
+
 is_valid_newstring!(
     AirCarrierCode,
     is_valid_air_carrier_code,
@@ -31,18 +61,31 @@ is_valid_newstring!(
 
 is_valid_newstring!(AirportCode, is_valid_airport_code, Deserialize, Serialize);
 
+is_valid_newstring!(ItemId, is_valid_item_id, Hash, Deserialize, Serialize);
+
 pub type FlightNumber = u16;
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Itinerary {
     identifier: String,
     name: String,
-    travelers: Vec<String>,
+    #[serde(flatten)]
+    metadata: Metadata,
+    #[serde(flatten)]
+    annotations: Annotations,
+    travelers: Vec<Traveler>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    bookings: Vec<Booking>,
     items: Vec<Item>,
 }
 
+///
+/// A booking groups together the items reserved with an agency under a single confirmation, for
+/// example flights and a shuttle purchased together as a package.
+///
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
-struct Booking {
+pub struct Booking {
     agency: String,
     confirmation: String,
     items: Vec<Item>,
@@ -52,18 +95,167 @@ struct Booking {
 #[serde(rename_all = "snake_case")]
 pub enum Item {
     Flight(Flight),
+    Journey(Journey),
+    Rail(Rail),
+    Cruise(Cruise),
     Transport(Transport),
     Vehicle(Vehicle),
     Accomodation(Accomodation),
     Event(Event),
 }
 
+///
+/// The kind of an item, without its details.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ItemKind {
+    Flight,
+    Journey,
+    Rail,
+    Cruise,
+    Transport,
+    Vehicle,
+    Accomodation,
+    Event,
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Flight {
+    #[serde(flatten)]
+    common: ItemCommon,
+    #[serde(flatten)]
+    segment: FlightSegment,
+}
+
+///
+/// A single flight, either the whole of a `Flight` item or one segment of a `Journey`. A segment
+/// has none of the values common to items; it is covered, costed, and cancelled along with the
+/// journey it is part of.
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FlightSegment {
     number: FlightNumber,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    operated_by: Option<OperatingFlight>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cabin: Option<Cabin>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    booking_class: Option<BookingClass>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    passengers: Vec<Passenger>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    departure_gate: Option<AirportGate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    arrival_gate: Option<AirportGate>,
     record: TravelRecord<AirCarrierCode, AirportCode>,
 }
 
+///
+/// Where a flight is sold by one carrier but flown by another (a codeshare), the flight that
+/// actually operates it.
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct OperatingFlight {
+    carrier: AirCarrierCode,
+    number: FlightNumber,
+}
+
+///
+/// A journey is a set of flights, in order, purchased on a single ticket and so sharing one record
+/// locator; for example SEA to CUN via ATL. Each segment is a `FlightSegment`, and the time
+/// between one segment arriving and the next departing is a connection.
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Journey {
+    #[serde(flatten)]
+    common: ItemCommon,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    record_locator: Option<String>,
+    #[serde(deserialize_with = "deserialize_segments")]
+    segments: Vec<FlightSegment>,
+}
+
+///
+/// A connection between two consecutive segments of a journey.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct Connection<'a> {
+    arriving: &'a FlightSegment,
+    departing: &'a FlightSegment,
+}
+
+///
+/// A rail ticket covering one or more trains, in order; for example Paris to Munich changing at
+/// Stuttgart. Each leg is a single train, and the time between one leg arriving and the next
+/// departing is a change.
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Rail {
+    #[serde(flatten)]
+    common: ItemCommon,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ticket_reference: Option<String>,
+    #[serde(deserialize_with = "deserialize_legs")]
+    legs: Vec<RailLeg>,
+}
+
+///
+/// A single train within a rail ticket; the record's provider is the operator, and its locations
+/// the departure and arrival stations.
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RailLeg {
+    train_number: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    class: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    coach: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seat: Option<String>,
+    record: TravelRecord<String, Location>,
+}
+
+///
+/// A change between two consecutive legs of a rail ticket.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct RailChange<'a> {
+    arriving: &'a RailLeg,
+    departing: &'a RailLeg,
+}
+
+///
+/// A cruise; the record's provider is the cruise line and its locations the ports of embarkation
+/// and disembarkation. Between these the ship calls at each port in order.
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Cruise {
+    #[serde(flatten)]
+    common: ItemCommon,
+    ship: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cabin: Option<String>,
+    record: TravelRecord<String, Location>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    port_calls: Vec<PortCall>,
+}
+
+///
+/// A stop made by a cruise ship between embarkation and disembarkation.
+///
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PortCall {
+    port: Location,
+    arrival_date_time: DateTime,
+    departure_date_time: DateTime,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    time_zone: Option<TimeZone>,
+    #[serde(skip)]
+    time_zone_inferred: bool,
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum TransportKind {
@@ -75,162 +267,1625 @@ pub enum TransportKind {
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Transport {
-    record: TravelRecord<String, String>,
+    #[serde(flatten)]
+    common: ItemCommon,
+    record: TravelRecord<String, Location>,
     #[serde(skip_serializing_if = "Option::is_none")]
     kind: Option<TransportKind>,
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
-pub struct Vehicle {
-    record: TravelRecord<String, String>,
-}
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Vehicle {
+    #[serde(flatten)]
+    common: ItemCommon,
+    record: TravelRecord<String, Location>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Accomodation {
+    #[serde(flatten)]
+    common: ItemCommon,
+    record: TravelRecord<String, Location>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Event {
+    #[serde(flatten)]
+    common: ItemCommon,
+    record: TravelRecord<String, Location>,
+}
+
+///
+/// A reference from one item to another, describing how the two are related.
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemLink {
+    /// This item waits for the end of the target, e.g. a shuttle meeting an arriving flight.
+    Meets(ItemId),
+    /// This item ends at the start of the target, e.g. a shuttle to a departing flight.
+    Delivers(ItemId),
+    /// Any other relationship.
+    Related(ItemId),
+}
+
+///
+/// Values held by every kind of item, regardless of the travel record it contains.
+///
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ItemCommon {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<ItemId>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    links: Vec<ItemLink>,
+    /// The names of the travelers this item covers; if empty it covers all of them.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    travelers: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cost: Option<Cost>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "ItemStatus::is_confirmed")]
+    status: ItemStatus,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    status_history: Vec<StatusChange>,
+    #[serde(flatten)]
+    annotations: Annotations,
+}
+
+///
+/// The start and end of an item. Time zones that were not given, but inferred from the
+/// locations, are marked so that they are not saved; the marks are not compared for equality.
+///
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct TravelRecord<P, L> {
+    provider: P,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    confirmation: Option<String>,
+    start_date_time: DateTime,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start_time_zone: Option<TimeZone>,
+    start_location: L,
+    end_date_time: DateTime,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end_time_zone: Option<TimeZone>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end_location: Option<L>,
+    #[serde(skip)]
+    start_time_zone_inferred: bool,
+    #[serde(skip)]
+    end_time_zone_inferred: bool,
+}
+
+///
+/// Implemented by location types that may be able to determine their own time zone.
+///
+pub(crate) trait TimeZoneLocation {
+    fn time_zone(&self) -> Option<TimeZone>;
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+impl Itinerary {
+    pub fn identifier(&self) -> &String {
+        &self.identifier
+    }
+
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    pub fn metadata_mut(&mut self) -> &mut Metadata {
+        &mut self.metadata
+    }
+
+    pub fn annotations(&self) -> &Annotations {
+        &self.annotations
+    }
+
+    pub fn annotations_mut(&mut self) -> &mut Annotations {
+        &mut self.annotations
+    }
+
+    pub fn travelers(&self) -> impl Iterator<Item = &Traveler> {
+        self.travelers.iter()
+    }
+
+    pub fn traveler(&self, name: &str) -> Option<&Traveler> {
+        self.travelers
+            .iter()
+            .find(|traveler| traveler.name() == name)
+    }
+
+    ///
+    /// The items that cover the named traveler.
+    ///
+    pub fn items_for_traveler<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Item> {
+        self.items()
+            .filter(move |item| item.common().is_for_traveler(name))
+    }
+
+    ///
+    /// Returns a copy of this itinerary as seen by a single traveler; it contains only that
+    /// traveler, and only the items and bookings that cover them. Returns `None` if there is no
+    /// traveler with this name.
+    ///
+    pub fn for_traveler(&self, name: &str) -> Option<Itinerary> {
+        let traveler = self.traveler(name)?;
+        let filter_items = |items: &Vec<Item>| -> Vec<Item> {
+            items
+                .iter()
+                .filter(|item| item.common().is_for_traveler(name))
+                .map(|item| {
+                    let mut item = item.clone();
+                    item.retain_passenger(name);
+                    item
+                })
+                .collect()
+        };
+        Some(Itinerary {
+            identifier: self.identifier.clone(),
+            name: self.name.clone(),
+            metadata: self.metadata.clone(),
+            annotations: self.annotations.clone(),
+            travelers: vec![traveler.clone()],
+            bookings: self
+                .bookings
+                .iter()
+                .map(|booking| Booking {
+                    items: filter_items(&booking.items),
+                    ..booking.clone()
+                })
+                .filter(|booking| !booking.items.is_empty())
+                .collect(),
+            items: filter_items(&self.items),
+        })
+    }
+
+    ///
+    /// Check the content of this itinerary, returning any problems found; an empty list means it
+    /// is valid. See the `validation` module for the rules applied.
+    ///
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        validation::validate(self)
+    }
+
+    ///
+    /// The overall start and end, and the countries visited, worked out from the items that are
+    /// not cancelled. The start and end are the local times of the items that start first, and
+    /// end last, in time; flights contribute the countries of those airports in the table used to
+    /// find their time zones.
+    ///
+    pub fn summary(&self) -> Summary {
+        self.summary_of(|item| !item.common().is_cancelled())
+    }
+
+    ///
+    /// The summary, as for `summary`, of only those items for which `is_included` returns `true`.
+    ///
+    pub(crate) fn summary_of<F>(&self, is_included: F) -> Summary
+    where
+        F: Fn(&&Item) -> bool,
+    {
+        let items = || self.items().filter(&is_included);
+        let mut countries: Vec<CountryCode> = Vec::new();
+        for country_code in items().flat_map(item_country_codes).chain(
+            self.metadata
+                .destinations()
+                .filter_map(|location| location.country_code().cloned()),
+        ) {
+            if !countries.contains(&country_code) {
+                countries.push(country_code);
+            }
+        }
+        // starts and ends are ordered together, so that unzoned times take the zone of their
+        // neighbours.
+        let times: Vec<(Option<Instant>, &DateTime)> = items()
+            .map(|item| (item.start_instant(), item.start_date_time()))
+            .chain(items().map(|item| (item.end_instant(), item.end_date_time())))
+            .collect();
+        let instants = ordering_instants(&times);
+        let (starts, ends) = instants.split_at(times.len() / 2);
+        Summary::new(
+            (0..starts.len())
+                .min_by_key(|index| starts[*index])
+                .map(|index| *times[index].1),
+            (0..ends.len())
+                .max_by_key(|index| ends[*index])
+                .map(|index| *times[starts.len() + index].1),
+            countries,
+        )
+    }
+
+    ///
+    /// The costs of all items, summed by currency and by traveler.
+    ///
+    pub fn cost_rollup(&self) -> CostRollup {
+        CostRollup::new(self)
+    }
+
+    ///
+    /// The costs, as for `cost_rollup`, of only those items selected by `filter`.
+    ///
+    pub fn cost_rollup_of(&self, filter: &ItemFilter) -> CostRollup {
+        CostRollup::new_of(self, |item| filter.matches(item))
+    }
+
+    pub fn bookings(&self) -> impl Iterator<Item = &Booking> {
+        self.bookings.iter()
+    }
+
+    ///
+    /// All items in the itinerary; those that are part of a booking, in booking order, followed
+    /// by those that are not.
+    ///
+    pub fn items(&self) -> impl Iterator<Item = &Item> {
+        self.bookings
+            .iter()
+            .flat_map(|booking| booking.items.iter())
+            .chain(self.items.iter())
+    }
+
+    ///
+    /// Returns the item with the identifier `id`, if present.
+    ///
+    pub fn item(&self, id: &ItemId) -> Option<&Item> {
+        self.items().find(|item| item.id() == Some(id))
+    }
+
+    pub fn contains_item(&self, id: &ItemId) -> bool {
+        self.item(id).is_some()
+    }
+
+    ///
+    /// Change the status of the item with the identifier `id`, recording the change in the item's
+    /// history. It is an error if there is no such item, or if its current status may not be
+    /// changed to `status`.
+    ///
+    pub fn set_item_status(
+        &mut self,
+        id: &ItemId,
+        status: ItemStatus,
+        at: Instant,
+        note: Option<&str>,
+    ) -> crate::error::Result<()> {
+        match self.items_mut().find(|item| item.id() == Some(id)) {
+            Some(item) => item.common_mut().set_status(status, at, note),
+            None => Err(ErrorKind::UnknownItem(id.to_string()).into()),
+        }
+    }
+
+    ///
+    /// Returns the items that `id` links to, along with the link itself. Links to items that are
+    /// not in this itinerary are ignored.
+    ///
+    pub fn linked_items(&self, id: &ItemId) -> Vec<(&ItemLink, &Item)> {
+        match self.item(id) {
+            None => Default::default(),
+            Some(item) => item
+                .links()
+                .filter_map(|link| self.item(link.target()).map(|target| (link, target)))
+                .collect(),
+        }
+    }
+
+    ///
+    /// Returns the items that link to `id`, along with the link itself.
+    ///
+    pub fn items_linking_to(&self, id: &ItemId) -> Vec<(&ItemLink, &Item)> {
+        self.items()
+            .flat_map(|item| {
+                item.links()
+                    .filter(|link| link.target() == id)
+                    .map(move |link| (link, item))
+            })
+            .collect()
+    }
+
+    ///
+    /// Only those items that are not part of any booking.
+    ///
+    pub fn unbooked_items(&self) -> impl Iterator<Item = &Item> {
+        self.items.iter()
+    }
+
+    ///
+    /// Give every item that does not already have one a unique identifier. Identifiers are derived
+    /// from the kind, provider and start date of the item so that they are readable, for example
+    /// `flight-dl594-20220624`; where this is not unique a numeric suffix is added.
+    ///
+    pub fn assign_item_ids(&mut self) {
+        let mut existing: HashSet<ItemId> =
+            self.items().filter_map(|item| item.id()).cloned().collect();
+        for item in self.items_mut() {
+            if item.id().is_none() {
+                let base = item.default_id_base();
+                let mut id = ItemId::new_unchecked(&base);
+                let mut suffix = 1;
+                while existing.contains(&id) {
+                    suffix += 1;
+                    id = ItemId::new_unchecked(&format!("{}-{}", base, suffix));
+                }
+                let _ = existing.insert(id.clone());
+                item.common_mut().id = Some(id);
+            }
+        }
+    }
+
+    ///
+    /// Fill in the time zone of any item endpoint that does not have one, where it can be
+    /// determined from the endpoint's location.
+    ///
+    pub fn infer_time_zones(&mut self) {
+        for item in self.items_mut() {
+            match item {
+                Item::Flight(v) => v.record.infer_time_zones(false),
+                Item::Journey(v) => v
+                    .segments
+                    .iter_mut()
+                    .for_each(|segment| segment.record.infer_time_zones(false)),
+                Item::Rail(v) => v
+                    .legs
+                    .iter_mut()
+                    .for_each(|leg| leg.record.infer_time_zones(false)),
+                Item::Cruise(v) => {
+                    v.record.infer_time_zones(false);
+                    v.port_calls.iter_mut().for_each(|call| {
+                        if call.time_zone.is_none() {
+                            call.time_zone = call.port.time_zone();
+                            call.time_zone_inferred = call.time_zone.is_some();
+                        }
+                    });
+                }
+                Item::Transport(v) => v.record.infer_time_zones(true),
+                Item::Vehicle(v) => v.record.infer_time_zones(true),
+                Item::Accomodation(v) => v.record.infer_time_zones(true),
+                Item::Event(v) => v.record.infer_time_zones(true),
+            }
+        }
+    }
+
+    ///
+    /// Remove the time zones filled in by `infer_time_zones`, leaving only those given
+    /// explicitly; this is the form in which an itinerary is saved.
+    ///
+    pub(crate) fn clear_inferred_time_zones(&mut self) {
+        for item in self.items_mut() {
+            match item {
+                Item::Flight(v) => v.record.clear_inferred_time_zones(),
+                Item::Journey(v) => v
+                    .segments
+                    .iter_mut()
+                    .for_each(|segment| segment.record.clear_inferred_time_zones()),
+                Item::Rail(v) => v
+                    .legs
+                    .iter_mut()
+                    .for_each(|leg| leg.record.clear_inferred_time_zones()),
+                Item::Cruise(v) => {
+                    v.record.clear_inferred_time_zones();
+                    v.port_calls.iter_mut().for_each(|call| {
+                        if call.time_zone_inferred {
+                            call.time_zone = None;
+                            call.time_zone_inferred = false;
+                        }
+                    });
+                }
+                Item::Transport(v) => v.record.clear_inferred_time_zones(),
+                Item::Vehicle(v) => v.record.clear_inferred_time_zones(),
+                Item::Accomodation(v) => v.record.clear_inferred_time_zones(),
+                Item::Event(v) => v.record.clear_inferred_time_zones(),
+            }
+        }
+    }
+
+    ///
+    /// All flights in the itinerary, including the segments of journeys; for example to update
+    /// them from schedule data.
+    ///
+    pub fn flights_mut(&mut self) -> impl Iterator<Item = &mut FlightSegment> {
+        self.items_mut().flat_map(|item| match item {
+            Item::Flight(v) => std::slice::from_mut(&mut v.segment).iter_mut(),
+            Item::Journey(v) => v.segments.iter_mut(),
+            _ => [].iter_mut(),
+        })
+    }
+
+    fn items_mut(&mut self) -> impl Iterator<Item = &mut Item> {
+        self.bookings
+            .iter_mut()
+            .flat_map(|booking| booking.items.iter_mut())
+            .chain(self.items.iter_mut())
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Booking {
+    pub fn agency(&self) -> &String {
+        &self.agency
+    }
+
+    pub fn confirmation(&self) -> &String {
+        &self.confirmation
+    }
+
+    pub fn items(&self) -> impl Iterator<Item = &Item> {
+        self.items.iter()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl ItemId {
+    fn new_unchecked(s: &str) -> Self {
+        Self(s.to_string())
+    }
+}
+
+impl ItemCommon {
+    pub fn id(&self) -> Option<&ItemId> {
+        self.id.as_ref()
+    }
+
+    pub fn links(&self) -> impl Iterator<Item = &ItemLink> {
+        self.links.iter()
+    }
+
+    pub fn travelers(&self) -> impl Iterator<Item = &String> {
+        self.travelers.iter()
+    }
+
+    ///
+    /// Returns `true` if this item covers the named traveler, either explicitly or because it
+    /// does not name any travelers at all.
+    ///
+    pub fn is_for_traveler(&self, name: &str) -> bool {
+        self.travelers.is_empty() || self.travelers.iter().any(|traveler| traveler == name)
+    }
+
+    pub fn cost(&self) -> Option<&Cost> {
+        self.cost.as_ref()
+    }
+
+    pub fn status(&self) -> ItemStatus {
+        self.status
+    }
+
+    pub fn annotations(&self) -> &Annotations {
+        &self.annotations
+    }
+
+    pub fn is_tentative(&self) -> bool {
+        self.status.is_tentative()
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.status.is_cancelled()
+    }
+
+    ///
+    /// The changes made to this item's status, oldest first.
+    ///
+    pub fn status_history(&self) -> impl Iterator<Item = &StatusChange> {
+        self.status_history.iter()
+    }
+
+    pub(crate) fn set_status(
+        &mut self,
+        status: ItemStatus,
+        at: Instant,
+        note: Option<&str>,
+    ) -> crate::error::Result<()> {
+        if self.status.can_transition_to(status) {
+            self.status_history
+                .push(StatusChange::new(self.status, status, at, note));
+            self.status = status;
+            Ok(())
+        } else {
+            Err(
+                ErrorKind::InvalidStatusTransition(self.status.to_string(), status.to_string())
+                    .into(),
+            )
+        }
+    }
+}
+
+impl ItemLink {
+    pub fn target(&self) -> &ItemId {
+        match self {
+            ItemLink::Meets(v) => v,
+            ItemLink::Delivers(v) => v,
+            ItemLink::Related(v) => v,
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for TimeZone {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TimeZone::Offset(v) => write!(f, "{}", v),
+            TimeZone::Named(v) => write!(f, "{}", v.name()),
+        }
+    }
+}
+
+impl FromStr for TimeZone {
+    type Err = Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        if s.starts_with('+') || s.starts_with('-') {
+            parse_offset(s)
+                .map(TimeZone::Offset)
+                .ok_or_else(|| ErrorKind::InvalidTimeZone(s.to_string()).into())
+        } else {
+            chrono_tz::Tz::from_str(s)
+                .map(TimeZone::Named)
+                .map_err(|_| ErrorKind::InvalidTimeZone(s.to_string()).into())
+        }
+    }
+}
+
+impl From<TimeZone> for String {
+    fn from(v: TimeZone) -> Self {
+        v.to_string()
+    }
+}
+
+impl TryFrom<String> for TimeZone {
+    type Error = Error;
+
+    fn try_from(v: String) -> std::result::Result<Self, Self::Error> {
+        Self::from_str(&v)
+    }
+}
+
+impl TimeZone {
+    ///
+    /// Interpret the wall-clock time `local` in this zone. This returns `None` if the local time
+    /// does not exist in this zone (it falls in a daylight-saving gap); if the local time is
+    /// ambiguous the earlier of the two possible instants is returned.
+    ///
+    pub fn localize(&self, local: &DateTime) -> Option<ZonedDateTime> {
+        match self {
+            TimeZone::Offset(tz) => tz.from_local_datetime(local).earliest(),
+            TimeZone::Named(tz) => tz
+                .from_local_datetime(local)
+                .earliest()
+                .map(|v| v.fixed_offset()),
+        }
+    }
+
+    pub fn for_airport(code: &AirportCode) -> Option<Self> {
+        time_zones::for_airport(code)
+    }
+
+    pub fn for_address(address: &str) -> Option<Self> {
+        time_zones::for_address(address)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for ItemKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                ItemKind::Flight => "flight",
+                ItemKind::Journey => "journey",
+                ItemKind::Rail => "rail",
+                ItemKind::Cruise => "cruise",
+                ItemKind::Transport => "transport",
+                ItemKind::Vehicle => "vehicle",
+                ItemKind::Accomodation => "accomodation",
+                ItemKind::Event => "event",
+            }
+        )
+    }
+}
+
+impl FromStr for ItemKind {
+    type Err = Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "flight" => Ok(Self::Flight),
+            "journey" => Ok(Self::Journey),
+            "rail" => Ok(Self::Rail),
+            "cruise" => Ok(Self::Cruise),
+            "transport" => Ok(Self::Transport),
+            "vehicle" => Ok(Self::Vehicle),
+            "accomodation" => Ok(Self::Accomodation),
+            "event" => Ok(Self::Event),
+            _ => Err(ErrorKind::InvalidValue("item kind".to_string(), s.to_string()).into()),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Item {
+    pub fn id(&self) -> Option<&ItemId> {
+        self.common().id.as_ref()
+    }
+
+    pub fn kind(&self) -> ItemKind {
+        match self {
+            Item::Flight(_) => ItemKind::Flight,
+            Item::Journey(_) => ItemKind::Journey,
+            Item::Rail(_) => ItemKind::Rail,
+            Item::Cruise(_) => ItemKind::Cruise,
+            Item::Transport(_) => ItemKind::Transport,
+            Item::Vehicle(_) => ItemKind::Vehicle,
+            Item::Accomodation(_) => ItemKind::Accomodation,
+            Item::Event(_) => ItemKind::Event,
+        }
+    }
+
+    pub fn links(&self) -> impl Iterator<Item = &ItemLink> {
+        self.common().links.iter()
+    }
+
+    pub fn is_flight(&self) -> bool {
+        matches!(&self, Item::Flight(_))
+    }
+
+    pub fn as_flight(&self) -> Option<&Flight> {
+        match self {
+            Item::Flight(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn is_journey(&self) -> bool {
+        matches!(&self, Item::Journey(_))
+    }
+
+    pub fn as_journey(&self) -> Option<&Journey> {
+        match self {
+            Item::Journey(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn is_rail(&self) -> bool {
+        matches!(&self, Item::Rail(_))
+    }
+
+    pub fn as_rail(&self) -> Option<&Rail> {
+        match self {
+            Item::Rail(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn is_cruise(&self) -> bool {
+        matches!(&self, Item::Cruise(_))
+    }
+
+    pub fn as_cruise(&self) -> Option<&Cruise> {
+        match self {
+            Item::Cruise(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn is_transport(&self) -> bool {
+        matches!(&self, Item::Transport(_))
+    }
+
+    pub fn as_transport(&self) -> Option<&Transport> {
+        match self {
+            Item::Transport(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn is_accomodation(&self) -> bool {
+        matches!(&self, Item::Accomodation(_))
+    }
+
+    pub fn as_accomodation(&self) -> Option<&Accomodation> {
+        match self {
+            Item::Accomodation(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn is_vehicle(&self) -> bool {
+        matches!(&self, Item::Vehicle(_))
+    }
+
+    pub fn as_vehicle(&self) -> Option<&Vehicle> {
+        match self {
+            Item::Vehicle(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn is_event(&self) -> bool {
+        matches!(&self, Item::Event(_))
+    }
+
+    pub fn as_event(&self) -> Option<&Event> {
+        match self {
+            Item::Event(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn common(&self) -> &ItemCommon {
+        match self {
+            Item::Flight(v) => &v.common,
+            Item::Journey(v) => &v.common,
+            Item::Rail(v) => &v.common,
+            Item::Cruise(v) => &v.common,
+            Item::Transport(v) => &v.common,
+            Item::Vehicle(v) => &v.common,
+            Item::Accomodation(v) => &v.common,
+            Item::Event(v) => &v.common,
+        }
+    }
+
+    pub(crate) fn common_mut(&mut self) -> &mut ItemCommon {
+        match self {
+            Item::Flight(v) => &mut v.common,
+            Item::Journey(v) => &mut v.common,
+            Item::Rail(v) => &mut v.common,
+            Item::Cruise(v) => &mut v.common,
+            Item::Transport(v) => &mut v.common,
+            Item::Vehicle(v) => &mut v.common,
+            Item::Accomodation(v) => &mut v.common,
+            Item::Event(v) => &mut v.common,
+        }
+    }
+
+    ///
+    /// Remove the flight booking details of all travelers other than `name`.
+    ///
+    fn retain_passenger(&mut self, name: &str) {
+        match self {
+            Item::Flight(v) => v.passengers.retain(|v| v.traveler() == name),
+            Item::Journey(v) => v
+                .segments
+                .iter_mut()
+                .for_each(|v| v.passengers.retain(|v| v.traveler() == name)),
+            _ => {}
+        }
+    }
+
+    fn default_id_base(&self) -> String {
+        let (kind, provider) = match self {
+            Item::Flight(v) => ("flight", v.flight_number_string()),
+            Item::Journey(v) => ("journey", v.first_segment().flight_number_string()),
+            Item::Rail(v) => ("rail", v.first_leg().train_string()),
+            Item::Cruise(v) => ("cruise", v.record.provider.clone()),
+            Item::Transport(v) => ("transport", v.record.provider.clone()),
+            Item::Vehicle(v) => ("vehicle", v.record.provider.clone()),
+            Item::Accomodation(v) => ("accomodation", v.record.provider.clone()),
+            Item::Event(v) => ("event", v.record.provider.clone()),
+        };
+        let provider: String = provider
+            .to_lowercase()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+            .collect();
+        format!(
+            "{}-{}-{}",
+            kind,
+            provider,
+            self.start_date_time().format("%Y%m%d")
+        )
+    }
+
+    ///
+    /// Every place this item goes, in order; stations, ports, addresses and so on. Flights and
+    /// journeys only record airport codes, and so have no locations.
+    ///
+    pub fn locations(&self) -> Vec<&Location> {
+        match self {
+            Item::Flight(_) | Item::Journey(_) => Vec::new(),
+            Item::Rail(v) => v
+                .legs()
+                .flat_map(|leg| {
+                    std::iter::once(leg.departure_station()).chain(leg.arrival_station())
+                })
+                .collect(),
+            Item::Cruise(v) => std::iter::once(v.embarkation_port())
+                .chain(v.port_calls().map(|port_call| port_call.port()))
+                .chain(std::iter::once(v.disembarkation_port()))
+                .collect(),
+            Item::Transport(v) => std::iter::once(v.departure_address())
+                .chain(v.arrival_address())
+                .collect(),
+            Item::Vehicle(v) => vec![v.pickup_address(), v.dropoff_address()],
+            Item::Accomodation(v) => vec![v.address()],
+            Item::Event(v) => vec![v.address()],
+        }
+    }
+
+    ///
+    /// The local wall-clock time this item starts; the departure, pickup, check-in, etc.
+    ///
+    pub fn start_date_time(&self) -> &DateTime {
+        match self {
+            Item::Flight(v) => &v.record.start_date_time,
+            Item::Journey(v) => v.departure_date_time(),
+            Item::Rail(v) => v.departure_date_time(),
+            Item::Cruise(v) => &v.record.start_date_time,
+            Item::Transport(v) => &v.record.start_date_time,
+            Item::Vehicle(v) => &v.record.start_date_time,
+            Item::Accomodation(v) => &v.record.start_date_time,
+            Item::Event(v) => &v.record.start_date_time,
+        }
+    }
+
+    pub fn start_instant(&self) -> Option<Instant> {
+        match self {
+            Item::Flight(v) => v.record.start_instant(),
+            Item::Journey(v) => v.departure_instant(),
+            Item::Rail(v) => v.departure_instant(),
+            Item::Cruise(v) => v.record.start_instant(),
+            Item::Transport(v) => v.record.start_instant(),
+            Item::Vehicle(v) => v.record.start_instant(),
+            Item::Accomodation(v) => v.record.start_instant(),
+            Item::Event(v) => v.record.start_instant(),
+        }
+    }
+
+    ///
+    /// The local wall-clock time this item ends; the arrival, dropoff, check-out, etc.
+    ///
+    pub fn end_date_time(&self) -> &DateTime {
+        match self {
+            Item::Flight(v) => &v.record.end_date_time,
+            Item::Journey(v) => v.arrival_date_time(),
+            Item::Rail(v) => v.arrival_date_time(),
+            Item::Cruise(v) => &v.record.end_date_time,
+            Item::Transport(v) => &v.record.end_date_time,
+            Item::Vehicle(v) => &v.record.end_date_time,
+            Item::Accomodation(v) => &v.record.end_date_time,
+            Item::Event(v) => &v.record.end_date_time,
+        }
+    }
+
+    pub fn end_instant(&self) -> Option<Instant> {
+        match self {
+            Item::Flight(v) => v.record.end_instant(),
+            Item::Journey(v) => v.arrival_instant(),
+            Item::Rail(v) => v.arrival_instant(),
+            Item::Cruise(v) => v.record.end_instant(),
+            Item::Transport(v) => v.record.end_instant(),
+            Item::Vehicle(v) => v.record.end_instant(),
+            Item::Accomodation(v) => v.record.end_instant(),
+            Item::Event(v) => v.record.end_instant(),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl From<Flight> for Item {
+    fn from(v: Flight) -> Self {
+        Self::Flight(v)
+    }
+}
+
+impl Flight {
+    pub fn common(&self) -> &ItemCommon {
+        &self.common
+    }
+
+    pub fn id(&self) -> Option<&ItemId> {
+        self.common.id.as_ref()
+    }
+
+    pub fn links(&self) -> impl Iterator<Item = &ItemLink> {
+        self.common.links.iter()
+    }
+
+    pub fn segment(&self) -> &FlightSegment {
+        &self.segment
+    }
+}
+
+impl Deref for Flight {
+    type Target = FlightSegment;
+
+    fn deref(&self) -> &Self::Target {
+        &self.segment
+    }
+}
+
+impl DerefMut for Flight {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.segment
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl FlightSegment {
+    pub fn carrier(&self) -> &AirCarrierCode {
+        &self.record.provider
+    }
+
+    pub fn number(&self) -> &FlightNumber {
+        &self.number
+    }
+
+    pub fn flight_number_string(&self) -> String {
+        format!("{}{}", self.carrier(), self.number())
+    }
+
+    pub fn is_codeshare(&self) -> bool {
+        self.operated_by.is_some()
+    }
+
+    pub fn operated_by(&self) -> Option<&OperatingFlight> {
+        self.operated_by.as_ref()
+    }
+
+    pub fn record_locator(&self) -> Option<&String> {
+        self.record.confirmation.as_ref()
+    }
+
+    pub fn cabin(&self) -> Option<Cabin> {
+        self.cabin
+    }
+
+    pub fn set_cabin(&mut self, cabin: Cabin) {
+        self.cabin = Some(cabin);
+    }
+
+    ///
+    /// The booking class, or fare bucket, the flight was sold in; for example `Y` or `W`.
+    ///
+    pub fn booking_class(&self) -> Option<&BookingClass> {
+        self.booking_class.as_ref()
+    }
+
+    pub fn set_booking_class(&mut self, booking_class: BookingClass) {
+        self.booking_class = Some(booking_class);
+    }
+
+    pub fn passengers(&self) -> impl Iterator<Item = &Passenger> {
+        self.passengers.iter()
+    }
+
+    ///
+    /// The booking details for the named traveler, if any were recorded.
+    ///
+    pub fn passenger(&self, traveler: &str) -> Option<&Passenger> {
+        self.passengers.iter().find(|v| v.traveler() == traveler)
+    }
+
+    ///
+    /// Add booking details for a traveler, replacing any already recorded for them.
+    ///
+    pub fn set_passenger(&mut self, passenger: Passenger) {
+        self.passengers
+            .retain(|v| v.traveler() != passenger.traveler());
+        self.passengers.push(passenger);
+    }
+
+    pub fn departure_gate(&self) -> Option<&AirportGate> {
+        self.departure_gate.as_ref()
+    }
+
+    ///
+    /// Set the departure terminal and gate, keeping any existing value `gate` does not have.
+    ///
+    pub fn update_departure_gate(&mut self, gate: &AirportGate) {
+        update_gate(&mut self.departure_gate, gate);
+    }
+
+    pub fn arrival_gate(&self) -> Option<&AirportGate> {
+        self.arrival_gate.as_ref()
+    }
+
+    ///
+    /// Set the arrival terminal and gate, keeping any existing value `gate` does not have.
+    ///
+    pub fn update_arrival_gate(&mut self, gate: &AirportGate) {
+        update_gate(&mut self.arrival_gate, gate);
+    }
+
+    pub fn departure_date_time(&self) -> &DateTime {
+        &self.record.start_date_time
+    }
+
+    pub fn departure_time_zone(&self) -> Option<&TimeZone> {
+        self.record.start_time_zone()
+    }
+
+    pub fn departure_instant(&self) -> Option<Instant> {
+        self.record.start_instant()
+    }
+
+    pub fn departure_airport(&self) -> &AirportCode {
+        &self.record.start_location
+    }
+
+    pub fn arrival_date_time(&self) -> &DateTime {
+        &self.record.end_date_time
+    }
+
+    ///
+    /// The arrival airport; this is only missing from an invalid itinerary, see
+    /// `Itinerary::validate`.
+    ///
+    pub fn arrival_airport(&self) -> Option<&AirportCode> {
+        self.record.end_location.as_ref()
+    }
+
+    pub fn arrival_time_zone(&self) -> Option<&TimeZone> {
+        self.record.end_time_zone()
+    }
+
+    pub fn arrival_instant(&self) -> Option<Instant> {
+        self.record.end_instant()
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.record.duration()
+    }
+
+    pub(crate) fn inner(&self) -> &TravelRecord<AirCarrierCode, AirportCode> {
+        &self.record
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl OperatingFlight {
+    pub fn new(carrier: AirCarrierCode, number: FlightNumber) -> Self {
+        Self { carrier, number }
+    }
+
+    pub fn carrier(&self) -> &AirCarrierCode {
+        &self.carrier
+    }
+
+    pub fn number(&self) -> &FlightNumber {
+        &self.number
+    }
+
+    pub fn flight_number_string(&self) -> String {
+        format!("{}{}", self.carrier(), self.number())
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl From<Journey> for Item {
+    fn from(v: Journey) -> Self {
+        Self::Journey(v)
+    }
+}
+
+impl Journey {
+    pub fn common(&self) -> &ItemCommon {
+        &self.common
+    }
+
+    pub fn id(&self) -> Option<&ItemId> {
+        self.common.id.as_ref()
+    }
+
+    pub fn links(&self) -> impl Iterator<Item = &ItemLink> {
+        self.common.links.iter()
+    }
+
+    pub fn record_locator(&self) -> Option<&String> {
+        self.record_locator.as_ref()
+    }
+
+    pub fn segments(&self) -> impl Iterator<Item = &FlightSegment> {
+        self.segments.iter()
+    }
+
+    ///
+    /// The flight numbers of all segments, separated by `/`, for example `DL594/DL1203`.
+    ///
+    pub fn flight_numbers_string(&self) -> String {
+        self.segments
+            .iter()
+            .map(|segment| segment.flight_number_string())
+            .collect::<Vec<String>>()
+            .join("/")
+    }
+
+    pub fn departure_date_time(&self) -> &DateTime {
+        self.first_segment().departure_date_time()
+    }
+
+    pub fn departure_time_zone(&self) -> Option<&TimeZone> {
+        self.first_segment().departure_time_zone()
+    }
+
+    pub fn departure_instant(&self) -> Option<Instant> {
+        self.first_segment().departure_instant()
+    }
+
+    pub fn departure_airport(&self) -> &AirportCode {
+        self.first_segment().departure_airport()
+    }
+
+    pub fn arrival_date_time(&self) -> &DateTime {
+        self.last_segment().arrival_date_time()
+    }
+
+    pub fn arrival_time_zone(&self) -> Option<&TimeZone> {
+        self.last_segment().arrival_time_zone()
+    }
+
+    pub fn arrival_instant(&self) -> Option<Instant> {
+        self.last_segment().arrival_instant()
+    }
+
+    pub fn arrival_airport(&self) -> Option<&AirportCode> {
+        self.last_segment().arrival_airport()
+    }
+
+    ///
+    /// The elapsed time from the first departure to the final arrival, including all layovers.
+    ///
+    pub fn duration(&self) -> Duration {
+        match (self.departure_instant(), self.arrival_instant()) {
+            (Some(start), Some(end)) => end - start,
+            _ => *self.arrival_date_time() - *self.departure_date_time(),
+        }
+    }
+
+    pub fn connections(&self) -> Vec<Connection<'_>> {
+        self.segments
+            .windows(2)
+            .map(|pair| Connection {
+                arriving: &pair[0],
+                departing: &pair[1],
+            })
+            .collect()
+    }
+
+    pub fn connection_airports(&self) -> impl Iterator<Item = &AirportCode> {
+        self.segments
+            .iter()
+            .skip(1)
+            .map(|segment| segment.departure_airport())
+    }
+
+    pub(crate) fn first_segment(&self) -> &FlightSegment {
+        self.segments.first().unwrap()
+    }
+
+    pub(crate) fn last_segment(&self) -> &FlightSegment {
+        self.segments.last().unwrap()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl<'a> Connection<'a> {
+    pub fn airport(&self) -> &'a AirportCode {
+        self.departing.departure_airport()
+    }
+
+    pub fn arriving(&self) -> &'a FlightSegment {
+        self.arriving
+    }
+
+    pub fn departing(&self) -> &'a FlightSegment {
+        self.departing
+    }
+
+    ///
+    /// The time between arriving and departing; both are at the same airport so this is correct
+    /// even when no time zone is known.
+    ///
+    pub fn layover(&self) -> Duration {
+        match (
+            self.arriving.arrival_instant(),
+            self.departing.departure_instant(),
+        ) {
+            (Some(arrives), Some(departs)) => departs - arrives,
+            _ => *self.departing.departure_date_time() - *self.arriving.arrival_date_time(),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl From<Rail> for Item {
+    fn from(v: Rail) -> Self {
+        Self::Rail(v)
+    }
+}
+
+impl Rail {
+    pub fn common(&self) -> &ItemCommon {
+        &self.common
+    }
+
+    pub fn id(&self) -> Option<&ItemId> {
+        self.common.id.as_ref()
+    }
+
+    pub fn links(&self) -> impl Iterator<Item = &ItemLink> {
+        self.common.links.iter()
+    }
+
+    pub fn ticket_reference(&self) -> Option<&String> {
+        self.ticket_reference.as_ref()
+    }
+
+    pub fn legs(&self) -> impl Iterator<Item = &RailLeg> {
+        self.legs.iter()
+    }
+
+    ///
+    /// The trains of all legs, separated by `/`, for example `TGV 9577/ICE 591`.
+    ///
+    pub fn train_numbers_string(&self) -> String {
+        self.legs
+            .iter()
+            .map(|leg| leg.train_number().clone())
+            .collect::<Vec<String>>()
+            .join("/")
+    }
+
+    pub fn departure_date_time(&self) -> &DateTime {
+        self.first_leg().departure_date_time()
+    }
+
+    pub fn departure_time_zone(&self) -> Option<&TimeZone> {
+        self.first_leg().departure_time_zone()
+    }
+
+    pub fn departure_instant(&self) -> Option<Instant> {
+        self.first_leg().departure_instant()
+    }
+
+    pub fn departure_station(&self) -> &Location {
+        self.first_leg().departure_station()
+    }
+
+    pub fn arrival_date_time(&self) -> &DateTime {
+        self.last_leg().arrival_date_time()
+    }
+
+    pub fn arrival_time_zone(&self) -> Option<&TimeZone> {
+        self.last_leg().arrival_time_zone()
+    }
+
+    pub fn arrival_instant(&self) -> Option<Instant> {
+        self.last_leg().arrival_instant()
+    }
+
+    pub fn arrival_station(&self) -> Option<&Location> {
+        self.last_leg().arrival_station()
+    }
+
+    ///
+    /// The elapsed time from the first departure to the final arrival, including all changes.
+    ///
+    pub fn duration(&self) -> Duration {
+        match (self.departure_instant(), self.arrival_instant()) {
+            (Some(start), Some(end)) => end - start,
+            _ => *self.arrival_date_time() - *self.departure_date_time(),
+        }
+    }
+
+    pub fn changes(&self) -> Vec<RailChange<'_>> {
+        self.legs
+            .windows(2)
+            .map(|pair| RailChange {
+                arriving: &pair[0],
+                departing: &pair[1],
+            })
+            .collect()
+    }
+
+    pub fn change_stations(&self) -> impl Iterator<Item = &Location> {
+        self.legs.iter().skip(1).map(|leg| leg.departure_station())
+    }
+
+    pub(crate) fn first_leg(&self) -> &RailLeg {
+        self.legs.first().unwrap()
+    }
+
+    pub(crate) fn last_leg(&self) -> &RailLeg {
+        self.legs.last().unwrap()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl RailLeg {
+    pub fn operator(&self) -> &String {
+        &self.record.provider
+    }
+
+    pub fn train_number(&self) -> &String {
+        &self.train_number
+    }
+
+    ///
+    /// The operator and train number, for example `SNCF TGV 9577`.
+    ///
+    pub fn train_string(&self) -> String {
+        format!("{} {}", self.operator(), self.train_number())
+    }
+
+    pub fn class(&self) -> Option<&String> {
+        self.class.as_ref()
+    }
+
+    pub fn coach(&self) -> Option<&String> {
+        self.coach.as_ref()
+    }
+
+    pub fn seat(&self) -> Option<&String> {
+        self.seat.as_ref()
+    }
+
+    pub fn confirmation(&self) -> Option<&String> {
+        self.record.confirmation.as_ref()
+    }
+
+    pub fn departure_date_time(&self) -> &DateTime {
+        &self.record.start_date_time
+    }
+
+    pub fn departure_time_zone(&self) -> Option<&TimeZone> {
+        self.record.start_time_zone()
+    }
+
+    pub fn departure_instant(&self) -> Option<Instant> {
+        self.record.start_instant()
+    }
+
+    pub fn departure_station(&self) -> &Location {
+        &self.record.start_location
+    }
+
+    pub fn arrival_date_time(&self) -> &DateTime {
+        &self.record.end_date_time
+    }
+
+    pub fn arrival_time_zone(&self) -> Option<&TimeZone> {
+        self.record.end_time_zone()
+    }
+
+    pub fn arrival_instant(&self) -> Option<Instant> {
+        self.record.end_instant()
+    }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
-pub struct Accomodation {
-    record: TravelRecord<String, String>,
-}
+    ///
+    /// The arrival station; this is only missing from an invalid itinerary, see
+    /// `Itinerary::validate`.
+    ///
+    pub fn arrival_station(&self) -> Option<&Location> {
+        self.record.end_location.as_ref()
+    }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
-pub struct Event {
-    record: TravelRecord<String, String>,
-}
+    pub fn duration(&self) -> Duration {
+        self.record.duration()
+    }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
-pub(crate) struct TravelRecord<P, L> {
-    provider: P,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    confirmation: Option<String>,
-    start_date_time: DateTime,
-    start_location: L,
-    end_date_time: DateTime,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    end_location: Option<L>,
+    pub(crate) fn inner(&self) -> &TravelRecord<String, Location> {
+        &self.record
+    }
 }
 
-// ------------------------------------------------------------------------------------------------
-// Private Types
 // ------------------------------------------------------------------------------------------------
 
-impl Itinerary {
-    pub fn identifier(&self) -> &String {
-        &self.identifier
+impl<'a> RailChange<'a> {
+    pub fn station(&self) -> &'a Location {
+        self.departing.departure_station()
     }
 
-    pub fn name(&self) -> &String {
-        &self.name
+    pub fn arriving(&self) -> &'a RailLeg {
+        self.arriving
     }
 
-    pub fn travelers(&self) -> impl Iterator<Item = &String> {
-        self.travelers.iter()
+    pub fn departing(&self) -> &'a RailLeg {
+        self.departing
     }
 
-    pub fn items(&self) -> impl Iterator<Item = &Item> {
-        self.items.iter()
+    ///
+    /// The time between arriving and departing; both are at the same station so this is correct
+    /// even when no time zone is known.
+    ///
+    pub fn change_time(&self) -> Duration {
+        match (
+            self.arriving.arrival_instant(),
+            self.departing.departure_instant(),
+        ) {
+            (Some(arrives), Some(departs)) => departs - arrives,
+            _ => *self.departing.departure_date_time() - *self.arriving.arrival_date_time(),
+        }
     }
 }
 
 // ------------------------------------------------------------------------------------------------
 
-impl Item {
-    pub fn is_flight(&self) -> bool {
-        matches!(&self, Item::Flight(_))
+impl From<Cruise> for Item {
+    fn from(v: Cruise) -> Self {
+        Self::Cruise(v)
     }
+}
 
-    pub fn as_flight(&self) -> Option<&Flight> {
-        match self {
-            Item::Flight(v) => Some(v),
-            _ => None,
-        }
+impl Cruise {
+    pub fn common(&self) -> &ItemCommon {
+        &self.common
     }
 
-    pub fn is_transport(&self) -> bool {
-        matches!(&self, Item::Transport(_))
+    pub fn id(&self) -> Option<&ItemId> {
+        self.common.id.as_ref()
     }
 
-    pub fn as_transport(&self) -> Option<&Transport> {
-        match self {
-            Item::Transport(v) => Some(v),
-            _ => None,
-        }
+    pub fn links(&self) -> impl Iterator<Item = &ItemLink> {
+        self.common.links.iter()
     }
 
-    pub fn is_accomodation(&self) -> bool {
-        matches!(&self, Item::Accomodation(_))
+    pub fn cruise_line(&self) -> &String {
+        &self.record.provider
     }
 
-    pub fn as_accomodation(&self) -> Option<&Accomodation> {
-        match self {
-            Item::Accomodation(v) => Some(v),
-            _ => None,
-        }
+    pub fn confirmation(&self) -> Option<&String> {
+        self.record.confirmation.as_ref()
     }
 
-    pub fn is_vehicle(&self) -> bool {
-        matches!(&self, Item::Vehicle(_))
+    pub fn ship(&self) -> &String {
+        &self.ship
     }
 
-    pub fn as_vehicle(&self) -> Option<&Vehicle> {
-        match self {
-            Item::Vehicle(v) => Some(v),
-            _ => None,
-        }
+    pub fn cabin(&self) -> Option<&String> {
+        self.cabin.as_ref()
     }
 
-    pub fn is_event(&self) -> bool {
-        matches!(&self, Item::Event(_))
+    pub fn embarkation_date_time(&self) -> &DateTime {
+        &self.record.start_date_time
     }
 
-    pub fn as_event(&self) -> Option<&Event> {
-        match self {
-            Item::Event(v) => Some(v),
-            _ => None,
-        }
+    pub fn embarkation_time_zone(&self) -> Option<&TimeZone> {
+        self.record.start_time_zone()
+    }
+
+    pub fn embarkation_instant(&self) -> Option<Instant> {
+        self.record.start_instant()
+    }
+
+    pub fn embarkation_port(&self) -> &Location {
+        &self.record.start_location
+    }
+
+    pub fn disembarkation_date_time(&self) -> &DateTime {
+        &self.record.end_date_time
+    }
+
+    pub fn disembarkation_time_zone(&self) -> Option<&TimeZone> {
+        self.record.end_time_zone()
+    }
+
+    pub fn disembarkation_instant(&self) -> Option<Instant> {
+        self.record.end_instant()
+    }
+
+    ///
+    /// The port of disembarkation; if not given the ship returns to the port of embarkation.
+    ///
+    pub fn disembarkation_port(&self) -> &Location {
+        self.record
+            .end_location
+            .as_ref()
+            .unwrap_or(&self.record.start_location)
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.record.duration()
+    }
+
+    pub fn port_calls(&self) -> impl Iterator<Item = &PortCall> {
+        self.port_calls.iter()
+    }
+
+    ///
+    /// The dates the ship spends entirely at sea; every date between embarkation and
+    /// disembarkation on which it neither arrives at nor departs from a port.
+    ///
+    pub fn days_at_sea(&self) -> Vec<Date> {
+        let embarks = self.embarkation_date_time().date();
+        let disembarks = self.disembarkation_date_time().date();
+        embarks
+            .iter_days()
+            .skip(1)
+            .take_while(|date| *date < disembarks)
+            .filter(|date| {
+                !self.port_calls.iter().any(|call| {
+                    call.arrival_date_time.date() <= *date
+                        && *date <= call.departure_date_time.date()
+                })
+            })
+            .collect()
+    }
+
+    pub(crate) fn inner(&self) -> &TravelRecord<String, Location> {
+        &self.record
     }
 }
 
 // ------------------------------------------------------------------------------------------------
 
-impl From<Flight> for Item {
-    fn from(v: Flight) -> Self {
-        Self::Flight(v)
+impl PartialEq for PortCall {
+    fn eq(&self, other: &Self) -> bool {
+        self.port == other.port
+            && self.arrival_date_time == other.arrival_date_time
+            && self.departure_date_time == other.departure_date_time
+            && self.time_zone == other.time_zone
     }
 }
 
-impl Flight {
-    pub fn carrier(&self) -> &AirCarrierCode {
-        &self.record.provider
+impl PortCall {
+    ///
+    /// A call at `port`; its time zone is determined from the port, where possible, once the
+    /// cruise is added to an itinerary.
+    ///
+    pub fn new(port: Location, arrival: DateTime, departure: DateTime) -> Self {
+        Self {
+            port,
+            arrival_date_time: arrival,
+            departure_date_time: departure,
+            time_zone: None,
+            time_zone_inferred: false,
+        }
     }
 
-    pub fn number(&self) -> &FlightNumber {
-        &self.number
+    pub fn port(&self) -> &Location {
+        &self.port
     }
 
-    pub fn flight_number_string(&self) -> String {
-        format!("{}{}", self.carrier(), self.number())
+    pub fn arrival_date_time(&self) -> &DateTime {
+        &self.arrival_date_time
     }
 
-    pub fn record_locator(&self) -> &String {
-        self.record.confirmation.as_ref().unwrap()
+    pub fn arrival_instant(&self) -> Option<Instant> {
+        self.time_zone
+            .and_then(|tz| tz.localize(&self.arrival_date_time))
+            .map(|v| v.with_timezone(&chrono::Utc))
     }
 
     pub fn departure_date_time(&self) -> &DateTime {
-        &self.record.start_date_time
-    }
-
-    pub fn departure_airport(&self) -> &AirportCode {
-        &self.record.start_location
+        &self.departure_date_time
     }
 
-    pub fn arrival_date_time(&self) -> &DateTime {
-        &self.record.end_date_time
+    pub fn departure_instant(&self) -> Option<Instant> {
+        self.time_zone
+            .and_then(|tz| tz.localize(&self.departure_date_time))
+            .map(|v| v.with_timezone(&chrono::Utc))
     }
 
-    pub fn arrival_airport(&self) -> &AirportCode {
-        self.record.end_location.as_ref().unwrap()
+    pub fn time_zone(&self) -> Option<&TimeZone> {
+        self.time_zone.as_ref()
     }
 
-    pub(crate) fn inner(&self) -> &TravelRecord<AirCarrierCode, AirportCode> {
-        &self.record
+    ///
+    /// The time the ship is in port.
+    ///
+    pub fn duration(&self) -> Duration {
+        self.departure_date_time - self.arrival_date_time
     }
 }
 
@@ -260,6 +1915,18 @@ impl From<Transport> for Item {
 }
 
 impl Transport {
+    pub fn common(&self) -> &ItemCommon {
+        &self.common
+    }
+
+    pub fn id(&self) -> Option<&ItemId> {
+        self.common.id.as_ref()
+    }
+
+    pub fn links(&self) -> impl Iterator<Item = &ItemLink> {
+        self.common.links.iter()
+    }
+
     pub fn company_or_agency(&self) -> &String {
         &self.record.provider
     }
@@ -272,7 +1939,15 @@ impl Transport {
         &self.record.start_date_time
     }
 
-    pub fn departure_address(&self) -> &String {
+    pub fn departure_time_zone(&self) -> Option<&TimeZone> {
+        self.record.start_time_zone()
+    }
+
+    pub fn departure_instant(&self) -> Option<Instant> {
+        self.record.start_instant()
+    }
+
+    pub fn departure_address(&self) -> &Location {
         &self.record.start_location
     }
 
@@ -280,15 +1955,31 @@ impl Transport {
         &self.record.end_date_time
     }
 
-    pub fn arrival_address(&self) -> &String {
-        self.record.end_location.as_ref().unwrap()
+    ///
+    /// The arrival address; this is only missing from an invalid itinerary, see
+    /// `Itinerary::validate`.
+    ///
+    pub fn arrival_address(&self) -> Option<&Location> {
+        self.record.end_location.as_ref()
+    }
+
+    pub fn arrival_time_zone(&self) -> Option<&TimeZone> {
+        self.record.end_time_zone()
+    }
+
+    pub fn arrival_instant(&self) -> Option<Instant> {
+        self.record.end_instant()
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.record.duration()
     }
 
     pub fn kind(&self) -> Option<&TransportKind> {
         self.kind.as_ref()
     }
 
-    pub(crate) fn inner(&self) -> &TravelRecord<String, String> {
+    pub(crate) fn inner(&self) -> &TravelRecord<String, Location> {
         &self.record
     }
 }
@@ -302,6 +1993,18 @@ impl From<Vehicle> for Item {
 }
 
 impl Vehicle {
+    pub fn common(&self) -> &ItemCommon {
+        &self.common
+    }
+
+    pub fn id(&self) -> Option<&ItemId> {
+        self.common.id.as_ref()
+    }
+
+    pub fn links(&self) -> impl Iterator<Item = &ItemLink> {
+        self.common.links.iter()
+    }
+
     pub fn rental_agency(&self) -> &String {
         &self.record.provider
     }
@@ -314,7 +2017,15 @@ impl Vehicle {
         &self.record.start_date_time
     }
 
-    pub fn pickup_address(&self) -> &String {
+    pub fn pickup_time_zone(&self) -> Option<&TimeZone> {
+        self.record.start_time_zone()
+    }
+
+    pub fn pickup_instant(&self) -> Option<Instant> {
+        self.record.start_instant()
+    }
+
+    pub fn pickup_address(&self) -> &Location {
         &self.record.start_location
     }
 
@@ -322,11 +2033,29 @@ impl Vehicle {
         &self.record.end_date_time
     }
 
-    pub fn dropoff_address(&self) -> &String {
-        self.record.end_location.as_ref().unwrap()
+    ///
+    /// The dropoff address; if not given the vehicle is returned where it was picked up.
+    ///
+    pub fn dropoff_address(&self) -> &Location {
+        self.record
+            .end_location
+            .as_ref()
+            .unwrap_or(&self.record.start_location)
+    }
+
+    pub fn dropoff_time_zone(&self) -> Option<&TimeZone> {
+        self.record.end_time_zone()
+    }
+
+    pub fn dropoff_instant(&self) -> Option<Instant> {
+        self.record.end_instant()
     }
 
-    pub(crate) fn inner(&self) -> &TravelRecord<String, String> {
+    pub fn duration(&self) -> Duration {
+        self.record.duration()
+    }
+
+    pub(crate) fn inner(&self) -> &TravelRecord<String, Location> {
         &self.record
     }
 }
@@ -340,6 +2069,18 @@ impl From<Accomodation> for Item {
 }
 
 impl Accomodation {
+    pub fn common(&self) -> &ItemCommon {
+        &self.common
+    }
+
+    pub fn id(&self) -> Option<&ItemId> {
+        self.common.id.as_ref()
+    }
+
+    pub fn links(&self) -> impl Iterator<Item = &ItemLink> {
+        self.common.links.iter()
+    }
+
     pub fn company(&self) -> &String {
         &self.record.provider
     }
@@ -348,7 +2089,7 @@ impl Accomodation {
         self.record.confirmation.as_ref()
     }
 
-    pub fn address(&self) -> &String {
+    pub fn address(&self) -> &Location {
         &self.record.start_location
     }
 
@@ -356,11 +2097,31 @@ impl Accomodation {
         &self.record.start_date_time
     }
 
+    pub fn checkin_time_zone(&self) -> Option<&TimeZone> {
+        self.record.start_time_zone()
+    }
+
+    pub fn checkin_instant(&self) -> Option<Instant> {
+        self.record.start_instant()
+    }
+
     pub fn checkout_date_time(&self) -> &DateTime {
         &self.record.end_date_time
     }
 
-    pub(crate) fn inner(&self) -> &TravelRecord<String, String> {
+    pub fn checkout_time_zone(&self) -> Option<&TimeZone> {
+        self.record.end_time_zone()
+    }
+
+    pub fn checkout_instant(&self) -> Option<Instant> {
+        self.record.end_instant()
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.record.duration()
+    }
+
+    pub(crate) fn inner(&self) -> &TravelRecord<String, Location> {
         &self.record
     }
 }
@@ -374,6 +2135,18 @@ impl From<Event> for Item {
 }
 
 impl Event {
+    pub fn common(&self) -> &ItemCommon {
+        &self.common
+    }
+
+    pub fn id(&self) -> Option<&ItemId> {
+        self.common.id.as_ref()
+    }
+
+    pub fn links(&self) -> impl Iterator<Item = &ItemLink> {
+        self.common.links.iter()
+    }
+
     pub fn company(&self) -> &String {
         &self.record.provider
     }
@@ -382,7 +2155,7 @@ impl Event {
         self.record.confirmation.as_ref()
     }
 
-    pub fn address(&self) -> &String {
+    pub fn address(&self) -> &Location {
         &self.record.start_location
     }
 
@@ -390,15 +2163,140 @@ impl Event {
         &self.record.start_date_time
     }
 
+    pub fn start_time_zone(&self) -> Option<&TimeZone> {
+        self.record.start_time_zone()
+    }
+
+    pub fn start_instant(&self) -> Option<Instant> {
+        self.record.start_instant()
+    }
+
     pub fn end_date_time(&self) -> &DateTime {
         &self.record.end_date_time
     }
 
-    pub(crate) fn inner(&self) -> &TravelRecord<String, String> {
+    pub fn end_time_zone(&self) -> Option<&TimeZone> {
+        self.record.end_time_zone()
+    }
+
+    pub fn end_instant(&self) -> Option<Instant> {
+        self.record.end_instant()
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.record.duration()
+    }
+
+    pub(crate) fn inner(&self) -> &TravelRecord<String, Location> {
         &self.record
     }
 }
 
+// ------------------------------------------------------------------------------------------------
+
+impl TimeZoneLocation for AirportCode {
+    fn time_zone(&self) -> Option<TimeZone> {
+        TimeZone::for_airport(self)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl<P: PartialEq, L: PartialEq> PartialEq for TravelRecord<P, L> {
+    fn eq(&self, other: &Self) -> bool {
+        self.provider == other.provider
+            && self.confirmation == other.confirmation
+            && self.start_date_time == other.start_date_time
+            && self.start_time_zone == other.start_time_zone
+            && self.start_location == other.start_location
+            && self.end_date_time == other.end_date_time
+            && self.end_time_zone == other.end_time_zone
+            && self.end_location == other.end_location
+    }
+}
+
+impl<P, L> TravelRecord<P, L> {
+    pub(crate) fn start_time_zone(&self) -> Option<&TimeZone> {
+        self.start_time_zone.as_ref()
+    }
+
+    pub(crate) fn end_time_zone(&self) -> Option<&TimeZone> {
+        self.end_time_zone.as_ref()
+    }
+
+    pub(crate) fn clear_inferred_time_zones(&mut self) {
+        if self.start_time_zone_inferred {
+            self.start_time_zone = None;
+            self.start_time_zone_inferred = false;
+        }
+        if self.end_time_zone_inferred {
+            self.end_time_zone = None;
+            self.end_time_zone_inferred = false;
+        }
+    }
+
+    pub(crate) fn start_zoned_date_time(&self) -> Option<ZonedDateTime> {
+        self.start_time_zone
+            .and_then(|tz| tz.localize(&self.start_date_time))
+    }
+
+    pub(crate) fn end_zoned_date_time(&self) -> Option<ZonedDateTime> {
+        self.end_time_zone
+            .and_then(|tz| tz.localize(&self.end_date_time))
+    }
+
+    pub(crate) fn start_instant(&self) -> Option<Instant> {
+        self.start_zoned_date_time()
+            .map(|v| v.with_timezone(&chrono::Utc))
+    }
+
+    pub(crate) fn end_instant(&self) -> Option<Instant> {
+        self.end_zoned_date_time()
+            .map(|v| v.with_timezone(&chrono::Utc))
+    }
+
+    ///
+    /// The elapsed time between start and end; if either endpoint has no time zone this is
+    /// simply the difference between the two wall-clock times.
+    ///
+    pub(crate) fn duration(&self) -> Duration {
+        match (self.start_instant(), self.end_instant()) {
+            (Some(start), Some(end)) => end - start,
+            _ => self.end_date_time - self.start_date_time,
+        }
+    }
+}
+
+impl<P, L: TimeZoneLocation> TravelRecord<P, L> {
+    ///
+    /// Fill in missing time zones from the start and end locations. If `shared` is true the two
+    /// endpoints are assumed to be in the same zone, so that if only one can be determined it is
+    /// used for both; this is true for everything but flights.
+    ///
+    pub(crate) fn infer_time_zones(&mut self, shared: bool) {
+        let (start_missing, end_missing) =
+            (self.start_time_zone.is_none(), self.end_time_zone.is_none());
+        if self.start_time_zone.is_none() {
+            self.start_time_zone = self.start_location.time_zone();
+        }
+        if self.end_time_zone.is_none() {
+            self.end_time_zone = match &self.end_location {
+                Some(location) => location.time_zone(),
+                None => self.start_time_zone,
+            };
+        }
+        if shared {
+            if self.start_time_zone.is_none() {
+                self.start_time_zone = self.end_time_zone;
+            } else if self.end_time_zone.is_none() {
+                self.end_time_zone = self.start_time_zone;
+            }
+        }
+        self.start_time_zone_inferred |= start_missing && self.start_time_zone.is_some();
+        self.end_time_zone_inferred |= end_missing && self.end_time_zone.is_some();
+    }
+}
+
 // ------------------------------------------------------------------------------------------------
 // Public Functions
 // ------------------------------------------------------------------------------------------------
@@ -416,6 +2314,110 @@ fn is_valid_air_carrier_code(s: &str) -> bool {
     (s.len() == 2 || s.len() == 3) && s.chars().all(|c| c.is_ascii_alphanumeric())
 }
 
+fn parse_offset(s: &str) -> Option<chrono::FixedOffset> {
+    let (sign, rest) = match s.split_at(1) {
+        ("+", rest) => (1, rest),
+        ("-", rest) => (-1, rest),
+        _ => return None,
+    };
+    let (hours, minutes) = match rest.split_once(':') {
+        Some(v) => v,
+        None if rest.len() == 4 => rest.split_at(2),
+        None => (rest, "0"),
+    };
+    let hours: i32 = hours.parse().ok()?;
+    let minutes: i32 = minutes.parse().ok()?;
+    if minutes >= 60 {
+        return None;
+    }
+    chrono::FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+fn update_gate(current: &mut Option<AirportGate>, gate: &AirportGate) {
+    if !gate.is_empty() {
+        current
+            .get_or_insert_with(Default::default)
+            .update_from(gate);
+    }
+}
+
+///
+/// Segments are read as flights so that any value common to items, which a segment can not have,
+/// is reported rather than silently dropped.
+///
+fn deserialize_segments<'de, D>(
+    deserializer: D,
+) -> std::result::Result<Vec<FlightSegment>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let segments: Vec<Flight> = Vec::deserialize(deserializer)?;
+    if segments.is_empty() {
+        Err(serde::de::Error::invalid_length(0, &"at least one segment"))
+    } else if let Some(segment) = segments
+        .iter()
+        .find(|segment| segment.common != ItemCommon::default())
+    {
+        Err(serde::de::Error::custom(format!(
+            "journey segment {} has an id, links, travelers, cost, status or annotations; these \
+             belong to the journey",
+            segment.flight_number_string()
+        )))
+    } else {
+        Ok(segments
+            .into_iter()
+            .map(|segment| segment.segment)
+            .collect())
+    }
+}
+
+fn deserialize_legs<'de, D>(deserializer: D) -> std::result::Result<Vec<RailLeg>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let legs: Vec<RailLeg> = Vec::deserialize(deserializer)?;
+    if legs.is_empty() {
+        Err(serde::de::Error::invalid_length(0, &"at least one leg"))
+    } else {
+        Ok(legs)
+    }
+}
+
+#[inline]
+fn is_valid_item_id(s: &str) -> bool {
+    !s.is_empty()
+        && s.chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+///
+/// The countries of an item's locations, or for flights of its airports, in the order visited.
+///
+fn item_country_codes(item: &Item) -> Vec<CountryCode> {
+    let airports: Vec<&AirportCode> = match item {
+        Item::Flight(v) => std::iter::once(v.departure_airport())
+            .chain(v.arrival_airport())
+            .collect(),
+        Item::Journey(v) => v
+            .segments()
+            .flat_map(|segment| {
+                std::iter::once(segment.departure_airport()).chain(segment.arrival_airport())
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+    item.locations()
+        .into_iter()
+        .filter_map(|location| location.country_code().cloned())
+        .chain(
+            airports
+                .into_iter()
+                .filter_map(|airport| time_zones::country_for_airport(airport))
+                .filter_map(|country| CountryCode::from_str(country).ok()),
+        )
+        .collect()
+}
+
 #[inline]
 fn is_valid_airport_code(s: &str) -> bool {
     s.len() == 3 && s.chars().all(|c| c.is_ascii_alphabetic())
@@ -425,12 +2427,42 @@ fn is_valid_airport_code(s: &str) -> bool {
 // Modules
 // ------------------------------------------------------------------------------------------------
 
+mod time_zones;
+
+pub mod traveler;
+
+pub mod cost;
+
+pub mod flight_details;
+
+pub mod status;
+
+pub mod annotation;
+
+pub mod location;
+
+pub mod metadata;
+
 pub mod io;
 
+pub mod builder;
+
+pub mod validation;
+
 pub mod visitor;
 
+pub mod filter;
+
+pub mod async_visitor;
+
+pub mod composite_visitor;
+
 pub mod display;
 
 pub mod export;
 
 pub mod edit;
+
+pub mod journal;
+
+pub mod template;
@@ -0,0 +1,205 @@
+/*!
+The status of an item, and the history of changes to it.
+
+An item starts out either tentative, for example a hotel option being held, or confirmed. A
+tentative item may be confirmed or cancelled, a confirmed item may be cancelled or completed once
+it has happened. Cancelled and completed items are final, they are kept in the itinerary so that
+refunds and expenses can still be tracked.
+ */
+
+use_required!();
+use crate::error::{Error, ErrorKind};
+use crate::itinerary::Instant;
+use serde::{Deserialize, Serialize};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemStatus {
+    Tentative,
+    #[default]
+    Confirmed,
+    Cancelled,
+    Completed,
+}
+
+///
+/// A record of a single change to the status of an item.
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StatusChange {
+    from: ItemStatus,
+    to: ItemStatus,
+    at: Instant,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    note: Option<String>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Display for ItemStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                ItemStatus::Tentative => "tentative",
+                ItemStatus::Confirmed => "confirmed",
+                ItemStatus::Cancelled => "cancelled",
+                ItemStatus::Completed => "completed",
+            }
+        )
+    }
+}
+
+impl FromStr for ItemStatus {
+    type Err = Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "tentative" => Ok(Self::Tentative),
+            "confirmed" => Ok(Self::Confirmed),
+            "cancelled" => Ok(Self::Cancelled),
+            "completed" => Ok(Self::Completed),
+            _ => Err(ErrorKind::InvalidItemStatus(s.to_string()).into()),
+        }
+    }
+}
+
+impl ItemStatus {
+    pub fn is_tentative(&self) -> bool {
+        matches!(self, Self::Tentative)
+    }
+
+    pub fn is_confirmed(&self) -> bool {
+        matches!(self, Self::Confirmed)
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self, Self::Cancelled)
+    }
+
+    pub fn is_completed(&self) -> bool {
+        matches!(self, Self::Completed)
+    }
+
+    ///
+    /// Returns `true` if an item with this status may be changed to `next`.
+    ///
+    pub fn can_transition_to(&self, next: ItemStatus) -> bool {
+        matches!(
+            (self, next),
+            (Self::Tentative, Self::Confirmed)
+                | (Self::Tentative, Self::Cancelled)
+                | (Self::Confirmed, Self::Cancelled)
+                | (Self::Confirmed, Self::Completed)
+        )
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl StatusChange {
+    pub(crate) fn new(from: ItemStatus, to: ItemStatus, at: Instant, note: Option<&str>) -> Self {
+        Self {
+            from,
+            to,
+            at,
+            note: note.map(|v| v.to_string()),
+        }
+    }
+
+    pub fn from(&self) -> ItemStatus {
+        self.from
+    }
+
+    pub fn to(&self) -> ItemStatus {
+        self.to
+    }
+
+    pub fn at(&self) -> &Instant {
+        &self.at
+    }
+
+    pub fn note(&self) -> Option<&String> {
+        self.note.as_ref()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::ItemStatus;
+    use crate::itinerary::io::{from_str, to_string};
+    use crate::itinerary::{Instant, ItemId};
+    use chrono::TimeZone;
+    use pretty_assertions::assert_eq;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_transitions() {
+        assert!(ItemStatus::Tentative.can_transition_to(ItemStatus::Confirmed));
+        assert!(ItemStatus::Tentative.can_transition_to(ItemStatus::Cancelled));
+        assert!(ItemStatus::Confirmed.can_transition_to(ItemStatus::Completed));
+        assert!(!ItemStatus::Tentative.can_transition_to(ItemStatus::Completed));
+        assert!(!ItemStatus::Cancelled.can_transition_to(ItemStatus::Confirmed));
+        assert!(!ItemStatus::Completed.can_transition_to(ItemStatus::Cancelled));
+        assert!(!ItemStatus::Confirmed.can_transition_to(ItemStatus::Confirmed));
+    }
+
+    #[test]
+    fn test_status_history() {
+        let mut itinerary = from_str(
+            r##"---
+identifier: 2022-06
+name: Playa del Carmen
+travelers:
+  - Simon
+items:
+  - accomodation:
+      status: tentative
+      record:
+        provider: Hotel Xcaret
+        start_date_time: "2022-06-24T16:00:00"
+        start_location: Playa del Carmen
+        end_date_time: "2022-07-01T11:00:00"
+"##,
+        )
+        .unwrap();
+        let id = ItemId::from_str("accomodation-hotel-xcaret-20220624").unwrap();
+        let at: Instant = chrono::Utc.with_ymd_and_hms(2022, 5, 1, 12, 0, 0).unwrap();
+
+        assert!(itinerary
+            .set_item_status(&id, ItemStatus::Completed, at, None)
+            .is_err());
+        itinerary
+            .set_item_status(&id, ItemStatus::Confirmed, at, Some("deposit paid"))
+            .unwrap();
+
+        let common = itinerary.item(&id).unwrap().common();
+        assert_eq!(common.status(), ItemStatus::Confirmed);
+        assert_eq!(common.status_history().count(), 1);
+
+        let reloaded = from_str(&to_string(&itinerary).unwrap()).unwrap();
+        assert_eq!(reloaded, itinerary);
+        let change = reloaded
+            .item(&id)
+            .unwrap()
+            .common()
+            .status_history()
+            .next()
+            .unwrap();
+        assert_eq!(change.from(), ItemStatus::Tentative);
+        assert_eq!(change.to(), ItemStatus::Confirmed);
+        assert_eq!(change.at(), &at);
+        assert_eq!(change.note(), Some(&"deposit paid".to_string()));
+    }
+}
@@ -0,0 +1,307 @@
+/*!
+Templates for itineraries that follow the same shape each time, such as regular visits to an
+office.
+
+A template is an itinerary written relative to a start date, along with a list of placeholders
+for the values that change from trip to trip. In the itinerary part any date-time may be written
+as `day N HH:MM`, meaning `N` days after the start date, and any string may include placeholders
+as `{{name}}`. The placeholder `start_date` is always available, and is the start date in
+`YYYY-MM-DD` form.
+
+# Example
+
+```rust
+use terminal_travel::itinerary::template::from_str;
+use terminal_travel::itinerary::Date;
+use std::collections::HashMap;
+
+let template = from_str(r##"
+placeholders:
+  - name: hotel_confirmation
+    description: The hotel's confirmation number
+itinerary:
+  identifier: "hq-{{start_date}}"
+  name: Visit to HQ
+  travelers: [Simon]
+  items:
+    - accomodation:
+        record:
+          provider: Hotel Nikko
+          confirmation: "{{hotel_confirmation}}"
+          start_date_time: "day 0 16:00"
+          start_location: San Francisco
+          end_date_time: "day 3 11:00"
+"##).unwrap();
+
+let mut values = HashMap::new();
+values.insert("hotel_confirmation".to_string(), "R1234".to_string());
+let itinerary = template
+    .instantiate(Date::from_ymd_opt(2022, 9, 12).unwrap(), &values)
+    .unwrap();
+assert_eq!(itinerary.identifier(), "hq-2022-09-12");
+```
+ */
+
+use crate::error::{ErrorKind, Result};
+use crate::itinerary::io::complete_loaded;
+use crate::itinerary::{Date, Itinerary, Time};
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::Path;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+pub const START_DATE_PLACEHOLDER: &str = "start_date";
+
+///
+/// A value to be provided when the template is used; if it has a default the value is optional.
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Placeholder {
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    default: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Template {
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    placeholders: Vec<Placeholder>,
+    itinerary: Value,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+pub fn from_str(s: &str) -> Result<Template> {
+    let t: Template = serde_yaml::from_str(s)?;
+    Ok(t)
+}
+
+pub fn from_file(p: &Path) -> Result<Template> {
+    let t: Template = serde_yaml::from_reader(File::open(p)?)?;
+    Ok(t)
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Placeholder {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            description: None,
+            default: None,
+        }
+    }
+
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    pub fn description(&self) -> Option<&String> {
+        self.description.as_ref()
+    }
+
+    pub fn default(&self) -> Option<&String> {
+        self.default.as_ref()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Template {
+    pub fn placeholders(&self) -> impl Iterator<Item = &Placeholder> {
+        self.placeholders.iter()
+    }
+
+    ///
+    /// Create an itinerary starting on `start`, taking placeholder values from `values` or, if
+    /// not present there, from their defaults. It is an error if a placeholder has no value, or
+    /// the template uses a placeholder it does not declare.
+    ///
+    pub fn instantiate(&self, start: Date, values: &HashMap<String, String>) -> Result<Itinerary> {
+        let mut resolved: HashMap<&str, String> = HashMap::new();
+        let _ = resolved.insert(START_DATE_PLACEHOLDER, start.format("%Y-%m-%d").to_string());
+        for placeholder in &self.placeholders {
+            let value = values
+                .get(&placeholder.name)
+                .or(placeholder.default.as_ref())
+                .ok_or_else(|| {
+                    ErrorKind::MissingValue("template".to_string(), placeholder.name.clone())
+                })?;
+            let _ = resolved.insert(&placeholder.name, value.clone());
+        }
+        let value = instantiate_value(&self.itinerary, start, &resolved)?;
+        let itinerary: Itinerary = serde_yaml::from_value(value)?;
+        Ok(complete_loaded(itinerary))
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn instantiate_value(value: &Value, start: Date, values: &HashMap<&str, String>) -> Result<Value> {
+    Ok(match value {
+        Value::String(s) => {
+            let s = replace_placeholders(s, values)?;
+            match relative_date_time(&s, start) {
+                Some(date_time) => Value::String(date_time),
+                None => Value::String(s),
+            }
+        }
+        Value::Sequence(vs) => Value::Sequence(
+            vs.iter()
+                .map(|v| instantiate_value(v, start, values))
+                .collect::<Result<Vec<Value>>>()?,
+        ),
+        Value::Mapping(vs) => {
+            let mut mapping = serde_yaml::Mapping::new();
+            for (k, v) in vs {
+                let _ = mapping.insert(k.clone(), instantiate_value(v, start, values)?);
+            }
+            Value::Mapping(mapping)
+        }
+        _ => value.clone(),
+    })
+}
+
+fn replace_placeholders(s: &str, values: &HashMap<&str, String>) -> Result<String> {
+    let mut result = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(open) = rest.find("{{") {
+        let close = match rest[open..].find("}}") {
+            Some(close) => open + close,
+            None => break,
+        };
+        let name = rest[open + 2..close].trim();
+        let value = values.get(name).ok_or_else(|| {
+            ErrorKind::InvalidValue(
+                "template".to_string(),
+                format!("the placeholder '{}' is not declared", name),
+            )
+        })?;
+        result.push_str(&rest[..open]);
+        result.push_str(value);
+        rest = &rest[close + 2..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+///
+/// Parse `day N HH:MM`, or `day N HH:MM:SS`, as a date-time `N` days after `start`.
+///
+fn relative_date_time(s: &str, start: Date) -> Option<String> {
+    let mut parts = s.trim().strip_prefix("day ")?.split_whitespace();
+    let days: i64 = parts.next()?.parse().ok()?;
+    let time = parts.next()?;
+    let time = Time::parse_from_str(time, "%H:%M:%S")
+        .or_else(|_| Time::parse_from_str(time, "%H:%M"))
+        .ok()?;
+    if parts.next().is_some() {
+        return None;
+    }
+    let date = start.checked_add_signed(chrono::Duration::days(days))?;
+    Some(date.and_time(time).format("%Y-%m-%dT%H:%M:%S").to_string())
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::from_str;
+    use crate::itinerary::Date;
+    use pretty_assertions::assert_eq;
+    use std::collections::HashMap;
+
+    const TEMPLATE: &str = r##"---
+placeholders:
+  - name: traveler
+  - name: record_locator
+    description: The airline's record locator
+  - name: hotel
+    default: Hotel Nikko
+itinerary:
+  identifier: "hq-{{ start_date }}"
+  name: "HQ visit, {{traveler}}"
+  travelers:
+    - "{{traveler}}"
+  items:
+    - flight:
+        number: 1471
+        record:
+          provider: AA
+          confirmation: "{{record_locator}}"
+          start_date_time: "day 0 08:00"
+          start_location: SEA
+          end_date_time: "day 0 10:15"
+          end_location: SFO
+    - accomodation:
+        record:
+          provider: "{{hotel}}"
+          start_date_time: "day 0 16:00"
+          start_location: San Francisco
+          end_date_time: "day 3 11:00"
+"##;
+
+    #[test]
+    fn test_instantiate() {
+        let template = from_str(TEMPLATE).unwrap();
+        assert_eq!(template.placeholders().count(), 3);
+        let mut values = HashMap::new();
+        let _ = values.insert("traveler".to_string(), "Simon".to_string());
+        let _ = values.insert("record_locator".to_string(), "NTCBUP".to_string());
+        let start = Date::from_ymd_opt(2022, 9, 12).unwrap();
+
+        let itinerary = template.instantiate(start, &values).unwrap();
+        assert_eq!(itinerary.identifier(), "hq-2022-09-12");
+        assert_eq!(itinerary.name(), "HQ visit, Simon");
+        assert_eq!(itinerary.travelers().next().unwrap().name(), "Simon");
+        let flight = itinerary.items().next().unwrap().as_flight().unwrap();
+        assert_eq!(flight.record_locator(), Some(&"NTCBUP".to_string()));
+        assert_eq!(
+            flight.departure_date_time(),
+            &start.and_hms_opt(8, 0, 0).unwrap()
+        );
+        assert!(flight.departure_time_zone().is_some());
+        let hotel = itinerary.items().nth(1).unwrap().as_accomodation().unwrap();
+        assert_eq!(hotel.company(), "Hotel Nikko");
+        assert_eq!(
+            hotel.checkout_date_time(),
+            &Date::from_ymd_opt(2022, 9, 15)
+                .unwrap()
+                .and_hms_opt(11, 0, 0)
+                .unwrap()
+        );
+        assert_eq!(
+            hotel.id().unwrap().to_string(),
+            "accomodation-hotel-nikko-20220912"
+        );
+    }
+
+    #[test]
+    fn test_missing_values() {
+        let template = from_str(TEMPLATE).unwrap();
+        let mut values = HashMap::new();
+        let _ = values.insert("traveler".to_string(), "Simon".to_string());
+        let start = Date::from_ymd_opt(2022, 9, 12).unwrap();
+        assert!(template.instantiate(start, &values).is_err());
+
+        let undeclared = from_str(&TEMPLATE.replace("{{hotel}}", "{{motel}}")).unwrap();
+        let _ = values.insert("record_locator".to_string(), "NTCBUP".to_string());
+        assert!(undeclared.instantiate(start, &values).is_err());
+    }
+}
@@ -0,0 +1,122 @@
+/*!
+Lookup of IANA time zones for airports and the cities they serve, and of the countries the
+airports are in.
+
+This is used to fill in the time zone of a travel record endpoint when the itinerary file does not
+specify one. The table is deliberately small, it covers the major airports likely to appear in an
+itinerary; anything not found here has to have its zone provided explicitly.
+ */
+
+use crate::itinerary::TimeZone;
+use chrono_tz::Tz;
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Return the time zone for the airport with the IATA code `code`, if known.
+///
+pub(crate) fn for_airport(code: &str) -> Option<TimeZone> {
+    AIRPORTS
+        .iter()
+        .find(|(airport, _, _, _)| airport.eq_ignore_ascii_case(code))
+        .map(|(_, _, _, tz)| TimeZone::Named(*tz))
+}
+
+///
+/// Return the ISO 3166 country code for the airport with the IATA code `code`, if known.
+///
+pub(crate) fn country_for_airport(code: &str) -> Option<&'static str> {
+    AIRPORTS
+        .iter()
+        .find(|(airport, _, _, _)| airport.eq_ignore_ascii_case(code))
+        .map(|(_, _, country, _)| *country)
+}
+
+///
+/// Return the time zone for a free-form address, if it is a known airport code or names the city
+/// an airport serves. An airport code must be the whole address, as a word within an address
+/// (the `SAN` in `"HOTEL SAN JUAN"`) is rarely a code. Only whole words of a city name match, and
+/// where more than one city is named the one with the most words (`"new york"` over `"york"`)
+/// and then the first named (`"Seattle, Washington"`) is used.
+///
+pub(crate) fn for_address(address: &str) -> Option<TimeZone> {
+    let address = address.trim();
+    if let Some((_, _, _, tz)) = AIRPORTS
+        .iter()
+        .find(|(airport, _, _, _)| *airport == address)
+    {
+        return Some(TimeZone::Named(*tz));
+    }
+    let words: Vec<String> = address
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase())
+        .collect();
+    AIRPORTS
+        .iter()
+        .filter_map(|(_, city, _, tz)| {
+            let city: Vec<&str> = city.split(' ').collect();
+            words
+                .windows(city.len())
+                .position(|window| window == city.as_slice())
+                .map(|position| (city.len(), position, tz))
+        })
+        .min_by_key(|(length, position, _)| (std::cmp::Reverse(*length), *position))
+        .map(|(_, _, tz)| TimeZone::Named(*tz))
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+const AIRPORTS: &[(&str, &str, &str, Tz)] = &[
+    ("AES", "alesund", "NO", Tz::Europe__Oslo),
+    ("AMS", "amsterdam", "NL", Tz::Europe__Amsterdam),
+    ("ANC", "anchorage", "US", Tz::America__Anchorage),
+    ("ATL", "atlanta", "US", Tz::America__New_York),
+    ("AUS", "austin", "US", Tz::America__Chicago),
+    ("BCN", "barcelona", "ES", Tz::Europe__Madrid),
+    ("BGO", "bergen", "NO", Tz::Europe__Oslo),
+    ("BOS", "boston", "US", Tz::America__New_York),
+    ("CDG", "paris", "FR", Tz::Europe__Paris),
+    ("CUN", "cancun", "MX", Tz::America__Cancun),
+    ("DEN", "denver", "US", Tz::America__Denver),
+    ("DFW", "dallas", "US", Tz::America__Chicago),
+    ("DUB", "dublin", "IE", Tz::Europe__Dublin),
+    ("DXB", "dubai", "AE", Tz::Asia__Dubai),
+    ("EDI", "edinburgh", "GB", Tz::Europe__London),
+    ("EWR", "newark", "US", Tz::America__New_York),
+    ("FCO", "rome", "IT", Tz::Europe__Rome),
+    ("FRA", "frankfurt", "DE", Tz::Europe__Berlin),
+    ("GVA", "geneva", "CH", Tz::Europe__Zurich),
+    ("HKG", "hong kong", "HK", Tz::Asia__Hong_Kong),
+    ("HNL", "honolulu", "US", Tz::Pacific__Honolulu),
+    ("IAD", "washington", "US", Tz::America__New_York),
+    ("JFK", "new york", "US", Tz::America__New_York),
+    ("LAS", "las vegas", "US", Tz::America__Los_Angeles),
+    ("LAX", "los angeles", "US", Tz::America__Los_Angeles),
+    ("LHR", "london", "GB", Tz::Europe__London),
+    ("MAD", "madrid", "ES", Tz::Europe__Madrid),
+    ("MEX", "mexico city", "MX", Tz::America__Mexico_City),
+    ("MIA", "miami", "US", Tz::America__New_York),
+    ("MSP", "minneapolis", "US", Tz::America__Chicago),
+    ("MUC", "munich", "DE", Tz::Europe__Berlin),
+    ("NRT", "tokyo", "JP", Tz::Asia__Tokyo),
+    ("ORD", "chicago", "US", Tz::America__Chicago),
+    ("PDX", "portland", "US", Tz::America__Los_Angeles),
+    ("PHX", "phoenix", "US", Tz::America__Phoenix),
+    ("SAN", "san diego", "US", Tz::America__Los_Angeles),
+    ("SEA", "seattle", "US", Tz::America__Los_Angeles),
+    ("SFO", "san francisco", "US", Tz::America__Los_Angeles),
+    ("SIN", "singapore", "SG", Tz::Asia__Singapore),
+    ("SJD", "los cabos", "MX", Tz::America__Mazatlan),
+    ("SLC", "salt lake city", "US", Tz::America__Denver),
+    ("STR", "stuttgart", "DE", Tz::Europe__Berlin),
+    ("SYD", "sydney", "AU", Tz::Australia__Sydney),
+    ("YUL", "montreal", "CA", Tz::America__Toronto),
+    ("YVR", "vancouver", "CA", Tz::America__Vancouver),
+    ("YYZ", "toronto", "CA", Tz::America__Toronto),
+    ("ZRH", "zurich", "CH", Tz::Europe__Zurich),
+];
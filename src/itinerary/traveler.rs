@@ -0,0 +1,332 @@
+/*!
+The people an itinerary is for.
+
+A traveler is identified within an itinerary by their name; items refer to the travelers they
+cover by this name. For compatibility with earlier files a traveler with nothing but a name is
+read from, and written to, a plain string.
+ */
+
+use crate::itinerary::Date;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(from = "TravelerRepr", into = "TravelerRepr")]
+pub struct Traveler {
+    name: String,
+    details: TravelerDetails,
+}
+
+///
+/// A membership number in a frequent flyer, hotel, or other loyalty program.
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LoyaltyNumber {
+    program: String,
+    number: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SeatPreference {
+    Aisle,
+    Middle,
+    Window,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+struct TravelerDetails {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    date_of_birth: Option<Date>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    loyalty_numbers: Vec<LoyaltyNumber>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    known_traveler_number: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    redress_number: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seat_preference: Option<SeatPreference>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    meal_preference: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum TravelerRepr {
+    Name(String),
+    Full {
+        name: String,
+        #[serde(flatten)]
+        details: TravelerDetails,
+    },
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl From<TravelerRepr> for Traveler {
+    fn from(v: TravelerRepr) -> Self {
+        match v {
+            TravelerRepr::Name(name) => Self::new(&name),
+            TravelerRepr::Full { name, details } => Self { name, details },
+        }
+    }
+}
+
+impl From<Traveler> for TravelerRepr {
+    fn from(v: Traveler) -> Self {
+        if v.details == TravelerDetails::default() {
+            TravelerRepr::Name(v.name)
+        } else {
+            TravelerRepr::Full {
+                name: v.name,
+                details: v.details,
+            }
+        }
+    }
+}
+
+impl Display for Traveler {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+impl Traveler {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            details: Default::default(),
+        }
+    }
+
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    pub fn date_of_birth(&self) -> Option<&Date> {
+        self.details.date_of_birth.as_ref()
+    }
+
+    pub fn set_date_of_birth(&mut self, date_of_birth: Date) {
+        self.details.date_of_birth = Some(date_of_birth);
+    }
+
+    pub fn loyalty_numbers(&self) -> impl Iterator<Item = &LoyaltyNumber> {
+        self.details.loyalty_numbers.iter()
+    }
+
+    ///
+    /// Returns the membership number for the named loyalty program, if the traveler has one.
+    ///
+    pub fn loyalty_number(&self, program: &str) -> Option<&String> {
+        self.loyalty_numbers()
+            .find(|v| v.program.eq_ignore_ascii_case(program))
+            .map(|v| &v.number)
+    }
+
+    pub fn add_loyalty_number(&mut self, loyalty_number: LoyaltyNumber) {
+        self.details.loyalty_numbers.push(loyalty_number);
+    }
+
+    pub fn known_traveler_number(&self) -> Option<&String> {
+        self.details.known_traveler_number.as_ref()
+    }
+
+    pub fn set_known_traveler_number(&mut self, known_traveler_number: &str) {
+        self.details.known_traveler_number = Some(known_traveler_number.to_string());
+    }
+
+    pub fn redress_number(&self) -> Option<&String> {
+        self.details.redress_number.as_ref()
+    }
+
+    pub fn set_redress_number(&mut self, redress_number: &str) {
+        self.details.redress_number = Some(redress_number.to_string());
+    }
+
+    pub fn seat_preference(&self) -> Option<&SeatPreference> {
+        self.details.seat_preference.as_ref()
+    }
+
+    pub fn set_seat_preference(&mut self, seat_preference: SeatPreference) {
+        self.details.seat_preference = Some(seat_preference);
+    }
+
+    pub fn meal_preference(&self) -> Option<&String> {
+        self.details.meal_preference.as_ref()
+    }
+
+    pub fn set_meal_preference(&mut self, meal_preference: &str) {
+        self.details.meal_preference = Some(meal_preference.to_string());
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl LoyaltyNumber {
+    pub fn new(program: &str, number: &str) -> Self {
+        Self {
+            program: program.to_string(),
+            number: number.to_string(),
+        }
+    }
+
+    pub fn program(&self) -> &String {
+        &self.program
+    }
+
+    pub fn number(&self) -> &String {
+        &self.number
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for SeatPreference {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                SeatPreference::Aisle => "aisle",
+                SeatPreference::Middle => "middle",
+                SeatPreference::Window => "window",
+            }
+        )
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::{LoyaltyNumber, SeatPreference, Traveler};
+    use crate::itinerary::io::from_str;
+    use crate::itinerary::Date;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_name_only_is_a_string() {
+        let traveler: Traveler = serde_yaml::from_str("Simon").unwrap();
+        assert_eq!(traveler, Traveler::new("Simon"));
+        assert_eq!(
+            serde_yaml::to_string(&traveler).unwrap(),
+            "---\nSimon\n".to_string()
+        );
+    }
+
+    #[test]
+    fn test_full_traveler() {
+        let yaml = r##"---
+name: Alice
+date_of_birth: 1980-02-29
+loyalty_numbers:
+  - program: DL
+    number: "9012345678"
+known_traveler_number: TT1234567
+seat_preference: aisle
+meal_preference: VGML
+"##;
+        let traveler: Traveler = serde_yaml::from_str(yaml).unwrap();
+
+        let mut expected = Traveler::new("Alice");
+        expected.set_date_of_birth(Date::from_ymd_opt(1980, 2, 29).unwrap());
+        expected.add_loyalty_number(LoyaltyNumber::new("DL", "9012345678"));
+        expected.set_known_traveler_number("TT1234567");
+        expected.set_seat_preference(SeatPreference::Aisle);
+        expected.set_meal_preference("VGML");
+
+        assert_eq!(traveler, expected);
+        assert_eq!(
+            traveler.loyalty_number("dl"),
+            Some(&"9012345678".to_string())
+        );
+        assert_eq!(serde_yaml::to_string(&traveler).unwrap(), yaml.to_string());
+    }
+
+    #[test]
+    fn test_itinerary_for_traveler() {
+        let itinerary = from_str(
+            r##"---
+identifier: 2022-06
+name: Playa del Carmen
+travelers:
+  - Simon
+  - name: Alice
+    seat_preference: window
+bookings:
+  - agency: Xcaret
+    confirmation: X123
+    items:
+      - event:
+          travelers:
+            - Simon
+          record:
+            provider: Xcaret
+            start_date_time: "2022-06-27T09:00:00"
+            start_location: Xcaret Park
+            end_date_time: "2022-06-27T17:00:00"
+items:
+  - flight:
+      number: 594
+      record:
+        provider: DL
+        start_date_time: "2022-06-24T07:15:00"
+        start_location: SEA
+        end_date_time: "2022-06-24T15:03:00"
+        end_location: CUN
+  - flight:
+      number: 1203
+      travelers:
+        - Alice
+      record:
+        provider: DL
+        start_date_time: "2022-06-25T07:15:00"
+        start_location: SEA
+        end_date_time: "2022-06-25T15:03:00"
+        end_location: CUN
+"##,
+        )
+        .unwrap();
+        assert_eq!(
+            itinerary.traveler("Alice").unwrap().seat_preference(),
+            Some(&SeatPreference::Window)
+        );
+        assert_eq!(itinerary.items_for_traveler("Simon").count(), 2);
+
+        let alice = itinerary.for_traveler("Alice").unwrap();
+        assert_eq!(
+            alice
+                .travelers()
+                .map(|v| v.name().clone())
+                .collect::<Vec<String>>(),
+            vec!["Alice".to_string()]
+        );
+        assert_eq!(alice.bookings().count(), 0);
+        assert_eq!(
+            alice
+                .items()
+                .map(|v| v.id().unwrap().to_string())
+                .collect::<Vec<String>>(),
+            vec![
+                "flight-dl594-20220624".to_string(),
+                "flight-dl1203-20220625".to_string()
+            ]
+        );
+
+        assert!(itinerary.for_traveler("Bob").is_none());
+    }
+}
@@ -0,0 +1,623 @@
+/*!
+Checks for itineraries that can be read from a file but do not make sense.
+
+Reading an itinerary only checks that it has the right shape; `Itinerary::validate` then checks
+its content and returns a list of diagnostics, each with a severity, the rule it breaks, the item
+(if any) it refers to, and a message. Errors are data that is certainly wrong, such as a flight
+with no arrival airport or one that arrives before it departs; warnings are data that is probably
+wrong or incomplete; and information notes things that limit what can be shown, such as an
+unknown time zone.
+ */
+
+use_required!();
+use crate::itinerary::location::Location;
+use crate::itinerary::{Item, ItemId, Itinerary, TimeZoneLocation, TravelRecord};
+use std::collections::HashSet;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+}
+
+///
+/// The rules an itinerary is checked against; each has a short, stable, code.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rule {
+    /// A flight, rail leg, or transport has no arrival location.
+    MissingArrival,
+    /// An item ends before it starts.
+    EndsBeforeStart,
+    /// A flight that is not part of a journey has no record locator.
+    MissingRecordLocator,
+    /// An endpoint of an item has no time zone, and none could be inferred.
+    MissingTimeZone,
+    /// More than one item has the same identifier.
+    DuplicateItemId,
+    /// More than one traveler has the same name.
+    DuplicateTraveler,
+    /// An item, or a flight's passenger details, names a traveler not in the itinerary.
+    UnknownTraveler,
+    /// An item links to an identifier that is not in the itinerary.
+    UnknownLinkTarget,
+    /// A journey segment, or rail leg, departs from somewhere other than where the previous one
+    /// arrived.
+    Disconnected,
+    /// A journey segment, rail leg, or port call starts before the previous one ended.
+    OutOfOrder,
+    /// A booking contains no items.
+    EmptyBooking,
+}
+
+///
+/// A single problem found by `Itinerary::validate`.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    severity: Severity,
+    rule: Rule,
+    item: Option<ItemId>,
+    message: String,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Check `itinerary`, returning diagnostics in item order; see `Itinerary::validate`.
+///
+pub fn validate(itinerary: &Itinerary) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    let mut names = HashSet::new();
+    for traveler in itinerary.travelers() {
+        if !names.insert(traveler.name()) {
+            diagnostics.push(Diagnostic::new(
+                Severity::Error,
+                Rule::DuplicateTraveler,
+                None,
+                format!("more than one traveler named '{}'", traveler.name()),
+            ));
+        }
+    }
+
+    for booking in itinerary.bookings() {
+        if booking.items().next().is_none() {
+            diagnostics.push(Diagnostic::new(
+                Severity::Warning,
+                Rule::EmptyBooking,
+                None,
+                format!("booking {} has no items", booking.confirmation()),
+            ));
+        }
+    }
+
+    let mut ids = HashSet::new();
+    for item in itinerary.items() {
+        let mut check = Checker {
+            item: item.id(),
+            diagnostics: &mut diagnostics,
+        };
+        if let Some(id) = item.id() {
+            if !ids.insert(id) {
+                check.push(
+                    Severity::Error,
+                    Rule::DuplicateItemId,
+                    format!("more than one item with identifier '{}'", id),
+                );
+            }
+        }
+        for name in item.common().travelers() {
+            check.traveler(itinerary, name);
+        }
+        for link in item.links() {
+            if !itinerary.contains_item(link.target()) {
+                check.push(
+                    Severity::Error,
+                    Rule::UnknownLinkTarget,
+                    format!(
+                        "links to '{}', which is not in this itinerary",
+                        link.target()
+                    ),
+                );
+            }
+        }
+        check.item(itinerary, item);
+    }
+
+    diagnostics
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+struct Checker<'a> {
+    item: Option<&'a ItemId>,
+    diagnostics: &'a mut Vec<Diagnostic>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Severity::Error => "error",
+                Severity::Warning => "warning",
+                Severity::Info => "info",
+            }
+        )
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for Rule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+impl Rule {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Rule::MissingArrival => "missing-arrival",
+            Rule::EndsBeforeStart => "ends-before-start",
+            Rule::MissingRecordLocator => "missing-record-locator",
+            Rule::MissingTimeZone => "missing-time-zone",
+            Rule::DuplicateItemId => "duplicate-item-id",
+            Rule::DuplicateTraveler => "duplicate-traveler",
+            Rule::UnknownTraveler => "unknown-traveler",
+            Rule::UnknownLinkTarget => "unknown-link-target",
+            Rule::Disconnected => "disconnected",
+            Rule::OutOfOrder => "out-of-order",
+            Rule::EmptyBooking => "empty-booking",
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.item {
+            Some(item) => write!(
+                f,
+                "{}[{}] {}: {}",
+                self.severity, self.rule, item, self.message
+            ),
+            None => write!(f, "{}[{}] {}", self.severity, self.rule, self.message),
+        }
+    }
+}
+
+impl Diagnostic {
+    fn new(severity: Severity, rule: Rule, item: Option<&ItemId>, message: String) -> Self {
+        Self {
+            severity,
+            rule,
+            item: item.cloned(),
+            message,
+        }
+    }
+
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+
+    pub fn rule(&self) -> Rule {
+        self.rule
+    }
+
+    ///
+    /// The item this diagnostic refers to, if it is not about the itinerary as a whole.
+    ///
+    pub fn item(&self) -> Option<&ItemId> {
+        self.item.as_ref()
+    }
+
+    pub fn message(&self) -> &String {
+        &self.message
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Checker<'_> {
+    fn push(&mut self, severity: Severity, rule: Rule, message: String) {
+        self.diagnostics
+            .push(Diagnostic::new(severity, rule, self.item, message));
+    }
+
+    fn traveler(&mut self, itinerary: &Itinerary, name: &str) {
+        if itinerary.traveler(name).is_none() {
+            self.push(
+                Severity::Error,
+                Rule::UnknownTraveler,
+                format!("no traveler named '{}' in this itinerary", name),
+            );
+        }
+    }
+
+    fn item(&mut self, itinerary: &Itinerary, item: &Item) {
+        match item {
+            Item::Flight(v) => {
+                self.flight(itinerary, v);
+                if v.record_locator().is_none() {
+                    self.push(
+                        Severity::Warning,
+                        Rule::MissingRecordLocator,
+                        format!("flight {} has no record locator", v.flight_number_string()),
+                    );
+                }
+            }
+            Item::Journey(v) => {
+                v.segments()
+                    .for_each(|segment| self.flight(itinerary, segment));
+                for connection in v.connections() {
+                    let (arriving, departing) = (connection.arriving(), connection.departing());
+                    if arriving
+                        .arrival_airport()
+                        .map(|airport| airport != departing.departure_airport())
+                        .unwrap_or_default()
+                    {
+                        self.push(
+                            Severity::Error,
+                            Rule::Disconnected,
+                            format!(
+                                "flight {} departs {}, not where flight {} arrives",
+                                departing.flight_number_string(),
+                                departing.departure_airport(),
+                                arriving.flight_number_string()
+                            ),
+                        );
+                    }
+                    if connection.layover() < crate::itinerary::Duration::zero() {
+                        self.push(
+                            Severity::Error,
+                            Rule::OutOfOrder,
+                            format!(
+                                "flight {} departs before flight {} arrives",
+                                departing.flight_number_string(),
+                                arriving.flight_number_string()
+                            ),
+                        );
+                    }
+                }
+            }
+            Item::Rail(v) => {
+                for leg in v.legs() {
+                    self.record(&format!("train {}", leg.train_string()), leg.inner(), false);
+                    if leg.arrival_station().is_none() {
+                        self.missing_arrival(&format!("train {}", leg.train_string()));
+                    }
+                }
+                for change in v.changes() {
+                    let (arriving, departing) = (change.arriving(), change.departing());
+                    if arriving
+                        .arrival_station()
+                        .map(|station| !is_same_station(station, departing.departure_station()))
+                        .unwrap_or_default()
+                    {
+                        self.push(
+                            Severity::Error,
+                            Rule::Disconnected,
+                            format!(
+                                "train {} departs {}, not where train {} arrives",
+                                departing.train_string(),
+                                departing.departure_station(),
+                                arriving.train_string()
+                            ),
+                        );
+                    }
+                    if change.change_time() < crate::itinerary::Duration::zero() {
+                        self.push(
+                            Severity::Error,
+                            Rule::OutOfOrder,
+                            format!(
+                                "train {} departs before train {} arrives",
+                                change.departing().train_string(),
+                                change.arriving().train_string()
+                            ),
+                        );
+                    }
+                }
+            }
+            Item::Cruise(v) => {
+                self.record(&format!("cruise on {}", v.ship()), v.inner(), false);
+                let mut previous = v.embarkation_date_time();
+                for port_call in v.port_calls() {
+                    if port_call.arrival_date_time() < previous
+                        || port_call.departure_date_time() < port_call.arrival_date_time()
+                    {
+                        self.push(
+                            Severity::Error,
+                            Rule::OutOfOrder,
+                            format!("the call at {} is out of order", port_call.port()),
+                        );
+                    }
+                    previous = port_call.departure_date_time();
+                }
+                if v.disembarkation_date_time() < previous {
+                    self.push(
+                        Severity::Error,
+                        Rule::OutOfOrder,
+                        "disembarkation is before the last port call".to_string(),
+                    );
+                }
+            }
+            Item::Transport(v) => {
+                self.record("transport", v.inner(), true);
+                if v.arrival_address().is_none() {
+                    self.missing_arrival("transport");
+                }
+            }
+            Item::Vehicle(v) => self.record("vehicle rental", v.inner(), true),
+            Item::Accomodation(v) => self.record("stay", v.inner(), true),
+            Item::Event(v) => self.record("event", v.inner(), true),
+        }
+    }
+
+    fn flight(&mut self, itinerary: &Itinerary, flight: &crate::itinerary::FlightSegment) {
+        let what = format!("flight {}", flight.flight_number_string());
+        self.record(&what, flight.inner(), false);
+        if flight.arrival_airport().is_none() {
+            self.missing_arrival(&what);
+        }
+        for passenger in flight.passengers() {
+            self.traveler(itinerary, passenger.traveler());
+        }
+    }
+
+    fn missing_arrival(&mut self, what: &str) {
+        self.push(
+            Severity::Error,
+            Rule::MissingArrival,
+            format!("{} has no arrival location", what),
+        );
+    }
+
+    ///
+    /// Check a record ends after it starts. If either end has no time zone this can only be
+    /// known for certain where both ends are assumed to share a zone; otherwise a record that
+    /// appears to end first, in local time, is only a warning.
+    ///
+    fn record<P, L: TimeZoneLocation>(
+        &mut self,
+        what: &str,
+        record: &TravelRecord<P, L>,
+        shared_time_zone: bool,
+    ) {
+        match (record.start_instant(), record.end_instant()) {
+            (Some(start), Some(end)) => {
+                if end < start {
+                    self.ends_before_start(Severity::Error, what);
+                }
+            }
+            _ => {
+                if record.end_date_time < record.start_date_time {
+                    self.ends_before_start(
+                        if shared_time_zone {
+                            Severity::Error
+                        } else {
+                            Severity::Warning
+                        },
+                        what,
+                    );
+                }
+                self.push(
+                    Severity::Info,
+                    Rule::MissingTimeZone,
+                    format!(
+                        "the time zone of {} is unknown, durations may be wrong",
+                        what
+                    ),
+                );
+            }
+        }
+    }
+
+    fn ends_before_start(&mut self, severity: Severity, what: &str) {
+        self.push(
+            severity,
+            Rule::EndsBeforeStart,
+            format!("{} ends before it starts", what),
+        );
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Stations are the same if they have the same station code or, where either has no code, the
+/// same name ignoring case.
+///
+fn is_same_station(a: &Location, b: &Location) -> bool {
+    match (a.station(), b.station()) {
+        (Some(a), Some(b)) => a.eq_ignore_ascii_case(b),
+        _ => a.name().trim().to_lowercase() == b.name().trim().to_lowercase(),
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::{Rule, Severity};
+    use crate::itinerary::io::from_str;
+    use crate::itinerary::Itinerary;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_valid_itinerary() {
+        let itinerary = from_str(
+            r##"---
+identifier: 2022-06
+name: Playa del Carmen
+travelers:
+  - Simon
+items:
+  - flight:
+      number: 594
+      record:
+        provider: DL
+        confirmation: GROVQY
+        start_date_time: "2022-06-24T07:15:00"
+        start_location: SEA
+        end_date_time: "2022-06-24T15:03:00"
+        end_location: CUN
+  - accomodation:
+      travelers:
+        - Simon
+      record:
+        provider: Hotel Xcaret
+        start_date_time: "2022-06-24T16:00:00"
+        start_location: Cancun
+        end_date_time: "2022-07-01T11:00:00"
+"##,
+        )
+        .unwrap();
+        assert_eq!(itinerary.validate(), vec![]);
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        let itinerary = from_str(
+            r##"---
+identifier: 2022-06
+name: Playa del Carmen
+travelers:
+  - Simon
+items:
+  - flight:
+      number: 594
+      record:
+        provider: DL
+        start_date_time: "2022-06-24T07:15:00"
+        start_location: SEA
+        end_date_time: "2022-06-24T15:03:00"
+  - accomodation:
+      id: hotel
+      travelers:
+        - Jane
+      links:
+        - related: shuttle
+      record:
+        provider: Hotel Xcaret
+        start_date_time: "2022-07-01T16:00:00"
+        start_location: Playa del Carmen
+        end_date_time: "2022-06-24T11:00:00"
+  - event:
+      id: hotel
+      record:
+        provider: Xcaret Park
+        start_date_time: "2022-06-25T09:00:00"
+        start_location: Xcaret Park
+        end_date_time: "2022-06-25T17:00:00"
+"##,
+        )
+        .unwrap();
+        let diagnostics = itinerary.validate();
+        let summary: Vec<(Severity, Rule, String)> = diagnostics
+            .iter()
+            .map(|v| {
+                (
+                    v.severity(),
+                    v.rule(),
+                    v.item().map(|v| v.to_string()).unwrap_or_default(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    Severity::Error,
+                    Rule::MissingArrival,
+                    "flight-dl594-20220624".to_string()
+                ),
+                (
+                    Severity::Warning,
+                    Rule::MissingRecordLocator,
+                    "flight-dl594-20220624".to_string()
+                ),
+                (Severity::Error, Rule::UnknownTraveler, "hotel".to_string()),
+                (
+                    Severity::Error,
+                    Rule::UnknownLinkTarget,
+                    "hotel".to_string()
+                ),
+                (Severity::Error, Rule::EndsBeforeStart, "hotel".to_string()),
+                (Severity::Info, Rule::MissingTimeZone, "hotel".to_string()),
+                (Severity::Error, Rule::DuplicateItemId, "hotel".to_string()),
+                (Severity::Info, Rule::MissingTimeZone, "hotel".to_string()),
+            ]
+        );
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "error[missing-arrival] flight-dl594-20220624: flight DL594 has no arrival location"
+        );
+    }
+
+    #[test]
+    fn test_rail_continuity() {
+        let rail = |change_at: &str| {
+            from_str(&format!(
+                r##"---
+identifier: 2023-05
+name: Paris to Munich
+travelers: []
+items:
+  - rail:
+      legs:
+        - train_number: TGV 9577
+          record:
+            provider: SNCF
+            start_date_time: "2023-05-12T09:55:00"
+            start_location: Paris Est
+            end_date_time: "2023-05-12T13:04:00"
+            end_location: Stuttgart Hbf
+        - train_number: ICE 591
+          record:
+            provider: DB
+            start_date_time: "2023-05-12T13:51:00"
+            start_location: {}
+            end_date_time: "2023-05-12T16:08:00"
+            end_location: Munich Hbf
+"##,
+                change_at
+            ))
+            .unwrap()
+        };
+        let disconnected = |itinerary: Itinerary| -> Vec<String> {
+            itinerary
+                .validate()
+                .iter()
+                .filter(|v| v.rule() == Rule::Disconnected)
+                .map(|v| v.to_string())
+                .collect()
+        };
+        assert!(disconnected(rail("stuttgart hbf")).is_empty());
+        assert_eq!(disconnected(rail("Karlsruhe Hbf")).len(), 1);
+    }
+}
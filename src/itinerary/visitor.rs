@@ -4,8 +4,16 @@ More detailed description, with
 # Example
  */
 
-use super::{Accomodation, Event, Flight, Itinerary, Transport, Vehicle};
+use super::{
+    Accomodation, Booking, Connection, Cruise, Date, DateTime, Duration, Event, Flight, Instant,
+    Item, Itinerary, Journey, PortCall, Rail, RailChange, Transport, Vehicle,
+};
 use crate::error::Result;
+use crate::itinerary::annotation::Annotations;
+use crate::itinerary::filter::ItemFilter;
+use crate::itinerary::metadata::{Metadata, Summary};
+use crate::itinerary::traveler::Traveler;
+use chrono::TimeZone;
 
 // ------------------------------------------------------------------------------------------------
 // Public Types
@@ -25,7 +33,30 @@ pub trait ItineraryVisitor {
         Ok(())
     }
 
-    fn traveler(&self, name: &str) -> Result<()> {
+    ///
+    /// Called after the name, with the itinerary's metadata and the summary derived from the
+    /// items being visited.
+    ///
+    fn summary(&self, metadata: &Metadata, summary: &Summary) -> Result<()> {
+        Ok(())
+    }
+
+    fn traveler(&self, traveler: &Traveler) -> Result<()> {
+        Ok(())
+    }
+
+    ///
+    /// Called after the travelers, if the itinerary has any notes, tags, or attachments.
+    ///
+    fn annotations(&self, annotations: &Annotations) -> Result<()> {
+        Ok(())
+    }
+
+    fn booking_start(&self, booking: &Booking) -> Result<()> {
+        Ok(())
+    }
+
+    fn booking_end(&self, booking: &Booking) -> Result<()> {
         Ok(())
     }
 
@@ -37,6 +68,46 @@ pub trait ItineraryVisitor {
         Ok(())
     }
 
+    fn journey_departs(&self, journey: &Journey) -> Result<()> {
+        Ok(())
+    }
+
+    fn journey_connection(&self, journey: &Journey, connection: &Connection<'_>) -> Result<()> {
+        Ok(())
+    }
+
+    fn journey_arrives(&self, journey: &Journey) -> Result<()> {
+        Ok(())
+    }
+
+    fn rail_departs(&self, rail: &Rail) -> Result<()> {
+        Ok(())
+    }
+
+    fn rail_change(&self, rail: &Rail, change: &RailChange<'_>) -> Result<()> {
+        Ok(())
+    }
+
+    fn rail_arrives(&self, rail: &Rail) -> Result<()> {
+        Ok(())
+    }
+
+    fn cruise_embarks(&self, cruise: &Cruise) -> Result<()> {
+        Ok(())
+    }
+
+    fn cruise_port_arrival(&self, cruise: &Cruise, port_call: &PortCall) -> Result<()> {
+        Ok(())
+    }
+
+    fn cruise_port_departure(&self, cruise: &Cruise, port_call: &PortCall) -> Result<()> {
+        Ok(())
+    }
+
+    fn cruise_disembarks(&self, cruise: &Cruise) -> Result<()> {
+        Ok(())
+    }
+
     fn transport_departs(&self, transport: &Transport) -> Result<()> {
         Ok(())
     }
@@ -69,17 +140,333 @@ pub trait ItineraryVisitor {
         Ok(())
     }
 
+    ///
+    /// Called immediately after the start of an item (`flight_departs`, `event_start`, etc.) if
+    /// the item has any notes, tags, or attachments.
+    ///
+    fn item_annotations(&self, item: &Item, annotations: &Annotations) -> Result<()> {
+        Ok(())
+    }
+
+    ///
+    /// Called, in `VisitOrder::Timing` only, before anything that happens on `date` in the
+    /// traveler's local time. Every day from the start of the first item to the end of the last
+    /// is reported, whether or not anything happens on it.
+    ///
+    fn day_start(&self, date: &Date) -> Result<()> {
+        Ok(())
+    }
+
+    ///
+    /// Called, in `VisitOrder::Timing` only, after everything that happens on `date`.
+    ///
+    fn day_end(&self, date: &Date) -> Result<()> {
+        Ok(())
+    }
+
+    ///
+    /// Called, in `VisitOrder::Timing` only, before an item that starts some time after every
+    /// earlier item has ended, unless the gap is a `layover`. It is reported within the day the
+    /// gap ends, after that day's `day_start`.
+    ///
+    fn idle_gap(&self, gap: &Gap) -> Result<()> {
+        Ok(())
+    }
+
+    ///
+    /// As `idle_gap`, but where the item before the gap and the item after it are both
+    /// transportation; a flight, journey, rail ticket, cruise, or transport.
+    ///
+    fn layover(&self, gap: &Gap) -> Result<()> {
+        Ok(())
+    }
+
+    ///
+    /// Called after `idle_gap` or `layover` if the gap spans one or more nights, that is nights
+    /// with no accomodation or other item in progress.
+    ///
+    fn unaccounted_nights(&self, gap: &Gap) -> Result<()> {
+        Ok(())
+    }
+
     fn end(&self) -> Result<()> {
         Ok(())
     }
+
+    ///
+    /// Cancelled items are not visited unless this returns `true`.
+    ///
+    fn include_cancelled(&self) -> bool {
+        false
+    }
 }
 
+///
+/// The same callbacks as `ItineraryVisitor` but each may modify the visitor, so that state can be
+/// collected without interior mutability. Any `&V` where `V: ItineraryVisitor` is also an
+/// `ItineraryVisitorMut`.
+///
+#[allow(unused_variables)]
+pub trait ItineraryVisitorMut {
+    fn start(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn identifier(&mut self, id: &str) -> Result<()> {
+        Ok(())
+    }
+
+    fn name(&mut self, name: &str) -> Result<()> {
+        Ok(())
+    }
+
+    ///
+    /// Called after the name, with the itinerary's metadata and the summary derived from the
+    /// items being visited.
+    ///
+    fn summary(&mut self, metadata: &Metadata, summary: &Summary) -> Result<()> {
+        Ok(())
+    }
+
+    fn traveler(&mut self, traveler: &Traveler) -> Result<()> {
+        Ok(())
+    }
+
+    ///
+    /// Called after the travelers, if the itinerary has any notes, tags, or attachments.
+    ///
+    fn annotations(&mut self, annotations: &Annotations) -> Result<()> {
+        Ok(())
+    }
+
+    fn booking_start(&mut self, booking: &Booking) -> Result<()> {
+        Ok(())
+    }
+
+    fn booking_end(&mut self, booking: &Booking) -> Result<()> {
+        Ok(())
+    }
+
+    fn flight_departs(&mut self, flight: &Flight) -> Result<()> {
+        Ok(())
+    }
+
+    fn flight_arrives(&mut self, flight: &Flight) -> Result<()> {
+        Ok(())
+    }
+
+    fn journey_departs(&mut self, journey: &Journey) -> Result<()> {
+        Ok(())
+    }
+
+    fn journey_connection(&mut self, journey: &Journey, connection: &Connection<'_>) -> Result<()> {
+        Ok(())
+    }
+
+    fn journey_arrives(&mut self, journey: &Journey) -> Result<()> {
+        Ok(())
+    }
+
+    fn rail_departs(&mut self, rail: &Rail) -> Result<()> {
+        Ok(())
+    }
+
+    fn rail_change(&mut self, rail: &Rail, change: &RailChange<'_>) -> Result<()> {
+        Ok(())
+    }
+
+    fn rail_arrives(&mut self, rail: &Rail) -> Result<()> {
+        Ok(())
+    }
+
+    fn cruise_embarks(&mut self, cruise: &Cruise) -> Result<()> {
+        Ok(())
+    }
+
+    fn cruise_port_arrival(&mut self, cruise: &Cruise, port_call: &PortCall) -> Result<()> {
+        Ok(())
+    }
+
+    fn cruise_port_departure(&mut self, cruise: &Cruise, port_call: &PortCall) -> Result<()> {
+        Ok(())
+    }
+
+    fn cruise_disembarks(&mut self, cruise: &Cruise) -> Result<()> {
+        Ok(())
+    }
+
+    fn transport_departs(&mut self, transport: &Transport) -> Result<()> {
+        Ok(())
+    }
+
+    fn transport_arrives(&mut self, transport: &Transport) -> Result<()> {
+        Ok(())
+    }
+
+    fn vehicle_pickup(&mut self, vehicle: &Vehicle) -> Result<()> {
+        Ok(())
+    }
+
+    fn vehicle_dropoff(&mut self, vehicle: &Vehicle) -> Result<()> {
+        Ok(())
+    }
+
+    fn accomodation_checkin(&mut self, accomodation: &Accomodation) -> Result<()> {
+        Ok(())
+    }
+
+    fn accomodation_checkout(&mut self, accomodation: &Accomodation) -> Result<()> {
+        Ok(())
+    }
+
+    fn event_start(&mut self, event: &Event) -> Result<()> {
+        Ok(())
+    }
+
+    fn event_end(&mut self, event: &Event) -> Result<()> {
+        Ok(())
+    }
+
+    ///
+    /// Called immediately after the start of an item (`flight_departs`, `event_start`, etc.) if
+    /// the item has any notes, tags, or attachments.
+    ///
+    fn item_annotations(&mut self, item: &Item, annotations: &Annotations) -> Result<()> {
+        Ok(())
+    }
+
+    ///
+    /// Called, in `VisitOrder::Timing` only, before anything that happens on `date` in the
+    /// traveler's local time. Every day from the start of the first item to the end of the last
+    /// is reported, whether or not anything happens on it.
+    ///
+    fn day_start(&mut self, date: &Date) -> Result<()> {
+        Ok(())
+    }
+
+    ///
+    /// Called, in `VisitOrder::Timing` only, after everything that happens on `date`.
+    ///
+    fn day_end(&mut self, date: &Date) -> Result<()> {
+        Ok(())
+    }
+
+    ///
+    /// Called, in `VisitOrder::Timing` only, before an item that starts some time after every
+    /// earlier item has ended, unless the gap is a `layover`. It is reported within the day the
+    /// gap ends, after that day's `day_start`.
+    ///
+    fn idle_gap(&mut self, gap: &Gap) -> Result<()> {
+        Ok(())
+    }
+
+    ///
+    /// As `idle_gap`, but where the item before the gap and the item after it are both
+    /// transportation; a flight, journey, rail ticket, cruise, or transport.
+    ///
+    fn layover(&mut self, gap: &Gap) -> Result<()> {
+        Ok(())
+    }
+
+    ///
+    /// Called after `idle_gap` or `layover` if the gap spans one or more nights, that is nights
+    /// with no accomodation or other item in progress.
+    ///
+    fn unaccounted_nights(&mut self, gap: &Gap) -> Result<()> {
+        Ok(())
+    }
+
+    fn end(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    ///
+    /// Cancelled items are not visited unless this returns `true`.
+    ///
+    fn include_cancelled(&self) -> bool {
+        false
+    }
+}
+
+///
+/// A visitor that computes a value from the itinerary it visits. The traversal calls `finish`
+/// after `ItineraryVisitorMut::end`, and returns the value it produces.
+///
+pub trait ItineraryFolder: ItineraryVisitorMut {
+    type Output;
+
+    fn finish(self) -> Result<Self::Output>;
+}
+
+///
+/// The time between one item ending and another starting, when nothing else is in progress.
+/// `from` is in the local time of the item that ended and `to` in that of the item that starts.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Gap {
+    from: DateTime,
+    to: DateTime,
+    duration: Duration,
+}
+
+///
+/// The order in which the items of an itinerary are presented to a visitor.
+///
+#[derive(Debug, Default)]
 pub enum VisitOrder {
+    /// Chronological; the start and end of every item are interleaved by the time they occur.
+    /// Where two occur at the same moment the end of one item comes before the start of another,
+    /// and otherwise the order of items in the itinerary is preserved. Only this order reports
+    /// day boundaries and the gaps between items.
     Timing,
+    /// Grouped by booking, ordered by agency and then confirmation; the items of each booking
+    /// are visited between calls to `booking_start` and `booking_end`. Items that are not part
+    /// of a booking are visited last, in itinerary order.
     Booking,
+    /// The order items appear in the itinerary, each item's start followed by its end (and for a
+    /// journey its connections in between).
+    #[default]
     AsIs,
 }
 
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+/// When, rank (ends before starts), item index, and edge index within the item.
+type TimingKey = (Instant, u8, usize, usize);
+
+///
+/// A single callback to be made to a visitor; traversals are planned as a list of these so that
+/// every kind of visitor is presented with the same sequence.
+///
+#[derive(Debug)]
+pub(crate) enum Visit<'a> {
+    Identifier(&'a str),
+    Name(&'a str),
+    Summary(&'a Metadata, Summary),
+    Traveler(&'a Traveler),
+    Annotations(&'a Annotations),
+    BookingStart(&'a Booking),
+    BookingEnd(&'a Booking),
+    Item(&'a Item, ItemEdge),
+    ItemAnnotations(&'a Item),
+    DayStart(Date),
+    DayEnd(Date),
+    IdleGap(Gap),
+    Layover(Gap),
+    UnaccountedNights(Gap),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ItemEdge {
+    Start,
+    Connection(usize),
+    PortArrival(usize),
+    PortDeparture(usize),
+    End,
+}
+
 // ------------------------------------------------------------------------------------------------
 // Public Functions
 // ------------------------------------------------------------------------------------------------
@@ -92,56 +479,1453 @@ pub fn visit_ordered_itinerary(
     itinerary: &Itinerary,
     visitor: &impl ItineraryVisitor,
     order: VisitOrder,
+) -> Result<()> {
+    visit_ordered_itinerary_mut(itinerary, &mut &*visitor, order)
+}
+
+///
+/// As `visit_ordered_itinerary` but only items selected by `filter` are visited; a booking none
+/// of whose items are selected is not visited either.
+///
+pub fn visit_filtered_itinerary(
+    itinerary: &Itinerary,
+    visitor: &impl ItineraryVisitor,
+    order: VisitOrder,
+    filter: &ItemFilter,
+) -> Result<()> {
+    visit_filtered_itinerary_mut(itinerary, &mut &*visitor, order, filter)
+}
+
+pub fn visit_itinerary_mut(
+    itinerary: &Itinerary,
+    visitor: &mut impl ItineraryVisitorMut,
+) -> Result<()> {
+    visit_ordered_itinerary_mut(itinerary, visitor, VisitOrder::default())
+}
+
+pub fn visit_ordered_itinerary_mut(
+    itinerary: &Itinerary,
+    visitor: &mut impl ItineraryVisitorMut,
+    order: VisitOrder,
+) -> Result<()> {
+    visit_filtered_itinerary_mut(itinerary, visitor, order, &ItemFilter::default())
+}
+
+pub fn visit_filtered_itinerary_mut(
+    itinerary: &Itinerary,
+    visitor: &mut impl ItineraryVisitorMut,
+    order: VisitOrder,
+    filter: &ItemFilter,
 ) -> Result<()> {
     visitor.start()?;
+    visit_planned(itinerary, visitor, order, filter)?;
+    visitor.end()
+}
+
+pub fn fold_itinerary<F: ItineraryFolder>(itinerary: &Itinerary, folder: F) -> Result<F::Output> {
+    fold_ordered_itinerary(itinerary, folder, VisitOrder::default())
+}
 
-    visitor.identifier(itinerary.identifier())?;
-    visitor.name(itinerary.name())?;
+pub fn fold_ordered_itinerary<F: ItineraryFolder>(
+    itinerary: &Itinerary,
+    folder: F,
+    order: VisitOrder,
+) -> Result<F::Output> {
+    fold_filtered_itinerary(itinerary, folder, order, &ItemFilter::default())
+}
+
+pub fn fold_filtered_itinerary<F: ItineraryFolder>(
+    itinerary: &Itinerary,
+    mut folder: F,
+    order: VisitOrder,
+    filter: &ItemFilter,
+) -> Result<F::Output> {
+    folder.start()?;
+    visit_planned(itinerary, &mut folder, order, filter)?;
+    folder.end()?;
+    folder.finish()
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Gap {
+    pub fn from(&self) -> &DateTime {
+        &self.from
+    }
+
+    pub fn to(&self) -> &DateTime {
+        &self.to
+    }
 
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    ///
+    /// The number of midnights, in local time, between `from` and `to`.
+    ///
+    pub fn nights(&self) -> i64 {
+        (self.to.date() - self.from.date()).num_days().max(0)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl<V: ItineraryVisitor + ?Sized> ItineraryVisitorMut for &V {
+    fn start(&mut self) -> Result<()> {
+        (**self).start()
+    }
+
+    fn identifier(&mut self, id: &str) -> Result<()> {
+        (**self).identifier(id)
+    }
+
+    fn name(&mut self, name: &str) -> Result<()> {
+        (**self).name(name)
+    }
+
+    fn summary(&mut self, metadata: &Metadata, summary: &Summary) -> Result<()> {
+        (**self).summary(metadata, summary)
+    }
+
+    fn traveler(&mut self, traveler: &Traveler) -> Result<()> {
+        (**self).traveler(traveler)
+    }
+
+    fn annotations(&mut self, annotations: &Annotations) -> Result<()> {
+        (**self).annotations(annotations)
+    }
+
+    fn booking_start(&mut self, booking: &Booking) -> Result<()> {
+        (**self).booking_start(booking)
+    }
+
+    fn booking_end(&mut self, booking: &Booking) -> Result<()> {
+        (**self).booking_end(booking)
+    }
+
+    fn flight_departs(&mut self, flight: &Flight) -> Result<()> {
+        (**self).flight_departs(flight)
+    }
+
+    fn flight_arrives(&mut self, flight: &Flight) -> Result<()> {
+        (**self).flight_arrives(flight)
+    }
+
+    fn journey_departs(&mut self, journey: &Journey) -> Result<()> {
+        (**self).journey_departs(journey)
+    }
+
+    fn journey_connection(&mut self, journey: &Journey, connection: &Connection<'_>) -> Result<()> {
+        (**self).journey_connection(journey, connection)
+    }
+
+    fn journey_arrives(&mut self, journey: &Journey) -> Result<()> {
+        (**self).journey_arrives(journey)
+    }
+
+    fn rail_departs(&mut self, rail: &Rail) -> Result<()> {
+        (**self).rail_departs(rail)
+    }
+
+    fn rail_change(&mut self, rail: &Rail, change: &RailChange<'_>) -> Result<()> {
+        (**self).rail_change(rail, change)
+    }
+
+    fn rail_arrives(&mut self, rail: &Rail) -> Result<()> {
+        (**self).rail_arrives(rail)
+    }
+
+    fn cruise_embarks(&mut self, cruise: &Cruise) -> Result<()> {
+        (**self).cruise_embarks(cruise)
+    }
+
+    fn cruise_port_arrival(&mut self, cruise: &Cruise, port_call: &PortCall) -> Result<()> {
+        (**self).cruise_port_arrival(cruise, port_call)
+    }
+
+    fn cruise_port_departure(&mut self, cruise: &Cruise, port_call: &PortCall) -> Result<()> {
+        (**self).cruise_port_departure(cruise, port_call)
+    }
+
+    fn cruise_disembarks(&mut self, cruise: &Cruise) -> Result<()> {
+        (**self).cruise_disembarks(cruise)
+    }
+
+    fn transport_departs(&mut self, transport: &Transport) -> Result<()> {
+        (**self).transport_departs(transport)
+    }
+
+    fn transport_arrives(&mut self, transport: &Transport) -> Result<()> {
+        (**self).transport_arrives(transport)
+    }
+
+    fn vehicle_pickup(&mut self, vehicle: &Vehicle) -> Result<()> {
+        (**self).vehicle_pickup(vehicle)
+    }
+
+    fn vehicle_dropoff(&mut self, vehicle: &Vehicle) -> Result<()> {
+        (**self).vehicle_dropoff(vehicle)
+    }
+
+    fn accomodation_checkin(&mut self, accomodation: &Accomodation) -> Result<()> {
+        (**self).accomodation_checkin(accomodation)
+    }
+
+    fn accomodation_checkout(&mut self, accomodation: &Accomodation) -> Result<()> {
+        (**self).accomodation_checkout(accomodation)
+    }
+
+    fn event_start(&mut self, event: &Event) -> Result<()> {
+        (**self).event_start(event)
+    }
+
+    fn event_end(&mut self, event: &Event) -> Result<()> {
+        (**self).event_end(event)
+    }
+
+    fn item_annotations(&mut self, item: &Item, annotations: &Annotations) -> Result<()> {
+        (**self).item_annotations(item, annotations)
+    }
+
+    fn day_start(&mut self, date: &Date) -> Result<()> {
+        (**self).day_start(date)
+    }
+
+    fn day_end(&mut self, date: &Date) -> Result<()> {
+        (**self).day_end(date)
+    }
+
+    fn idle_gap(&mut self, gap: &Gap) -> Result<()> {
+        (**self).idle_gap(gap)
+    }
+
+    fn layover(&mut self, gap: &Gap) -> Result<()> {
+        (**self).layover(gap)
+    }
+
+    fn unaccounted_nights(&mut self, gap: &Gap) -> Result<()> {
+        (**self).unaccounted_nights(gap)
+    }
+
+    fn end(&mut self) -> Result<()> {
+        (**self).end()
+    }
+
+    fn include_cancelled(&self) -> bool {
+        (**self).include_cancelled()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Every callback, other than `start` and `end`, that a traversal makes; in the order it makes
+/// them.
+///
+pub(crate) fn plan_visits<'a>(
+    itinerary: &'a Itinerary,
+    order: VisitOrder,
+    filter: &ItemFilter,
+    include_cancelled: bool,
+) -> Vec<Visit<'a>> {
+    let include_cancelled = include_cancelled || filter.selects_cancelled();
+    let is_visible =
+        |item: &&Item| (include_cancelled || !item.common().is_cancelled()) && filter.matches(item);
+
+    let mut visits = vec![
+        Visit::Identifier(itinerary.identifier()),
+        Visit::Name(itinerary.name()),
+        Visit::Summary(itinerary.metadata(), itinerary.summary_of(is_visible)),
+    ];
+    visits.extend(itinerary.travelers().map(Visit::Traveler));
+    if !itinerary.annotations().is_empty() {
+        visits.push(Visit::Annotations(itinerary.annotations()));
+    }
     match order {
-        VisitOrder::Timing => todo!(),
-        VisitOrder::Booking => todo!(),
-        VisitOrder::AsIs => {
-            for item in itinerary.items() {
-                match item {
-                    super::Item::Flight(v) => {
-                        visitor.flight_departs(v)?;
-                        visitor.flight_arrives(v)?;
-                    }
-                    super::Item::Transport(v) => {
-                        visitor.transport_departs(v)?;
-                        visitor.transport_arrives(v)?;
+        VisitOrder::Timing => plan_timed_visits(itinerary, is_visible, &mut visits),
+        VisitOrder::Booking => {
+            let mut bookings: Vec<&Booking> = itinerary.bookings().collect();
+            bookings.sort_by_key(|booking| (booking.agency(), booking.confirmation()));
+            for booking in bookings {
+                let items: Vec<&Item> = booking.items().filter(is_visible).collect();
+                if !items.is_empty() {
+                    visits.push(Visit::BookingStart(booking));
+                    for item in items {
+                        plan_item_visits(item, &mut visits);
                     }
-                    super::Item::Vehicle(v) => {
-                        visitor.vehicle_pickup(v)?;
-                        visitor.vehicle_dropoff(v)?;
-                    }
-                    super::Item::Accomodation(v) => {
-                        visitor.accomodation_checkin(v)?;
-                        visitor.accomodation_checkout(v)?;
+                    visits.push(Visit::BookingEnd(booking));
+                }
+            }
+            for item in itinerary.unbooked_items().filter(is_visible) {
+                plan_item_visits(item, &mut visits);
+            }
+        }
+        VisitOrder::AsIs => {
+            for item in itinerary.items().filter(is_visible) {
+                plan_item_visits(item, &mut visits);
+            }
+        }
+    }
+    visits
+}
+
+///
+/// The callback for a single planned visit; this is shared by the synchronous traversal and, as
+/// `dispatch_visit!(visit, visitor, await)`, by the asynchronous one so that the two can not
+/// drift apart.
+///
+macro_rules! dispatch_visit {
+    (@ $visit:expr, $visitor:ident, $($await:tt)*) => {{
+        use $crate::itinerary::visitor::{ItemEdge, Visit};
+        use $crate::itinerary::Item;
+        match $visit {
+            Visit::Identifier(id) => $visitor.identifier(id) $($await)*,
+            Visit::Name(name) => $visitor.name(name) $($await)*,
+            Visit::Summary(metadata, summary) => $visitor.summary(metadata, summary) $($await)*,
+            Visit::Traveler(traveler) => $visitor.traveler(traveler) $($await)*,
+            Visit::Annotations(annotations) => $visitor.annotations(annotations) $($await)*,
+            Visit::BookingStart(booking) => $visitor.booking_start(booking) $($await)*,
+            Visit::BookingEnd(booking) => $visitor.booking_end(booking) $($await)*,
+            Visit::ItemAnnotations(item) => {
+                $visitor.item_annotations(item, item.common().annotations()) $($await)*
+            }
+            Visit::DayStart(date) => $visitor.day_start(date) $($await)*,
+            Visit::DayEnd(date) => $visitor.day_end(date) $($await)*,
+            Visit::IdleGap(gap) => $visitor.idle_gap(gap) $($await)*,
+            Visit::Layover(gap) => $visitor.layover(gap) $($await)*,
+            Visit::UnaccountedNights(gap) => $visitor.unaccounted_nights(gap) $($await)*,
+            Visit::Item(item, edge) => match (item, edge) {
+                (Item::Flight(v), ItemEdge::Start) => $visitor.flight_departs(v) $($await)*,
+                (Item::Flight(v), _) => $visitor.flight_arrives(v) $($await)*,
+                (Item::Journey(v), ItemEdge::Start) => $visitor.journey_departs(v) $($await)*,
+                (Item::Journey(v), ItemEdge::Connection(index)) => {
+                    let connections = v.connections();
+                    $visitor.journey_connection(v, &connections[*index]) $($await)*
+                }
+                (Item::Journey(v), _) => $visitor.journey_arrives(v) $($await)*,
+                (Item::Rail(v), ItemEdge::Start) => $visitor.rail_departs(v) $($await)*,
+                (Item::Rail(v), ItemEdge::Connection(index)) => {
+                    let changes = v.changes();
+                    $visitor.rail_change(v, &changes[*index]) $($await)*
+                }
+                (Item::Rail(v), _) => $visitor.rail_arrives(v) $($await)*,
+                (Item::Cruise(v), ItemEdge::Start) => $visitor.cruise_embarks(v) $($await)*,
+                (Item::Cruise(v), ItemEdge::PortArrival(index)) => {
+                    let port_call = v.port_calls().nth(*index).unwrap();
+                    $visitor.cruise_port_arrival(v, port_call) $($await)*
+                }
+                (Item::Cruise(v), ItemEdge::PortDeparture(index)) => {
+                    let port_call = v.port_calls().nth(*index).unwrap();
+                    $visitor.cruise_port_departure(v, port_call) $($await)*
+                }
+                (Item::Cruise(v), _) => $visitor.cruise_disembarks(v) $($await)*,
+                (Item::Transport(v), ItemEdge::Start) => $visitor.transport_departs(v) $($await)*,
+                (Item::Transport(v), _) => $visitor.transport_arrives(v) $($await)*,
+                (Item::Vehicle(v), ItemEdge::Start) => $visitor.vehicle_pickup(v) $($await)*,
+                (Item::Vehicle(v), _) => $visitor.vehicle_dropoff(v) $($await)*,
+                (Item::Accomodation(v), ItemEdge::Start) => {
+                    $visitor.accomodation_checkin(v) $($await)*
+                }
+                (Item::Accomodation(v), _) => $visitor.accomodation_checkout(v) $($await)*,
+                (Item::Event(v), ItemEdge::Start) => $visitor.event_start(v) $($await)*,
+                (Item::Event(v), _) => $visitor.event_end(v) $($await)*,
+            },
+        }
+    }};
+    ($visit:expr, $visitor:ident, await) => {
+        dispatch_visit!(@ $visit, $visitor, .await)
+    };
+    ($visit:expr, $visitor:ident) => {
+        dispatch_visit!(@ $visit, $visitor,)
+    };
+}
+
+pub(crate) use dispatch_visit;
+
+///
+/// Make the callback for a single planned visit.
+///
+pub(crate) fn make_visit(visit: &Visit<'_>, visitor: &mut impl ItineraryVisitorMut) -> Result<()> {
+    dispatch_visit!(visit, visitor)
+}
+
+fn visit_planned<V: ItineraryVisitorMut>(
+    itinerary: &Itinerary,
+    visitor: &mut V,
+    order: VisitOrder,
+    filter: &ItemFilter,
+) -> Result<()> {
+    for visit in plan_visits(itinerary, order, filter, visitor.include_cancelled()) {
+        make_visit(&visit, visitor)?;
+    }
+    Ok(())
+}
+
+///
+/// Plans the edges of every item chronologically, adding day boundaries and the gaps between
+/// items. The current day only moves forward, so an edge whose local date is earlier than that
+/// of an edge before it (such as arriving after crossing the date line eastward) is reported
+/// within the later day.
+///
+fn plan_timed_visits<'a>(
+    itinerary: &'a Itinerary,
+    is_visible: impl Fn(&&Item) -> bool,
+    visits: &mut Vec<Visit<'a>>,
+) {
+    let mut current_day: Option<Date> = None;
+    let mut in_progress = 0_usize;
+    let mut last_ended: Option<(&Item, Instant)> = None;
+    for (item, edge, instant) in timed_item_edges(itinerary, is_visible) {
+        let local = edge_times(item, edge).1;
+        let date = local.date();
+        match current_day {
+            None => {
+                visits.push(Visit::DayStart(date));
+                current_day = Some(date);
+            }
+            Some(current) if date > current => {
+                visits.push(Visit::DayEnd(current));
+                for day in current.iter_days().skip(1).take_while(|day| *day < date) {
+                    visits.push(Visit::DayStart(day));
+                    visits.push(Visit::DayEnd(day));
+                }
+                visits.push(Visit::DayStart(date));
+                current_day = Some(date);
+            }
+            _ => {}
+        }
+
+        if edge == ItemEdge::Start && in_progress == 0 {
+            if let Some((previous, ended)) = last_ended {
+                if instant > ended {
+                    let gap = Gap {
+                        from: *previous.end_date_time(),
+                        to: *local,
+                        duration: instant - ended,
+                    };
+                    let nights = gap.nights() > 0;
+                    if is_transportation(previous) && is_transportation(item) {
+                        visits.push(Visit::Layover(gap.clone()));
+                    } else {
+                        visits.push(Visit::IdleGap(gap.clone()));
                     }
-                    super::Item::Event(v) => {
-                        visitor.event_start(v)?;
-                        visitor.event_end(v)?;
+                    if nights {
+                        visits.push(Visit::UnaccountedNights(gap));
                     }
                 }
             }
         }
+
+        plan_item_edge_visits(item, edge, visits);
+
+        match edge {
+            ItemEdge::Start => in_progress += 1,
+            ItemEdge::End => {
+                in_progress = in_progress.saturating_sub(1);
+                last_ended = Some((item, instant));
+            }
+            _ => {}
+        }
     }
+    if let Some(current) = current_day {
+        visits.push(Visit::DayEnd(current));
+    }
+}
 
-    visitor.end()
+fn plan_item_visits<'a>(item: &'a Item, visits: &mut Vec<Visit<'a>>) {
+    for edge in item_edges(item) {
+        plan_item_edge_visits(item, edge, visits);
+    }
 }
 
-// ------------------------------------------------------------------------------------------------
-// Implementations
-// ------------------------------------------------------------------------------------------------
+fn plan_item_edge_visits<'a>(item: &'a Item, edge: ItemEdge, visits: &mut Vec<Visit<'a>>) {
+    visits.push(Visit::Item(item, edge));
+    if edge == ItemEdge::Start && !item.common().annotations().is_empty() {
+        visits.push(Visit::ItemAnnotations(item));
+    }
+}
+
+#[inline]
+fn is_transportation(item: &Item) -> bool {
+    matches!(
+        item,
+        Item::Flight(_) | Item::Journey(_) | Item::Rail(_) | Item::Cruise(_) | Item::Transport(_)
+    )
+}
+
+///
+/// Returns the start and end of every item, with the instant used to order it, ordered as
+/// described for `VisitOrder::Timing`.
+///
+fn timed_item_edges(
+    itinerary: &Itinerary,
+    is_visible: impl Fn(&&Item) -> bool,
+) -> Vec<(&Item, ItemEdge, Instant)> {
+    const END_RANK: u8 = 0;
+    const START_RANK: u8 = 1;
+
+    let item_edges: Vec<(usize, &Item, usize, ItemEdge)> = itinerary
+        .items()
+        .filter(is_visible)
+        .enumerate()
+        .flat_map(|(index, item)| {
+            item_edges(item)
+                .into_iter()
+                .enumerate()
+                .map(move |(edge_index, edge)| (index, item, edge_index, edge))
+        })
+        .collect();
+    let times: Vec<(Option<Instant>, &DateTime)> = item_edges
+        .iter()
+        .map(|(_, item, _, edge)| edge_times(item, *edge))
+        .collect();
 
-impl Default for VisitOrder {
-    fn default() -> Self {
-        Self::AsIs
+    let mut edges: Vec<(TimingKey, &Item, ItemEdge)> = Default::default();
+    let mut start = None;
+    for ((index, item, edge_index, edge), instant) in
+        item_edges.into_iter().zip(ordering_instants(&times))
+    {
+        // the start is always an item's first edge.
+        if edge == ItemEdge::Start {
+            start = Some(instant);
+        }
+        let start = start.unwrap_or(instant);
+        // an invalid item with an edge before its start is treated as happening at its start.
+        let instant = instant.max(start);
+        // an item that ends the moment it starts must not have its end sorted before its start.
+        let rank = if edge == ItemEdge::Start || instant == start {
+            START_RANK
+        } else {
+            END_RANK
+        };
+        edges.push(((instant, rank, index, edge_index), item, edge));
     }
+    edges.sort_by_key(|(key, _, _)| *key);
+    edges
+        .into_iter()
+        .map(|(key, item, edge)| (item, edge, key.0))
+        .collect()
+}
+
+///
+/// The edges of an item, in the order they occur; a journey has a connection edge between each
+/// pair of segments, a rail ticket between each pair of legs, and a cruise has an arrival and a
+/// departure edge for each port call.
+///
+fn item_edges(item: &Item) -> Vec<ItemEdge> {
+    let (connections, port_calls) = match item {
+        Item::Journey(v) => (v.connections().len(), 0),
+        Item::Rail(v) => (v.changes().len(), 0),
+        Item::Cruise(v) => (0, v.port_calls().count()),
+        _ => (0, 0),
+    };
+    std::iter::once(ItemEdge::Start)
+        .chain((0..connections).map(ItemEdge::Connection))
+        .chain(
+            (0..port_calls)
+                .flat_map(|index| [ItemEdge::PortArrival(index), ItemEdge::PortDeparture(index)]),
+        )
+        .chain(std::iter::once(ItemEdge::End))
+        .collect()
+}
+
+///
+/// The instant of an edge, if its time zone is known, and its local date-time.
+///
+fn edge_times(item: &Item, edge: ItemEdge) -> (Option<Instant>, &DateTime) {
+    match (item, edge) {
+        (_, ItemEdge::Start) => (item.start_instant(), item.start_date_time()),
+        (Item::Journey(v), ItemEdge::Connection(index)) => {
+            let arriving = v.connections()[index].arriving();
+            (arriving.arrival_instant(), arriving.arrival_date_time())
+        }
+        (Item::Rail(v), ItemEdge::Connection(index)) => {
+            let arriving = v.changes()[index].arriving();
+            (arriving.arrival_instant(), arriving.arrival_date_time())
+        }
+        (Item::Cruise(v), ItemEdge::PortArrival(index)) => {
+            let call = v.port_calls().nth(index).unwrap();
+            (call.arrival_instant(), call.arrival_date_time())
+        }
+        (Item::Cruise(v), ItemEdge::PortDeparture(index)) => {
+            let call = v.port_calls().nth(index).unwrap();
+            (call.departure_instant(), call.departure_date_time())
+        }
+        _ => (item.end_instant(), item.end_date_time()),
+    }
+}
+
+///
+/// The instants used to order `times`, each the instant of a time if its time zone is known and
+/// its local date-time. A time whose zone is not known is given the offset from UTC of the
+/// nearest zoned time before it in local order, or else after it, so that it keeps its place
+/// among its neighbours; where no time is zoned the local times are treated as UTC.
+///
+pub(crate) fn ordering_instants(times: &[(Option<Instant>, &DateTime)]) -> Vec<Instant> {
+    let mut by_local: Vec<usize> = (0..times.len()).collect();
+    by_local.sort_by_key(|index| times[*index].1);
+
+    let mut instants: Vec<Option<Instant>> = times.iter().map(|(instant, _)| *instant).collect();
+    let mut offset: Option<Duration> = None;
+    let mut unzoned: Vec<usize> = Default::default();
+    for index in by_local {
+        match (times[index], offset) {
+            ((Some(instant), local), _) => {
+                let zone_offset = instant.naive_utc() - *local;
+                for index in unzoned.drain(..) {
+                    instants[index] = Some(offset_instant(times[index].1, zone_offset));
+                }
+                offset = Some(zone_offset);
+            }
+            ((None, local), Some(offset)) => instants[index] = Some(offset_instant(local, offset)),
+            ((None, _), None) => unzoned.push(index),
+        }
+    }
+    for index in unzoned {
+        instants[index] = Some(offset_instant(times[index].1, Duration::zero()));
+    }
+    instants.into_iter().map(Option::unwrap).collect()
+}
+
+#[inline]
+fn offset_instant(local: &DateTime, offset: Duration) -> Instant {
+    chrono::Utc.from_utc_datetime(&(*local + offset))
 }
 
 // ------------------------------------------------------------------------------------------------
 // Modules
 // ------------------------------------------------------------------------------------------------
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        fold_ordered_itinerary, visit_filtered_itinerary, visit_filtered_itinerary_mut,
+        visit_ordered_itinerary, visit_ordered_itinerary_mut, Gap, ItineraryFolder,
+        ItineraryVisitor, ItineraryVisitorMut, VisitOrder,
+    };
+    use crate::error::Result;
+    use crate::itinerary::annotation::Annotations;
+    use crate::itinerary::filter::ItemFilter;
+    use crate::itinerary::io::{from_file, from_str};
+    use crate::itinerary::metadata::{Metadata, Summary};
+    use crate::itinerary::{
+        Accomodation, Booking, Connection, Cruise, Date, Event, Flight, Item, ItemKind, Itinerary,
+        Journey, PortCall, Rail, RailChange, Transport,
+    };
+    use pretty_assertions::assert_eq;
+    use std::cell::RefCell;
+    use std::path::PathBuf;
+
+    #[derive(Default)]
+    struct Recorder(RefCell<Vec<String>>, bool);
+
+    impl ItineraryVisitor for Recorder {
+        fn booking_start(&self, booking: &Booking) -> Result<()> {
+            self.record(format!(
+                "{} {} {{",
+                booking.agency(),
+                booking.confirmation()
+            ))
+        }
+
+        fn booking_end(&self, booking: &Booking) -> Result<()> {
+            self.record(format!("}} {}", booking.confirmation()))
+        }
+
+        fn flight_departs(&self, flight: &Flight) -> Result<()> {
+            self.record(format!("{} departs", flight.flight_number_string()))
+        }
+
+        fn flight_arrives(&self, flight: &Flight) -> Result<()> {
+            self.record(format!("{} arrives", flight.flight_number_string()))
+        }
+
+        fn journey_departs(&self, journey: &Journey) -> Result<()> {
+            self.record(format!("{} departs", journey.flight_numbers_string()))
+        }
+
+        fn journey_connection(&self, _: &Journey, connection: &Connection<'_>) -> Result<()> {
+            self.record(format!(
+                "connect {} {}",
+                connection.airport(),
+                connection.layover().num_minutes()
+            ))
+        }
+
+        fn journey_arrives(&self, journey: &Journey) -> Result<()> {
+            self.record(format!("{} arrives", journey.flight_numbers_string()))
+        }
+
+        fn rail_departs(&self, rail: &Rail) -> Result<()> {
+            self.record(format!("{} departs", rail.train_numbers_string()))
+        }
+
+        fn rail_change(&self, _: &Rail, change: &RailChange<'_>) -> Result<()> {
+            self.record(format!(
+                "change {} {}",
+                change.station(),
+                change.change_time().num_minutes()
+            ))
+        }
+
+        fn rail_arrives(&self, rail: &Rail) -> Result<()> {
+            self.record(format!("{} arrives", rail.train_numbers_string()))
+        }
+
+        fn cruise_embarks(&self, cruise: &Cruise) -> Result<()> {
+            self.record(format!("{} embarks", cruise.ship()))
+        }
+
+        fn cruise_port_arrival(&self, _: &Cruise, port_call: &PortCall) -> Result<()> {
+            self.record(format!("arrive {}", port_call.port()))
+        }
+
+        fn cruise_port_departure(&self, _: &Cruise, port_call: &PortCall) -> Result<()> {
+            self.record(format!("depart {}", port_call.port()))
+        }
+
+        fn cruise_disembarks(&self, cruise: &Cruise) -> Result<()> {
+            self.record(format!("{} disembarks", cruise.ship()))
+        }
+
+        fn transport_departs(&self, transport: &Transport) -> Result<()> {
+            self.record(format!("{} departs", transport.departure_date_time()))
+        }
+
+        fn transport_arrives(&self, transport: &Transport) -> Result<()> {
+            self.record(format!("{} arrives", transport.arrival_date_time()))
+        }
+
+        fn accomodation_checkin(&self, accomodation: &Accomodation) -> Result<()> {
+            self.record(format!("{} checkin", accomodation.company()))
+        }
+
+        fn accomodation_checkout(&self, accomodation: &Accomodation) -> Result<()> {
+            self.record(format!("{} checkout", accomodation.company()))
+        }
+
+        fn event_start(&self, event: &Event) -> Result<()> {
+            self.record(format!("{} starts", event.company()))
+        }
+
+        fn event_end(&self, event: &Event) -> Result<()> {
+            self.record(format!("{} ends", event.company()))
+        }
+
+        fn annotations(&self, annotations: &Annotations) -> Result<()> {
+            self.record(format!(
+                "notes {}",
+                annotations.notes().cloned().unwrap_or_default()
+            ))
+        }
+
+        fn item_annotations(&self, item: &Item, annotations: &Annotations) -> Result<()> {
+            self.record(format!(
+                "{} tags {}",
+                item.id().unwrap(),
+                annotations
+                    .tags()
+                    .cloned()
+                    .collect::<Vec<String>>()
+                    .join(",")
+            ))
+        }
+
+        fn include_cancelled(&self) -> bool {
+            self.1
+        }
+    }
+
+    impl Recorder {
+        fn including_cancelled() -> Self {
+            Self(Default::default(), true)
+        }
+
+        fn record(&self, s: String) -> Result<()> {
+            self.0.borrow_mut().push(s);
+            Ok(())
+        }
+    }
+
+    fn visit(itinerary: &Itinerary, order: VisitOrder) -> Vec<String> {
+        let recorder = Recorder::default();
+        visit_ordered_itinerary(itinerary, &recorder, order).unwrap();
+        recorder.0.into_inner()
+    }
+
+    #[derive(Default)]
+    struct Nights(i64);
+
+    impl ItineraryVisitorMut for Nights {
+        fn accomodation_checkin(&mut self, accomodation: &Accomodation) -> Result<()> {
+            self.0 += (accomodation.checkout_date_time().date()
+                - accomodation.checkin_date_time().date())
+            .num_days();
+            Ok(())
+        }
+    }
+
+    impl ItineraryFolder for Nights {
+        type Output = i64;
+
+        fn finish(self) -> Result<Self::Output> {
+            Ok(self.0)
+        }
+    }
+
+    #[derive(Default)]
+    struct Days(Vec<String>);
+
+    impl ItineraryVisitorMut for Days {
+        fn transport_departs(&mut self, transport: &Transport) -> Result<()> {
+            self.record(format!(
+                "{} departs",
+                transport.departure_date_time().time()
+            ))
+        }
+
+        fn accomodation_checkin(&mut self, accomodation: &Accomodation) -> Result<()> {
+            self.record(format!("{} checkin", accomodation.company()))
+        }
+
+        fn accomodation_checkout(&mut self, accomodation: &Accomodation) -> Result<()> {
+            self.record(format!("{} checkout", accomodation.company()))
+        }
+
+        fn event_start(&mut self, event: &Event) -> Result<()> {
+            self.record(format!("{} starts", event.company()))
+        }
+
+        fn day_start(&mut self, date: &Date) -> Result<()> {
+            self.record(format!("{} {{", date))
+        }
+
+        fn day_end(&mut self, date: &Date) -> Result<()> {
+            self.record(format!("}} {}", date))
+        }
+
+        fn idle_gap(&mut self, gap: &Gap) -> Result<()> {
+            self.record(format!("idle {}", gap.duration().num_minutes()))
+        }
+
+        fn layover(&mut self, gap: &Gap) -> Result<()> {
+            self.record(format!("layover {}", gap.duration().num_minutes()))
+        }
+
+        fn unaccounted_nights(&mut self, gap: &Gap) -> Result<()> {
+            self.record(format!("nights {} from {}", gap.nights(), gap.from()))
+        }
+    }
+
+    impl Days {
+        fn record(&mut self, s: String) -> Result<()> {
+            self.0.push(s);
+            Ok(())
+        }
+    }
+
+    fn example_itinerary() -> Itinerary {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/data/playa.yml");
+        from_file(&path).unwrap()
+    }
+
+    #[test]
+    fn test_visit_timing_order() {
+        assert_eq!(
+            visit(&example_itinerary(), VisitOrder::Timing),
+            vec![
+                "DL594 departs",
+                "DL594 arrives",
+                "2022-06-24 16:00:00 departs",
+                "2022-06-24 17:00:00 arrives",
+                "bric checkin",
+                "bric checkout",
+                "2022-07-05 11:00:00 departs",
+                "2022-07-05 12:00:00 arrives",
+                "DL604 departs",
+                "DL604 arrives",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_visit_timing_tie_break() {
+        let itinerary = from_str(
+            r##"---
+identifier: tie-break
+name: Tie Break
+travelers: []
+items:
+  - transport:
+      record:
+        provider: CARM
+        start_date_time: "2022-06-24T10:00:00"
+        start_time_zone: America/Cancun
+        start_location: Hotel
+        end_date_time: "2022-06-24T11:00:00"
+        end_location: Cancun airport
+  - event:
+      record:
+        provider: instant
+        start_date_time: "2022-06-24T10:00:00"
+        start_time_zone: America/Cancun
+        start_location: Hotel
+        end_date_time: "2022-06-24T10:00:00"
+  - event:
+      record:
+        provider: breakfast
+        start_date_time: "2022-06-24T09:00:00"
+        start_time_zone: America/Cancun
+        start_location: Hotel
+        end_date_time: "2022-06-24T10:00:00"
+"##,
+        )
+        .unwrap();
+        assert_eq!(
+            visit(&itinerary, VisitOrder::Timing),
+            vec![
+                "breakfast starts",
+                "breakfast ends",
+                "2022-06-24 10:00:00 departs",
+                "instant starts",
+                "instant ends",
+                "2022-06-24 11:00:00 arrives",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_visit_booking_order() {
+        let itinerary = from_str(
+            r##"---
+identifier: 2022-06
+name: Playa del Carmen
+travelers:
+  - Simon
+bookings:
+  - agency: Delta Vacations
+    confirmation: GROVQY
+    items:
+      - flight:
+          number: 604
+          record:
+            provider: DL
+            confirmation: GROVQY
+            start_date_time: "2022-07-05T14:03:00"
+            start_location: CUN
+            end_date_time: "2022-07-05T20:23:00"
+            end_location: SEA
+      - transport:
+          record:
+            provider: CARM
+            confirmation: GROVQY
+            start_date_time: "2022-07-05T11:00:00"
+            start_location: "The Elements, Playa del Carmen"
+            end_date_time: "2022-07-05T12:00:00"
+            end_location: Cancun airport
+  - agency: Booking.com
+    confirmation: "6015334"
+    items:
+      - accomodation:
+          record:
+            provider: bric
+            confirmation: "6015334"
+            start_date_time: "2022-06-26T16:00:00"
+            start_location: "Garden house 11, The Elements, Playa dl Carmen."
+            end_date_time: "2022-07-05T11:00:00"
+items:
+  - flight:
+      number: 594
+      record:
+        provider: DL
+        start_date_time: "2022-06-24T07:15:00"
+        start_location: SEA
+        end_date_time: "2022-06-24T15:03:00"
+        end_location: CUN
+"##,
+        )
+        .unwrap();
+        assert_eq!(itinerary.items().count(), 4);
+        assert_eq!(
+            visit(&itinerary, VisitOrder::Booking),
+            vec![
+                "Booking.com 6015334 {",
+                "bric checkin",
+                "bric checkout",
+                "} 6015334",
+                "Delta Vacations GROVQY {",
+                "DL604 departs",
+                "DL604 arrives",
+                "2022-07-05 11:00:00 departs",
+                "2022-07-05 12:00:00 arrives",
+                "} GROVQY",
+                "DL594 departs",
+                "DL594 arrives",
+            ]
+        );
+        assert_eq!(
+            visit(&itinerary, VisitOrder::Timing).first().unwrap(),
+            "DL594 departs"
+        );
+
+        let recorder = Recorder::default();
+        let filter = ItemFilter::default().with_predicate(|item| item.is_flight());
+        visit_filtered_itinerary(&itinerary, &recorder, VisitOrder::Booking, &filter).unwrap();
+        assert_eq!(
+            recorder.0.into_inner(),
+            vec![
+                "Delta Vacations GROVQY {",
+                "DL604 departs",
+                "DL604 arrives",
+                "} GROVQY",
+                "DL594 departs",
+                "DL594 arrives",
+            ]
+        );
+
+        // the summary is of the visited items only
+        struct SummaryStart(Option<String>);
+        impl ItineraryVisitorMut for SummaryStart {
+            fn summary(&mut self, _: &Metadata, summary: &Summary) -> Result<()> {
+                self.0 = summary.start().map(|v| v.to_string());
+                Ok(())
+            }
+        }
+        let mut start = SummaryStart(None);
+        let filter = ItemFilter::default().with_kind(ItemKind::Accomodation);
+        visit_filtered_itinerary_mut(&itinerary, &mut start, VisitOrder::Booking, &filter).unwrap();
+        assert_eq!(start.0.as_deref(), Some("2022-06-26 16:00:00"));
+    }
+
+    #[test]
+    fn test_visit_journey_connections() {
+        let itinerary = from_str(
+            r##"---
+identifier: 2022-06
+name: Playa del Carmen
+travelers: []
+items:
+  - transport:
+      record:
+        provider: CARM
+        start_date_time: "2022-06-24T19:00:00"
+        start_location: Cancun airport
+        end_date_time: "2022-06-24T20:00:00"
+        end_location: "The Elements, Playa del Carmen"
+  - journey:
+      segments:
+        - number: 1850
+          record:
+            provider: DL
+            start_date_time: "2022-06-24T06:00:00"
+            start_location: SEA
+            end_date_time: "2022-06-24T13:50:00"
+            end_location: ATL
+        - number: 1203
+          record:
+            provider: DL
+            start_date_time: "2022-06-24T14:40:00"
+            start_location: ATL
+            end_date_time: "2022-06-24T16:30:00"
+            end_location: MIA
+        - number: 2761
+          record:
+            provider: AA
+            start_date_time: "2022-06-24T17:15:00"
+            start_location: MIA
+            end_date_time: "2022-06-24T18:05:00"
+            end_location: CUN
+"##,
+        )
+        .unwrap();
+        let expected = vec![
+            "DL1850/DL1203/AA2761 departs",
+            "connect ATL 50",
+            "connect MIA 45",
+            "DL1850/DL1203/AA2761 arrives",
+            "2022-06-24 19:00:00 departs",
+            "2022-06-24 20:00:00 arrives",
+        ];
+        assert_eq!(visit(&itinerary, VisitOrder::Timing), expected);
+        assert_eq!(
+            visit(&itinerary, VisitOrder::AsIs),
+            [&expected[4..], &expected[..4]].concat()
+        );
+    }
+
+    #[test]
+    fn test_visit_skips_cancelled() {
+        let itinerary = from_str(
+            r##"---
+identifier: 2022-06
+name: Playa del Carmen
+travelers:
+  - Simon
+bookings:
+  - agency: Booking.com
+    confirmation: "6015334"
+    items:
+      - accomodation:
+          status: cancelled
+          tags:
+            - refund
+          record:
+            provider: bric
+            start_date_time: "2022-06-26T16:00:00"
+            start_location: "Garden house 11, The Elements, Playa dl Carmen."
+            end_date_time: "2022-07-05T11:00:00"
+items:
+  - flight:
+      number: 594
+      status: tentative
+      record:
+        provider: DL
+        start_date_time: "2022-06-24T07:15:00"
+        start_location: SEA
+        end_date_time: "2022-06-24T15:03:00"
+        end_location: CUN
+"##,
+        )
+        .unwrap();
+        for order in [VisitOrder::Timing, VisitOrder::Booking, VisitOrder::AsIs] {
+            assert_eq!(
+                visit(&itinerary, order),
+                vec!["DL594 departs", "DL594 arrives"]
+            );
+        }
+
+        let recorder = Recorder::including_cancelled();
+        visit_ordered_itinerary(&itinerary, &recorder, VisitOrder::Booking).unwrap();
+        assert_eq!(
+            recorder.0.into_inner(),
+            vec![
+                "Booking.com 6015334 {",
+                "bric checkin",
+                "accomodation-bric-20220626 tags refund",
+                "bric checkout",
+                "} 6015334",
+                "DL594 departs",
+                "DL594 arrives",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_visit_rail_changes() {
+        let itinerary = from_str(
+            r##"---
+identifier: 2023-05
+name: Paris to Munich
+travelers: []
+items:
+  - event:
+      record:
+        provider: Schlossplatz
+        start_date_time: "2023-05-12T13:15:00"
+        start_location: Stuttgart
+        end_date_time: "2023-05-12T13:30:00"
+  - rail:
+      legs:
+        - train_number: TGV 9577
+          record:
+            provider: SNCF
+            start_date_time: "2023-05-12T09:55:00"
+            start_location: Paris Est
+            end_date_time: "2023-05-12T13:04:00"
+            end_location: Stuttgart Hbf
+        - train_number: ICE 591
+          record:
+            provider: DB
+            start_date_time: "2023-05-12T13:51:00"
+            start_location: Stuttgart Hbf
+            end_date_time: "2023-05-12T16:08:00"
+            end_location: Munich Hbf
+"##,
+        )
+        .unwrap();
+        assert_eq!(
+            visit(&itinerary, VisitOrder::Timing),
+            vec![
+                "TGV 9577/ICE 591 departs",
+                "change Stuttgart Hbf 47",
+                "Schlossplatz starts",
+                "Schlossplatz ends",
+                "TGV 9577/ICE 591 arrives",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_visit_cruise_port_calls() {
+        let itinerary = from_str(
+            r##"---
+identifier: 2023-09
+name: Norwegian fjords
+travelers: []
+items:
+  - cruise:
+      ship: Hurtigruten Nordnorge
+      record:
+        provider: Hurtigruten
+        start_date_time: "2023-09-01T20:30:00"
+        start_location: Bergen
+        end_date_time: "2023-09-04T10:00:00"
+        end_location: Trondheim
+      port_calls:
+        - port: Geiranger
+          arrival_date_time: "2023-09-02T09:30:00"
+          departure_date_time: "2023-09-02T13:30:00"
+        - port: Alesund
+          arrival_date_time: "2023-09-02T16:00:00"
+          departure_date_time: "2023-09-02T18:30:00"
+  - event:
+      record:
+        provider: Troll Wall tour
+        start_date_time: "2023-09-02T10:00:00"
+        start_location: Geiranger
+        end_date_time: "2023-09-02T12:30:00"
+"##,
+        )
+        .unwrap();
+        assert_eq!(
+            visit(&itinerary, VisitOrder::Timing),
+            vec![
+                "Hurtigruten Nordnorge embarks",
+                "arrive Geiranger",
+                "Troll Wall tour starts",
+                "Troll Wall tour ends",
+                "depart Geiranger",
+                "arrive Alesund",
+                "depart Alesund",
+                "Hurtigruten Nordnorge disembarks",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_visit_mut_and_fold() {
+        let itinerary = example_itinerary();
+        for order in [VisitOrder::Timing, VisitOrder::Booking, VisitOrder::AsIs] {
+            assert_eq!(
+                fold_ordered_itinerary(&itinerary, Nights::default(), order).unwrap(),
+                9
+            );
+        }
+
+        let mut nights = Nights(1);
+        visit_ordered_itinerary_mut(&itinerary, &mut nights, VisitOrder::AsIs).unwrap();
+        assert_eq!(nights.0, 10);
+
+        let mut recorder = &Recorder::default();
+        visit_ordered_itinerary_mut(&itinerary, &mut recorder, VisitOrder::Timing).unwrap();
+        assert_eq!(
+            recorder.0.borrow().clone(),
+            visit(&itinerary, VisitOrder::Timing)
+        );
+    }
+
+    #[test]
+    fn test_visit_days_and_gaps() {
+        let itinerary = from_str(
+            r##"---
+identifier: 2022-06
+name: Playa del Carmen
+travelers: []
+items:
+  - event:
+      record:
+        provider: dinner
+        start_date_time: "2022-06-24T19:00:00"
+        start_time_zone: America/Cancun
+        start_location: Playa del Carmen
+        end_date_time: "2022-06-24T21:00:00"
+  - event:
+      record:
+        provider: tour
+        start_date_time: "2022-06-25T09:00:00"
+        start_time_zone: America/Cancun
+        start_location: Tulum
+        end_date_time: "2022-06-25T12:00:00"
+  - transport:
+      record:
+        provider: CARM
+        start_date_time: "2022-06-25T13:00:00"
+        start_time_zone: America/Cancun
+        start_location: Tulum
+        end_date_time: "2022-06-25T14:00:00"
+        end_time_zone: America/Cancun
+        end_location: Playa del Carmen
+  - transport:
+      record:
+        provider: Ferry
+        start_date_time: "2022-06-25T14:30:00"
+        start_time_zone: America/Cancun
+        start_location: Playa del Carmen
+        end_date_time: "2022-06-25T15:00:00"
+        end_time_zone: America/Cancun
+        end_location: Cozumel
+  - accomodation:
+      record:
+        provider: bric
+        start_date_time: "2022-06-25T16:00:00"
+        start_time_zone: America/Cancun
+        start_location: Cozumel
+        end_date_time: "2022-06-27T11:00:00"
+  - event:
+      record:
+        provider: dive
+        start_date_time: "2022-06-28T10:00:00"
+        start_time_zone: America/Cancun
+        start_location: Cozumel
+        end_date_time: "2022-06-28T11:00:00"
+"##,
+        )
+        .unwrap();
+        let mut days = Days::default();
+        visit_ordered_itinerary_mut(&itinerary, &mut days, VisitOrder::Timing).unwrap();
+        assert_eq!(
+            days.0,
+            vec![
+                "2022-06-24 {",
+                "dinner starts",
+                "} 2022-06-24",
+                "2022-06-25 {",
+                "idle 720",
+                "nights 1 from 2022-06-24 21:00:00",
+                "tour starts",
+                "idle 60",
+                "13:00:00 departs",
+                "layover 30",
+                "14:30:00 departs",
+                "idle 60",
+                "bric checkin",
+                "} 2022-06-25",
+                "2022-06-26 {",
+                "} 2022-06-26",
+                "2022-06-27 {",
+                "bric checkout",
+                "} 2022-06-27",
+                "2022-06-28 {",
+                "idle 1380",
+                "nights 1 from 2022-06-27 11:00:00",
+                "dive starts",
+                "} 2022-06-28",
+            ]
+        );
+
+        let mut days = Days::default();
+        visit_ordered_itinerary_mut(&itinerary, &mut days, VisitOrder::AsIs).unwrap();
+        assert!(!days.0.iter().any(|v| v.contains('{')));
+    }
+
+    #[test]
+    fn test_visit_timing_ends_before_start() {
+        let itinerary = from_str(
+            r##"---
+identifier: 2022-06
+name: Playa del Carmen
+travelers: []
+items:
+  - event:
+      record:
+        provider: backwards
+        start_date_time: "2022-06-24T10:00:00"
+        start_time_zone: America/Cancun
+        start_location: Hotel
+        end_date_time: "2022-06-24T09:00:00"
+  - event:
+      record:
+        provider: lunch
+        start_date_time: "2022-06-24T12:00:00"
+        start_time_zone: America/Cancun
+        start_location: Hotel
+        end_date_time: "2022-06-24T13:00:00"
+"##,
+        )
+        .unwrap();
+        assert_eq!(
+            visit(&itinerary, VisitOrder::Timing),
+            vec![
+                "backwards starts",
+                "backwards ends",
+                "lunch starts",
+                "lunch ends"
+            ]
+        );
+
+        let mut days = Days::default();
+        visit_ordered_itinerary_mut(&itinerary, &mut days, VisitOrder::Timing).unwrap();
+        assert_eq!(
+            days.0,
+            vec![
+                "2022-06-24 {",
+                "backwards starts",
+                "idle 120",
+                "lunch starts",
+                "} 2022-06-24"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_visit_timing_unzoned_between_zoned() {
+        let itinerary = from_str(
+            r##"---
+identifier: 2022-06
+name: Playa del Carmen
+travelers: []
+items:
+  - event:
+      record:
+        provider: dinner
+        start_date_time: "2022-06-24T19:00:00"
+        start_time_zone: America/Cancun
+        start_location: Hotel
+        end_date_time: "2022-06-24T20:00:00"
+  - event:
+      record:
+        provider: tour
+        start_date_time: "2022-06-24T10:00:00"
+        start_location: Tulum
+        end_date_time: "2022-06-24T11:00:00"
+  - event:
+      record:
+        provider: breakfast
+        start_date_time: "2022-06-24T08:00:00"
+        start_time_zone: America/Cancun
+        start_location: Hotel
+        end_date_time: "2022-06-24T09:00:00"
+"##,
+        )
+        .unwrap();
+        assert_eq!(
+            visit(&itinerary, VisitOrder::Timing),
+            vec![
+                "breakfast starts",
+                "breakfast ends",
+                "tour starts",
+                "tour ends",
+                "dinner starts",
+                "dinner ends"
+            ]
+        );
+        let summary = itinerary.summary();
+        assert_eq!(
+            summary.start().map(ToString::to_string),
+            Some("2022-06-24 08:00:00".to_string())
+        );
+        assert_eq!(
+            summary.end().map(ToString::to_string),
+            Some("2022-06-24 20:00:00".to_string())
+        );
+    }
+}
@@ -7,6 +7,9 @@ More detailed description, with
 
 */
 
+// error_chain's expansion of `errors::ErrorKind` exceeds the default limit.
+#![recursion_limit = "256"]
+
 #[macro_use]
 extern crate error_chain;
 
@@ -47,3 +50,5 @@ pub mod error;
 pub mod config;
 
 pub mod itinerary;
+
+pub mod library;
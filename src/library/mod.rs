@@ -0,0 +1,514 @@
+/*!
+A library of itinerary files, kept together in one directory.
+
+By default the library is the `itineraries` directory within the application's XDG data
+directory (see `config::get_app_library_path`). Every `.yml` or `.yaml` file in the directory is
+an itinerary, and the library keeps an index of them, in `index.yml`, recording the identifier,
+name, dates and travelers of each so that an itinerary can be found without knowing its file
+name. The index is brought up to date whenever the library is opened; files that were added,
+removed, or modified since the index was written are re-read.
+
+# Example
+
+```rust,no_run
+use terminal_travel::library::Library;
+
+let library = Library::open_default().unwrap();
+if let Some(entry) = library.find("2022-06").unwrap() {
+    println!("{} is in {:?}", entry.name(), library.path_of(entry));
+}
+```
+ */
+
+use crate::config::get_app_library_path;
+use crate::error::{ErrorKind, Result};
+use crate::itinerary::io::from_file;
+use crate::itinerary::{Date, Itinerary};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+pub const INDEX_FILE_NAME: &str = "index.yml";
+
+///
+/// The indexed details of a single itinerary file in the library.
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LibraryEntry {
+    file_name: String,
+    identifier: String,
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start_date: Option<Date>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end_date: Option<Date>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    travelers: Vec<String>,
+    modified: SystemTime,
+    size: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct Library {
+    root: PathBuf,
+    entries: Vec<LibraryEntry>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct LibraryIndex {
+    entries: Vec<LibraryEntry>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl LibraryEntry {
+    ///
+    /// The name of the itinerary file, relative to the library directory.
+    ///
+    pub fn file_name(&self) -> &String {
+        &self.file_name
+    }
+
+    pub fn identifier(&self) -> &String {
+        &self.identifier
+    }
+
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    ///
+    /// The date the first item, that is not cancelled, starts; `None` if there are no items.
+    ///
+    pub fn start_date(&self) -> Option<&Date> {
+        self.start_date.as_ref()
+    }
+
+    ///
+    /// The date the last item, that is not cancelled, ends; `None` if there are no items.
+    ///
+    pub fn end_date(&self) -> Option<&Date> {
+        self.end_date.as_ref()
+    }
+
+    pub fn travelers(&self) -> impl Iterator<Item = &String> {
+        self.travelers.iter()
+    }
+
+    pub fn has_traveler(&self, name: &str) -> bool {
+        self.travelers.iter().any(|v| v == name)
+    }
+
+    ///
+    /// Returns `true` if the itinerary is under way on `date`.
+    ///
+    pub fn includes_date(&self, date: &Date) -> bool {
+        match (&self.start_date, &self.end_date) {
+            (Some(start), Some(end)) => start <= date && date <= end,
+            _ => false,
+        }
+    }
+
+    ///
+    /// Returns `true` if `other` appears to be the same trip; either it has the same identifier,
+    /// or the same name and dates.
+    ///
+    pub fn is_duplicate_of(&self, other: &LibraryEntry) -> bool {
+        self.identifier == other.identifier
+            || (self.name == other.name
+                && self.start_date == other.start_date
+                && self.end_date == other.end_date)
+    }
+
+    fn new(file_name: &str, itinerary: &Itinerary, modified: SystemTime, size: u64) -> Self {
+        let summary = itinerary.summary();
+        Self {
+            file_name: file_name.to_string(),
+            identifier: itinerary.identifier().clone(),
+            name: itinerary.name().clone(),
+            start_date: summary.start().map(|v| v.date()),
+            end_date: summary.end().map(|v| v.date()),
+            travelers: itinerary.travelers().map(|v| v.name().clone()).collect(),
+            modified,
+            size,
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Library {
+    ///
+    /// Open the library in the default location, creating the directory if necessary.
+    ///
+    pub fn open_default() -> Result<Self> {
+        Self::open(&get_app_library_path())
+    }
+
+    ///
+    /// Open the library in the directory `root`, creating it if necessary, and bring its index
+    /// up to date.
+    ///
+    pub fn open(root: &Path) -> Result<Self> {
+        std::fs::create_dir_all(root)?;
+        let index_path = root.join(INDEX_FILE_NAME);
+        let index: LibraryIndex = if index_path.is_file() {
+            match serde_yaml::from_reader(File::open(&index_path)?) {
+                Ok(index) => index,
+                Err(e) => {
+                    warn!(
+                        "library index {:?} could not be read, rebuilding: {}",
+                        index_path, e
+                    );
+                    Default::default()
+                }
+            }
+        } else {
+            Default::default()
+        };
+        let mut library = Self {
+            root: root.to_path_buf(),
+            entries: index.entries,
+        };
+        let _ = library.refresh()?;
+        Ok(library)
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    ///
+    /// The full path of the itinerary file for `entry`.
+    ///
+    pub fn path_of(&self, entry: &LibraryEntry) -> PathBuf {
+        self.root.join(&entry.file_name)
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &LibraryEntry> {
+        self.entries.iter()
+    }
+
+    ///
+    /// Find the itinerary with the given identifier; it is an error if more than one itinerary
+    /// has this identifier.
+    ///
+    pub fn find(&self, identifier: &str) -> Result<Option<&LibraryEntry>> {
+        let mut found = self.entries.iter().filter(|v| v.identifier == identifier);
+        match (found.next(), found.next()) {
+            (Some(first), Some(_)) => Err(ErrorKind::DuplicateItinerary(
+                identifier.to_string(),
+                first.file_name.clone(),
+            )
+            .into()),
+            (found, _) => Ok(found),
+        }
+    }
+
+    ///
+    /// Itineraries whose name contains `name`, ignoring case.
+    ///
+    pub fn find_by_name<'a>(&'a self, name: &str) -> impl Iterator<Item = &'a LibraryEntry> {
+        let name = name.to_lowercase();
+        self.entries
+            .iter()
+            .filter(move |v| v.name.to_lowercase().contains(&name))
+    }
+
+    pub fn find_by_traveler<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a LibraryEntry> {
+        self.entries.iter().filter(move |v| v.has_traveler(name))
+    }
+
+    pub fn find_by_date<'a>(&'a self, date: &'a Date) -> impl Iterator<Item = &'a LibraryEntry> {
+        self.entries.iter().filter(move |v| v.includes_date(date))
+    }
+
+    ///
+    /// Groups of itineraries that appear to be the same trip, see `LibraryEntry::is_duplicate_of`.
+    ///
+    pub fn duplicates(&self) -> Vec<Vec<&LibraryEntry>> {
+        let mut groups: Vec<Vec<&LibraryEntry>> = Default::default();
+        for entry in &self.entries {
+            match groups
+                .iter_mut()
+                .find(|group| group.iter().any(|v| v.is_duplicate_of(entry)))
+            {
+                Some(group) => group.push(entry),
+                None => groups.push(vec![entry]),
+            }
+        }
+        groups.retain(|group| group.len() > 1);
+        groups
+    }
+
+    ///
+    /// Copy the itinerary file at `file` into the library, named for its identifier; if the
+    /// identifier is not usable as a file name, or would name the index, the source file's name
+    /// is kept. It is an error if the library already holds a duplicate of it.
+    ///
+    pub fn add(&mut self, file: &Path) -> Result<&LibraryEntry> {
+        let itinerary = from_file(file)?;
+        let identifier_file_name = format!("{}.yml", itinerary.identifier());
+        let file_name = if is_safe_file_stem(itinerary.identifier())
+            && is_itinerary_file(&identifier_file_name)
+        {
+            identifier_file_name
+        } else {
+            match file.file_name().and_then(|v| v.to_str()) {
+                Some(file_name) if is_itinerary_file(file_name) => file_name.to_string(),
+                _ => {
+                    return Err(ErrorKind::InvalidValue(
+                        "library file name".to_string(),
+                        format!(
+                            "'{}' is not a .yml or .yaml file, or is named for the index",
+                            file.display()
+                        ),
+                    )
+                    .into())
+                }
+            }
+        };
+        let metadata = std::fs::metadata(file)?;
+        let entry = LibraryEntry::new(&file_name, &itinerary, metadata.modified()?, metadata.len());
+        if let Some(existing) = self
+            .entries
+            .iter()
+            .find(|v| v.is_duplicate_of(&entry) || v.file_name.eq_ignore_ascii_case(&file_name))
+        {
+            return Err(ErrorKind::DuplicateItinerary(
+                itinerary.identifier().clone(),
+                existing.file_name.clone(),
+            )
+            .into());
+        }
+        let _ = std::fs::copy(file, self.root.join(&file_name))?;
+        let _ = self.refresh()?;
+        self.entries
+            .iter()
+            .find(|v| v.file_name == file_name)
+            .ok_or_else(|| {
+                ErrorKind::InvalidValue(
+                    "library file name".to_string(),
+                    format!("'{}' was not indexed once copied", file_name),
+                )
+                .into()
+            })
+    }
+
+    ///
+    /// Bring the index up to date with the files in the library directory, saving it if anything
+    /// changed; returns `true` if the index was changed.
+    ///
+    pub fn refresh(&mut self) -> Result<bool> {
+        let mut changed = false;
+        let mut entries: BTreeMap<String, LibraryEntry> = self
+            .entries
+            .drain(..)
+            .map(|v| (v.file_name.clone(), v))
+            .collect();
+        let mut refreshed = Vec::with_capacity(entries.len());
+        for dir_entry in std::fs::read_dir(&self.root)? {
+            let path = dir_entry?.path();
+            let file_name = match path.file_name().and_then(|v| v.to_str()) {
+                Some(file_name) if is_itinerary_file(file_name) => file_name.to_string(),
+                _ => continue,
+            };
+            let metadata = std::fs::metadata(&path)?;
+            let modified = metadata.modified()?;
+            match entries.remove(&file_name) {
+                Some(entry) if entry.modified == modified && entry.size == metadata.len() => {
+                    refreshed.push(entry)
+                }
+                _ => {
+                    changed = true;
+                    match from_file(&path) {
+                        Ok(itinerary) => refreshed.push(LibraryEntry::new(
+                            &file_name,
+                            &itinerary,
+                            modified,
+                            metadata.len(),
+                        )),
+                        Err(e) => warn!("library file {:?} is not an itinerary: {}", path, e),
+                    }
+                }
+            }
+        }
+        changed = changed || !entries.is_empty();
+        refreshed.sort_by(|a, b| {
+            (a.start_date, &a.identifier, &a.file_name).cmp(&(
+                b.start_date,
+                &b.identifier,
+                &b.file_name,
+            ))
+        });
+        self.entries = refreshed;
+        if changed {
+            debug!("library index changed, saving");
+            self.save_index()?;
+        }
+        Ok(changed)
+    }
+
+    ///
+    /// Discard the index and re-read every itinerary file.
+    ///
+    pub fn rebuild(&mut self) -> Result<()> {
+        self.entries.clear();
+        let _ = self.refresh()?;
+        self.save_index()
+    }
+
+    fn save_index(&self) -> Result<()> {
+        let index = LibraryIndex {
+            entries: self.entries.clone(),
+        };
+        serde_yaml::to_writer(File::create(self.root.join(INDEX_FILE_NAME))?, &index)?;
+        Ok(())
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// An identifier that can be used, as-is, for a file within the library directory; it may not
+/// contain path separators or start with `.`, so neither `..` nor hidden files are possible.
+///
+fn is_safe_file_stem(s: &str) -> bool {
+    !s.is_empty()
+        && !s.starts_with('.')
+        && s.chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+}
+
+///
+/// The index is excluded regardless of case, as on some file systems `Index.yml` would replace it.
+///
+fn is_itinerary_file(file_name: &str) -> bool {
+    !file_name.eq_ignore_ascii_case(INDEX_FILE_NAME)
+        && (file_name.ends_with(".yml") || file_name.ends_with(".yaml"))
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::{Library, INDEX_FILE_NAME};
+    use crate::itinerary::Date;
+    use pretty_assertions::assert_eq;
+    use std::path::{Path, PathBuf};
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("library-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write(dir: &Path, file_name: &str, identifier: &str, name: &str, traveler: &str) {
+        std::fs::write(
+            dir.join(file_name),
+            format!(
+                r##"---
+identifier: {}
+name: {}
+travelers:
+  - {}
+items:
+  - event:
+      record:
+        provider: Dinner
+        start_date_time: "2022-06-25T19:00:00"
+        start_location: Playa del Carmen
+        end_date_time: "2022-06-27T21:00:00"
+"##,
+                identifier, name, traveler
+            ),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_library_index() {
+        let dir = test_dir("index");
+        let source = test_dir("source");
+        write(&dir, "playa.yml", "2022-06", "Playa del Carmen", "Simon");
+        std::fs::write(dir.join("notes.txt"), "not an itinerary").unwrap();
+
+        let library = Library::open(&dir).unwrap();
+        assert!(dir.join(INDEX_FILE_NAME).is_file());
+        let entry = library.find("2022-06").unwrap().unwrap();
+        assert_eq!(entry.name(), "Playa del Carmen");
+        assert_eq!(entry.start_date(), Date::from_ymd_opt(2022, 6, 25).as_ref());
+        assert_eq!(entry.end_date(), Date::from_ymd_opt(2022, 6, 27).as_ref());
+        assert_eq!(library.path_of(entry), dir.join("playa.yml"));
+        assert!(library.find("2022-07").unwrap().is_none());
+        assert_eq!(library.find_by_traveler("Simon").count(), 1);
+        assert_eq!(library.find_by_name("playa").count(), 1);
+        let date = Date::from_ymd_opt(2022, 6, 26).unwrap();
+        assert_eq!(library.find_by_date(&date).count(), 1);
+
+        // adding a file copies it in, unless it duplicates an existing one
+        write(&source, "cancun.yml", "2022-08", "Cancun", "Jane");
+        write(&source, "again.yml", "2022-09", "Playa del Carmen", "Jane");
+        let mut library = Library::open(&dir).unwrap();
+        assert_eq!(
+            library.add(&source.join("cancun.yml")).unwrap().file_name(),
+            "2022-08.yml"
+        );
+        assert!(library.add(&source.join("again.yml")).is_err());
+        assert_eq!(library.find_by_traveler("Jane").count(), 1);
+
+        // an identifier that is not a safe file name does not name the copy
+        write(&source, "escape.yml", "../../escape", "Escape", "Jane");
+        assert_eq!(
+            library.add(&source.join("escape.yml")).unwrap().file_name(),
+            "escape.yml"
+        );
+        assert!(dir.join("escape.yml").is_file());
+        std::fs::remove_file(dir.join("escape.yml")).unwrap();
+
+        // nor does one that would replace the index, regardless of case
+        for identifier in ["index", "INDEX"] {
+            write(&source, "trip.yml", identifier, "Index", "Jane");
+            assert_eq!(
+                library.add(&source.join("trip.yml")).unwrap().file_name(),
+                "trip.yml"
+            );
+            assert!(Library::open(&dir).unwrap().find(identifier).is_ok());
+            std::fs::remove_file(dir.join("trip.yml")).unwrap();
+            library.refresh().unwrap();
+        }
+        write(&source, INDEX_FILE_NAME, "index", "Index", "Jane");
+        assert!(library.add(&source.join(INDEX_FILE_NAME)).is_err());
+
+        // changes made outside the library are picked up when it is next opened
+        std::fs::remove_file(dir.join("2022-08.yml")).unwrap();
+        write(&dir, "copy.yml", "2022-06", "Playa", "Jane");
+        let library = Library::open(&dir).unwrap();
+        assert_eq!(library.entries().count(), 2);
+        assert!(library.find("2022-06").is_err());
+        let duplicates = library.duplicates();
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(duplicates[0].len(), 2);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        std::fs::remove_dir_all(&source).unwrap();
+    }
+}
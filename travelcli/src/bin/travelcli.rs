@@ -7,14 +7,32 @@ More detailed description, with
 
 */
 
+use chrono::Utc;
+use std::collections::HashMap;
 use std::error::Error;
 use std::fs::File;
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command as ProcessCommand;
+use std::str::FromStr;
 use structopt::StructOpt;
-use terminal_travel::config::{get_app_config_from, get_app_config_path, get_stage};
+use terminal_travel::amadeus::schedule::{fetch_flight_schedule, flight_schedule_request};
+use terminal_travel::config::{
+    get_app_config_from, get_app_config_path, get_app_library_path, get_app_template_path,
+    get_stage,
+};
 use terminal_travel::config::{AppConfig, APP_CONFIG_NAME};
-use terminal_travel::itinerary::display::{display_itinerary, DisplayFormat};
-use terminal_travel::itinerary::io::from_reader;
+use terminal_travel::itinerary::display::{display_itinerary_with, DisplayFormat, DisplayOptions};
+use terminal_travel::itinerary::edit::{EditCommand, ItemPosition};
+use terminal_travel::itinerary::filter::ItemFilter;
+use terminal_travel::itinerary::io::{from_reader, to_file};
+use terminal_travel::itinerary::journal::Journal;
+use terminal_travel::itinerary::status::ItemStatus;
+use terminal_travel::itinerary::template;
+use terminal_travel::itinerary::traveler::Traveler;
+use terminal_travel::itinerary::validation::Severity;
+use terminal_travel::itinerary::{Date, ItemId, ItemKind, Itinerary};
+use terminal_travel::library::Library;
 use tracing::{debug, info};
 
 // ------------------------------------------------------------------------------------------------
@@ -36,6 +54,10 @@ struct CommandLine {
     /// Use an alternate configuration from the named file
     config_file: Option<PathBuf>,
 
+    #[structopt(short, long, parse(from_os_str))]
+    /// Use an alternate library of itineraries in the named directory
+    library_dir: Option<PathBuf>,
+
     #[structopt(subcommand)]
     cmd: Command,
 }
@@ -48,6 +70,18 @@ enum Command {
         /// Use interactive mode
         interactive: bool,
 
+        #[structopt(long, parse(from_os_str))]
+        /// Create the itinerary from a template file, or a named template in the templates directory
+        from_template: Option<PathBuf>,
+
+        #[structopt(long, requires = "from-template", parse(try_from_str = parse_date))]
+        /// The date the itinerary starts, as YYYY-MM-DD; the template's dates are relative to this
+        start: Option<Date>,
+
+        #[structopt(long, number_of_values = 1, requires = "from-template", parse(try_from_str = parse_placeholder))]
+        /// A template placeholder value, as NAME=VALUE; values not given are prompted for
+        set: Vec<(String, String)>,
+
         #[structopt(name = "FILE", parse(from_os_str))]
         /// Itinerary file path
         file: PathBuf,
@@ -58,19 +92,151 @@ enum Command {
         /// Use interactive mode
         interactive: bool,
 
+        #[structopt(flatten)]
+        changes: EditChanges,
+
         #[structopt(name = "FILE", parse(from_os_str))]
-        /// Itinerary file path
+        /// Itinerary file path, or the identifier of an itinerary in the library
+        file: PathBuf,
+    },
+    /// Undo the most recent edit to an itinerary file
+    Undo {
+        #[structopt(name = "FILE", parse(from_os_str))]
+        /// Itinerary file path, or the identifier of an itinerary in the library
         file: PathBuf,
     },
+    /// Redo the most recently undone edit to an itinerary file
+    Redo {
+        #[structopt(name = "FILE", parse(from_os_str))]
+        /// Itinerary file path, or the identifier of an itinerary in the library
+        file: PathBuf,
+    },
+    /// Show the journal of edits made to an itinerary file
+    History {
+        #[structopt(name = "FILE", parse(from_os_str))]
+        /// Itinerary file path, or the identifier of an itinerary in the library
+        file: PathBuf,
+    },
+    /// Manage the library of itinerary files
+    Library {
+        #[structopt(subcommand)]
+        cmd: LibraryCommand,
+    },
     /// Display an existing itinerary file
     Display {
         #[structopt(long)]
+        /// Fill in flight terminals and gates from the current schedule
         update_flights: bool,
 
+        #[structopt(short, long, default_value = "nutable", parse(try_from_str = parse_display_format))]
+        /// The display format; one of nutable, cards, or indented
+        format: DisplayFormat,
+
+        #[structopt(short, long)]
+        /// Only show the items for the named traveler
+        traveler: Option<String>,
+
+        #[structopt(short, long)]
+        /// Show notes, tags, and attachments
+        notes: bool,
+
+        #[structopt(flatten)]
+        filter: DisplayFilter,
+
+        #[structopt(name = "FILE", parse(from_os_str))]
+        /// Itinerary file path, or the identifier of an itinerary in the library
+        file: PathBuf,
+    },
+    /// Open a file attached to an existing itinerary file
+    Open {
+        #[structopt(short, long)]
+        /// The identifier of the item the file is attached to, otherwise the itinerary itself
+        item: Option<String>,
+
+        #[structopt(name = "FILE", parse(from_os_str))]
+        /// Itinerary file path, or the identifier of an itinerary in the library
+        file: PathBuf,
+
+        #[structopt(name = "ATTACHMENT", parse(from_os_str))]
+        /// The attachment path, as shown by display --notes; defaults to the first attachment
+        attachment: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, StructOpt)]
+enum LibraryCommand {
+    /// List the itineraries in the library
+    List {
+        #[structopt(short, long)]
+        /// Only list the itineraries for the named traveler
+        traveler: Option<String>,
+    },
+    /// Copy an itinerary file into the library
+    Add {
         #[structopt(name = "FILE", parse(from_os_str))]
         /// Itinerary file path
         file: PathBuf,
     },
+    /// Re-read every itinerary file in the library, rebuilding its index
+    Rebuild,
+}
+
+#[derive(Debug, StructOpt)]
+struct EditChanges {
+    #[structopt(long)]
+    /// Change the name of the itinerary
+    rename: Option<String>,
+
+    #[structopt(long, number_of_values = 1)]
+    /// Add a traveler with the given name
+    add_traveler: Vec<String>,
+
+    #[structopt(long, number_of_values = 1)]
+    /// Remove the named traveler; no item may refer to them
+    remove_traveler: Vec<String>,
+
+    #[structopt(long, number_of_values = 1)]
+    /// Remove the item with the given identifier
+    remove_item: Vec<String>,
+
+    #[structopt(long, number_of_values = 1)]
+    /// Remove the booking with the given confirmation, and its items
+    remove_booking: Vec<String>,
+
+    #[structopt(long, requires = "to")]
+    /// Move the item with the given identifier to the position given by --to
+    move_item: Option<String>,
+
+    #[structopt(long)]
+    /// The position to move an item to, starting at 0
+    to: Option<usize>,
+
+    #[structopt(long, requires = "move-item")]
+    /// Move the item into the booking with this confirmation, rather than the unbooked items
+    booking: Option<String>,
+}
+
+#[derive(Debug, StructOpt)]
+struct DisplayFilter {
+    #[structopt(long, number_of_values = 1, parse(try_from_str = parse_item_kind))]
+    /// Only show items of this kind, such as flight or accomodation
+    kind: Vec<ItemKind>,
+
+    #[structopt(long, parse(try_from_str = parse_date))]
+    /// Only show items that end on or after this date (YYYY-MM-DD)
+    from: Option<Date>,
+
+    #[structopt(long, parse(try_from_str = parse_date))]
+    /// Only show items that start on or before this date (YYYY-MM-DD)
+    to: Option<Date>,
+
+    #[structopt(long, number_of_values = 1, parse(try_from_str = parse_item_status))]
+    /// Only show items with this status; cancelled items are only shown if selected here
+    status: Vec<ItemStatus>,
+
+    #[structopt(long, number_of_values = 1)]
+    /// Only show items with this tag
+    tag: Vec<String>,
 }
 
 // ------------------------------------------------------------------------------------------------
@@ -81,6 +247,71 @@ enum Command {
 // Implementations
 // ------------------------------------------------------------------------------------------------
 
+impl DisplayFilter {
+    fn item_filter(&self) -> ItemFilter {
+        let mut filter = ItemFilter::default();
+        for kind in &self.kind {
+            filter = filter.with_kind(*kind);
+        }
+        if let Some(from) = self.from {
+            filter = filter.from_date(from);
+        }
+        if let Some(to) = self.to {
+            filter = filter.to_date(to);
+        }
+        for status in &self.status {
+            filter = filter.with_status(*status);
+        }
+        for tag in &self.tag {
+            filter = filter.with_tag(tag);
+        }
+        filter
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl EditChanges {
+    ///
+    /// The edit commands for these changes, in the order renames, additions, moves, then removals.
+    ///
+    fn commands(&self) -> Result<Vec<EditCommand>, String> {
+        let mut commands = Vec::new();
+        if let Some(name) = &self.rename {
+            commands.push(EditCommand::Rename { name: name.clone() });
+        }
+        for name in &self.add_traveler {
+            commands.push(EditCommand::AddTraveler {
+                traveler: Traveler::new(name),
+                index: None,
+            });
+        }
+        if let (Some(id), Some(index)) = (&self.move_item, self.to) {
+            commands.push(EditCommand::MoveItem {
+                id: parse_item_id(id)?,
+                position: match &self.booking {
+                    Some(booking) => ItemPosition::in_booking(booking, index),
+                    None => ItemPosition::unbooked(index),
+                },
+            });
+        }
+        for id in &self.remove_item {
+            commands.push(EditCommand::RemoveItem {
+                id: parse_item_id(id)?,
+            });
+        }
+        for confirmation in &self.remove_booking {
+            commands.push(EditCommand::RemoveBooking {
+                confirmation: confirmation.clone(),
+            });
+        }
+        for name in &self.remove_traveler {
+            commands.push(EditCommand::RemoveTraveler { name: name.clone() });
+        }
+        Ok(commands)
+    }
+}
+
 // ------------------------------------------------------------------------------------------------
 // Private Functions
 // ------------------------------------------------------------------------------------------------
@@ -105,35 +336,323 @@ enum Command {
 // }
 
 async fn cmd_new_itinerary(
-    _file: PathBuf,
-    _interactive: bool,
+    file: PathBuf,
+    interactive: bool,
+    from_template: Option<PathBuf>,
+    start: Option<Date>,
+    values: Vec<(String, String)>,
     _app_config: AppConfig,
 ) -> Result<(), Box<dyn Error>> {
+    if file.exists() {
+        return Err(format!("the file '{}' already exists", file.display()).into());
+    }
+    let template = match from_template {
+        Some(template) => template,
+        None => return Err("a new itinerary must be created --from-template".into()),
+    };
+    let template = if template.is_file() {
+        template
+    } else {
+        get_app_template_path().join(template).with_extension("yml")
+    };
+    let template = template::from_file(&template)?;
+    let start = match start {
+        Some(start) => start,
+        None => parse_date(&prompt("start date (YYYY-MM-DD)", None)?)?,
+    };
+    let mut values: HashMap<String, String> = values.into_iter().collect();
+    for placeholder in template.placeholders() {
+        if !values.contains_key(placeholder.name())
+            && (interactive || placeholder.default().is_none())
+        {
+            let value = prompt(
+                placeholder
+                    .description()
+                    .unwrap_or_else(|| placeholder.name()),
+                placeholder.default(),
+            )?;
+            let _ = values.insert(placeholder.name().clone(), value);
+        }
+    }
+    let mut itinerary = template.instantiate(start, &values)?;
+    for diagnostic in itinerary.validate() {
+        if diagnostic.severity() != Severity::Info {
+            eprintln!("{}", diagnostic);
+        }
+    }
+    itinerary.metadata_mut().touch(Utc::now());
+    to_file(&itinerary, &file)?;
     Ok(())
 }
 
 async fn cmd_edit_itinerary(
-    _file: PathBuf,
+    file: PathBuf,
     _interactive: bool,
+    changes: EditChanges,
+    _app_config: AppConfig,
+) -> Result<(), Box<dyn Error>> {
+    let mut commands = changes.commands()?;
+    let command = match commands.len() {
+        0 => return Ok(()),
+        1 => commands.remove(0),
+        _ => EditCommand::Batch { commands },
+    };
+    let mut itinerary = load_itinerary(&file)?;
+    let mut journal = Journal::open(&file)?;
+    let entry = journal.edit(&mut itinerary, command, &command_origin(), Utc::now())?;
+    info!("{}", entry);
+    itinerary.metadata_mut().touch(Utc::now());
+    journal.save(&itinerary)?;
+    to_file(&itinerary, &file)?;
+    Ok(())
+}
+
+async fn cmd_undo_redo(
+    file: PathBuf,
+    redo: bool,
     _app_config: AppConfig,
 ) -> Result<(), Box<dyn Error>> {
+    let mut itinerary = load_itinerary(&file)?;
+    let mut journal = Journal::open(&file)?;
+    let entry = if redo {
+        journal.redo(&mut itinerary, &command_origin(), Utc::now())?
+    } else {
+        journal.undo(&mut itinerary, &command_origin(), Utc::now())?
+    };
+    match entry {
+        Some(entry) => println!("{}", entry),
+        None => {
+            eprintln!("Nothing to {}", if redo { "redo" } else { "undo" });
+            return Ok(());
+        }
+    }
+    itinerary.metadata_mut().touch(Utc::now());
+    journal.save(&itinerary)?;
+    to_file(&itinerary, &file)?;
+    Ok(())
+}
+
+async fn cmd_show_history(file: PathBuf, _app_config: AppConfig) -> Result<(), Box<dyn Error>> {
+    let journal = Journal::open(&file)?;
+    for entry in journal.entries() {
+        println!("{}", entry);
+    }
+    Ok(())
+}
+
+async fn cmd_library(cmd: LibraryCommand, library_dir: &Path) -> Result<(), Box<dyn Error>> {
+    let mut library = Library::open(library_dir)?;
+    match cmd {
+        LibraryCommand::List { traveler } => {
+            for entry in library.entries() {
+                if traveler
+                    .as_ref()
+                    .map(|name| entry.has_traveler(name))
+                    .unwrap_or(true)
+                {
+                    println!(
+                        "{:<12} {:<30} {} - {}  {}",
+                        entry.identifier(),
+                        entry.name(),
+                        or_unknown(entry.start_date()),
+                        or_unknown(entry.end_date()),
+                        entry
+                            .travelers()
+                            .cloned()
+                            .collect::<Vec<String>>()
+                            .join(", ")
+                    );
+                }
+            }
+        }
+        LibraryCommand::Add { file } => {
+            let entry = library.add(&file)?;
+            println!("Added {} as {}", entry.identifier(), entry.file_name());
+        }
+        LibraryCommand::Rebuild => library.rebuild()?,
+    }
+    for group in library.duplicates() {
+        eprintln!(
+            "Warning: these files appear to be the same itinerary: {}",
+            group
+                .iter()
+                .map(|entry| entry.file_name().clone())
+                .collect::<Vec<String>>()
+                .join(", ")
+        );
+    }
     Ok(())
 }
 
 async fn cmd_display_itinerary(
     file: PathBuf,
-    _update_flights: bool,
-    _app_config: AppConfig,
+    format: DisplayFormat,
+    options: DisplayOptions,
+    update_flights: bool,
+    mut app_config: AppConfig,
 ) -> Result<(), Box<dyn Error>> {
     if !file.is_file() {
         eprintln!("Error: file '{:?}' does not exist", file);
     }
-    let file = File::open(&file)?;
-    let itinerary = from_reader(file)?;
-    display_itinerary(&itinerary, DisplayFormat::default())?;
+    let mut itinerary = load_itinerary(&file)?;
+    if update_flights {
+        for flight in itinerary.flights_mut() {
+            let request = flight_schedule_request(
+                flight.carrier().clone(),
+                *flight.number(),
+                flight.departure_date_time().date(),
+                None,
+            )?;
+            let response = fetch_flight_schedule(&mut app_config, &request).await?;
+            let schedule = response.flights().next();
+            if let Some(schedule) = schedule {
+                schedule.update_flight(flight);
+            }
+        }
+    }
+    display_itinerary_with(&itinerary, format, &options)?;
+    Ok(())
+}
+
+async fn cmd_open_attachment(
+    file: PathBuf,
+    item: Option<String>,
+    attachment: Option<PathBuf>,
+    _app_config: AppConfig,
+) -> Result<(), Box<dyn Error>> {
+    let itinerary = load_itinerary(&file)?;
+    let annotations = match &item {
+        Some(id) => itinerary
+            .item(&parse_item_id(id)?)
+            .ok_or_else(|| format!("no item with identifier '{}'", id))?
+            .common()
+            .annotations(),
+        None => itinerary.annotations(),
+    };
+    let found = match &attachment {
+        Some(path) => annotations.attachments().find(|v| v.path() == path),
+        None => annotations.attachments().next(),
+    };
+    let found = found.ok_or("no such attachment")?;
+    if !found.verify(&file)? {
+        eprintln!(
+            "Warning: the content of '{}' has changed since it was attached",
+            found.path().display()
+        );
+    }
+    let path = found.resolve(&file)?;
+    info!("opening attachment {:?}", path);
+    let status = if cfg!(target_os = "macos") {
+        ProcessCommand::new("open").arg(&path).status()?
+    } else if cfg!(target_os = "windows") {
+        // explorer takes the path as a single argument, unlike `cmd /C start` which re-parses it.
+        ProcessCommand::new("explorer").arg(&path).status()?
+    } else {
+        ProcessCommand::new("xdg-open").arg(&path).status()?
+    };
+    if !status.success() {
+        eprintln!("Error: could not open '{}'", path.display());
+    }
     Ok(())
 }
 
+///
+/// Read an itinerary, reporting any problems `Itinerary::validate` finds with it. Information is
+/// only logged, as it is not something the user needs to fix.
+///
+fn load_itinerary(file: &Path) -> Result<Itinerary, Box<dyn Error>> {
+    let itinerary = from_reader(File::open(file)?)?;
+    for diagnostic in itinerary.validate() {
+        match diagnostic.severity() {
+            Severity::Info => info!("{}", diagnostic),
+            _ => eprintln!("{}", diagnostic),
+        }
+    }
+    Ok(itinerary)
+}
+
+///
+/// Commands accept either the path of an itinerary file, or the identifier of an itinerary in the
+/// library; an existing file takes precedence.
+///
+fn resolve_itinerary(file: PathBuf, library_dir: &Path) -> Result<PathBuf, Box<dyn Error>> {
+    if file.is_file() {
+        return Ok(file);
+    }
+    let library = Library::open(library_dir)?;
+    let identifier = file.to_string_lossy();
+    match library.find(&identifier)? {
+        Some(entry) => Ok(library.path_of(entry)),
+        None => Err(format!(
+            "no itinerary file, or library itinerary, named '{}'",
+            identifier
+        )
+        .into()),
+    }
+}
+
+fn or_unknown<T: ToString>(value: Option<T>) -> String {
+    value
+        .map(|v| v.to_string())
+        .unwrap_or_else(|| "?".to_string())
+}
+
+///
+/// The command line of this process, recorded in the journal as the origin of a change.
+///
+fn command_origin() -> String {
+    std::env::args().collect::<Vec<String>>().join(" ")
+}
+
+///
+/// Ask the user for a value; an empty response selects the default, if there is one.
+///
+fn prompt(label: &str, default: Option<&String>) -> Result<String, Box<dyn Error>> {
+    loop {
+        match default {
+            Some(default) => print!("{} [{}]: ", label, default),
+            None => print!("{}: ", label),
+        }
+        std::io::stdout().flush()?;
+        let mut value = String::new();
+        if std::io::stdin().read_line(&mut value)? == 0 {
+            return Err(format!("no value given for '{}'", label).into());
+        }
+        match (value.trim(), default) {
+            ("", Some(default)) => return Ok(default.clone()),
+            ("", None) => continue,
+            (value, _) => return Ok(value.to_string()),
+        }
+    }
+}
+
+fn parse_date(s: &str) -> Result<Date, String> {
+    Date::parse_from_str(s.trim(), "%Y-%m-%d").map_err(|_| format!("invalid date '{}'", s))
+}
+
+fn parse_placeholder(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((name, value)) => Ok((name.trim().to_string(), value.to_string())),
+        None => Err(format!("expecting NAME=VALUE, not '{}'", s)),
+    }
+}
+
+fn parse_item_id(s: &str) -> Result<ItemId, String> {
+    ItemId::from_str(s).map_err(|_| format!("invalid item identifier '{}'", s))
+}
+
+fn parse_display_format(s: &str) -> Result<DisplayFormat, String> {
+    DisplayFormat::from_str(s).map_err(|_| format!("invalid display format '{}'", s))
+}
+
+fn parse_item_kind(s: &str) -> Result<ItemKind, String> {
+    ItemKind::from_str(s).map_err(|_| format!("invalid item kind '{}'", s))
+}
+
+fn parse_item_status(s: &str) -> Result<ItemStatus, String> {
+    ItemStatus::from_str(s).map_err(|_| format!("invalid item status '{}'", s))
+}
+
 fn init_tracing(level: i8) {
     use terminal_travel::config::Stage;
     use tracing_subscriber::filter::LevelFilter;
@@ -151,7 +670,7 @@ fn init_tracing(level: i8) {
         .into(),
     );
 
-    let file_appender = tracing_appender::rolling::daily(".", &format!("{}.log", APP_CONFIG_NAME));
+    let file_appender = tracing_appender::rolling::daily(".", format!("{}.log", APP_CONFIG_NAME));
 
     match get_stage() {
         Stage::Development => tracing_subscriber::fmt()
@@ -187,7 +706,7 @@ fn init_tracing(level: i8) {
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
     human_panic::setup_panic!();
-    
+
     let cmd_line = CommandLine::from_args();
     debug!("{:?}", cmd_line);
 
@@ -200,17 +719,62 @@ async fn main() -> Result<(), Box<dyn Error>> {
 
     let app_config = get_app_config_from(&config_file)?;
 
+    let library_dir = cmd_line.library_dir.unwrap_or_else(get_app_library_path);
+
     match cmd_line.cmd {
-        Command::New { interactive, file } => {
-            cmd_new_itinerary(file, interactive, app_config).await?
+        Command::New {
+            interactive,
+            from_template,
+            start,
+            set,
+            file,
+        } => cmd_new_itinerary(file, interactive, from_template, start, set, app_config).await?,
+        Command::Edit {
+            interactive,
+            changes,
+            file,
+        } => {
+            let file = resolve_itinerary(file, &library_dir)?;
+            cmd_edit_itinerary(file, interactive, changes, app_config).await?
+        }
+        Command::Undo { file } => {
+            let file = resolve_itinerary(file, &library_dir)?;
+            cmd_undo_redo(file, false, app_config).await?
         }
-        Command::Edit { interactive, file } => {
-            cmd_edit_itinerary(file, interactive, app_config).await?
+        Command::Redo { file } => {
+            let file = resolve_itinerary(file, &library_dir)?;
+            cmd_undo_redo(file, true, app_config).await?
         }
+        Command::History { file } => {
+            let file = resolve_itinerary(file, &library_dir)?;
+            cmd_show_history(file, app_config).await?
+        }
+        Command::Library { cmd } => cmd_library(cmd, &library_dir).await?,
         Command::Display {
             update_flights,
+            format,
+            traveler,
+            notes,
+            filter,
+            file,
+        } => {
+            let file = resolve_itinerary(file, &library_dir)?;
+            let mut options = DisplayOptions::default()
+                .with_notes(notes)
+                .with_filter(filter.item_filter());
+            if let Some(traveler) = traveler {
+                options = options.for_traveler(&traveler);
+            }
+            cmd_display_itinerary(file, format, options, update_flights, app_config).await?
+        }
+        Command::Open {
+            item,
             file,
-        } => cmd_display_itinerary(file, update_flights, app_config).await?,
+            attachment,
+        } => {
+            let file = resolve_itinerary(file, &library_dir)?;
+            cmd_open_attachment(file, item, attachment, app_config).await?
+        }
     }
 
     Ok(())